@@ -33,13 +33,16 @@ use soroban_sdk::{xdr::ToXdr, Address, Bytes, BytesN, Env};
 /// - Salt length capped at 1024 bytes to prevent DoS via excessive hashing
 /// - Uses XDR serialization for Address to ensure canonical representation
 /// - Big-endian encoding for amount ensures consistent byte ordering
+/// - Verification compares the recomputed commitment with [`commitments_equal`],
+///   which examines all 32 bytes regardless of where the first mismatch is
 ///
 /// ## Limitations
 ///
 /// - No formal cryptographic proof provided in-code (empirical testing only)
 /// - Relies on SHA-256 security assumptions (pre-image resistance, collision resistance)
 /// - Salt must be kept secret by the user; if leaked, privacy is compromised
-/// - Does not protect against timing attacks (constant-time operations not guaranteed)
+/// - Hashing runs in the host and is not guaranteed to be constant-time; only the
+///   final comparison is (see below)
 ///
 /// ## Implementation Details
 ///
@@ -84,6 +87,19 @@ pub fn create_amount_commitment(
     Ok(env.crypto().sha256(&payload).into())
 }
 
+/// Compare two commitments in constant time.
+///
+/// XORs every byte pair and ORs the differences together, so the running time does
+/// not depend on how many leading bytes match.
+pub fn commitments_equal(a: &BytesN<32>, b: &BytesN<32>) -> bool {
+    let (a, b) = (a.to_array(), b.to_array());
+    let mut diff = 0u8;
+    for i in 0..32 {
+        diff |= a[i] ^ b[i];
+    }
+    diff == 0
+}
+
 pub fn verify_amount_commitment(
     env: &Env,
     commitment: BytesN<32>,
@@ -91,8 +107,116 @@ pub fn verify_amount_commitment(
     amount: i128,
     salt: Bytes,
 ) -> bool {
-    match create_amount_commitment(env, owner, amount, salt) {
-        Ok(hash) => hash == commitment,
+    verify_amount_commitment_detailed(env, commitment, owner, amount, salt).unwrap_or(false)
+}
+
+/// Like [`verify_amount_commitment`], but reports invalid inputs as errors
+/// instead of folding them into `false`.
+///
+/// Returns `Ok(false)` only for well-formed inputs that do not match.
+///
+/// # Errors
+/// - [`QuickexError::InvalidAmount`] – amount < 0.
+/// - [`QuickexError::InvalidSalt`] – salt > 1024 bytes.
+pub fn verify_amount_commitment_detailed(
+    env: &Env,
+    commitment: BytesN<32>,
+    owner: Address,
+    amount: i128,
+    salt: Bytes,
+) -> Result<bool, QuickexError> {
+    let expected = create_amount_commitment(env, owner, amount, salt)?;
+    Ok(commitments_equal(&expected, &commitment))
+}
+
+/// Create a commitment that also binds a per-owner deposit nonce.
+///
+/// Commitment = SHA256(XDR(owner) || BE(amount) || salt || BE(nonce))
+/// where BE(nonce) is the 8-byte big-endian representation of the `u64` nonce.
+/// Identical `(owner, amount, salt)` inputs yield distinct commitments for
+/// distinct nonces. The 1024-byte cap applies to `salt` alone.
+pub fn create_nonced_commitment(
+    env: &Env,
+    owner: Address,
+    amount: i128,
+    salt: Bytes,
+    nonce: u64,
+) -> Result<BytesN<32>, QuickexError> {
+    if salt.len() > 1024 {
+        return Err(QuickexError::InvalidSalt);
+    }
+
+    let mut salted = salt;
+    salted.extend_from_array(&nonce.to_be_bytes());
+    create_amount_commitment(env, owner, amount, salted)
+}
+
+/// Domain tag prefixed to range commitments so they can never equal an amount
+/// commitment, whose payload starts with the owner's XDR.
+const RANGE_COMMITMENT_TAG: &[u8] = b"range";
+
+/// Create a commitment to an amount range `[lower, upper]` instead of an exact amount.
+///
+/// Commitment = SHA256("range" || XDR(owner) || BE(lower) || BE(upper) || salt)
+///
+/// **Not zero-knowledge.** This is a bucketed commitment: verifying it requires
+/// revealing both bounds and the amount (see [`verify_range_commitment`]). It only
+/// lets an owner commit to a bucket up front and later show the amount fell in it.
+///
+/// # Errors
+/// - [`QuickexError::InvalidAmount`] – `lower` < 0 or `upper` < `lower`.
+/// - [`QuickexError::InvalidSalt`] – salt > 1024 bytes.
+pub fn create_range_commitment(
+    env: &Env,
+    owner: Address,
+    lower: i128,
+    upper: i128,
+    salt: Bytes,
+) -> Result<BytesN<32>, QuickexError> {
+    if lower < 0 || upper < lower {
+        return Err(QuickexError::InvalidAmount);
+    }
+
+    if salt.len() > 1024 {
+        return Err(QuickexError::InvalidSalt);
+    }
+
+    let mut payload = Bytes::from_slice(env, RANGE_COMMITMENT_TAG);
+    payload.append(&owner.to_xdr(env));
+    payload.extend_from_array(&lower.to_be_bytes());
+    payload.extend_from_array(&upper.to_be_bytes());
+    payload.append(&salt);
+
+    Ok(env.crypto().sha256(&payload).into())
+}
+
+/// Check that `commitment` opens to `[lower, upper]` for `owner` and `salt`, and
+/// that `amount` lies within that range (inclusive).
+///
+/// Returns `false` for invalid inputs, a mismatched opening, or an out-of-range amount.
+pub fn verify_range_commitment(
+    env: &Env,
+    commitment: BytesN<32>,
+    owner: Address,
+    lower: i128,
+    upper: i128,
+    salt: Bytes,
+    amount: i128,
+) -> bool {
+    match create_range_commitment(env, owner, lower, upper, salt) {
+        Ok(expected) => {
+            commitments_equal(&expected, &commitment) && (lower..=upper).contains(&amount)
+        }
         Err(_) => false,
     }
 }
+
+/// Hash a salt for use with salt-hash escrows: `SHA256(salt)`.
+///
+/// Applies the same 1024-byte salt cap as [`create_amount_commitment`].
+pub fn hash_salt(env: &Env, salt: &Bytes) -> Result<BytesN<32>, QuickexError> {
+    if salt.len() > 1024 {
+        return Err(QuickexError::InvalidSalt);
+    }
+    Ok(env.crypto().sha256(salt).into())
+}
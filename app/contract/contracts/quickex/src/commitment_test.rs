@@ -356,3 +356,125 @@ fn test_commitment_property_verification_consistency() {
         assert!(!client.verify_amount_commitment(&commitment, &wrong_owner, amount, &salt));
     }
 }
+
+// ============================================================================
+// Detailed Verification
+// ============================================================================
+
+#[test]
+fn test_verify_detailed_match_and_mismatch() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+    let amount = 1_000i128;
+    let salt = Bytes::from_slice(&env, b"detailed");
+    let commitment = client.create_amount_commitment(&owner, &amount, &salt);
+
+    assert!(client.verify_commitment_detailed(&commitment, &owner, &amount, &salt));
+
+    let wrong_salt = Bytes::from_slice(&env, b"wrong");
+    assert!(!client.verify_commitment_detailed(&commitment, &owner, &amount, &wrong_salt));
+    assert!(!client.verify_commitment_detailed(&commitment, &owner, &(amount + 1), &salt));
+}
+
+#[test]
+fn test_verify_detailed_reports_invalid_amount() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"detailed");
+    let commitment = client.create_amount_commitment(&owner, &1_000, &salt);
+
+    let result = client.try_verify_commitment_detailed(&commitment, &owner, &-1, &salt);
+    assert_eq!(result, Err(Ok(QuickexError::InvalidAmount)));
+    assert!(!client.verify_amount_commitment(&commitment, &owner, &-1, &salt));
+}
+
+#[test]
+fn test_verify_detailed_reports_invalid_salt() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+    let amount = 1_000i128;
+    let commitment = client.create_amount_commitment(&owner, &amount, &Bytes::new(&env));
+
+    let mut large_salt = Bytes::new(&env);
+    for _ in 0..1025 {
+        large_salt.push_back(0xFF);
+    }
+
+    let result = client.try_verify_commitment_detailed(&commitment, &owner, &amount, &large_salt);
+    assert_eq!(result, Err(Ok(QuickexError::InvalidSalt)));
+    assert!(!client.verify_amount_commitment(&commitment, &owner, &amount, &large_salt));
+}
+
+// ============================================================================
+// Range commitments
+// ============================================================================
+
+#[test]
+fn test_range_commitment_verifies_in_range_amount() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"range");
+    let commitment = client.create_range_commitment(&owner, &100, &200, &salt);
+
+    assert!(client.verify_range_commitment(&commitment, &owner, &100, &200, &salt, &100));
+    assert!(client.verify_range_commitment(&commitment, &owner, &100, &200, &salt, &150));
+    assert!(client.verify_range_commitment(&commitment, &owner, &100, &200, &salt, &200));
+}
+
+#[test]
+fn test_range_commitment_rejects_out_of_range_or_wrong_bounds() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"range");
+    let commitment = client.create_range_commitment(&owner, &100, &200, &salt);
+
+    assert!(!client.verify_range_commitment(&commitment, &owner, &100, &200, &salt, &99));
+    assert!(!client.verify_range_commitment(&commitment, &owner, &100, &200, &salt, &201));
+    // Claiming wider bounds does not open the commitment.
+    assert!(!client.verify_range_commitment(&commitment, &owner, &0, &1_000, &salt, &150));
+
+    let exact = client.create_amount_commitment(&owner, &150, &salt);
+    assert_ne!(
+        exact,
+        client.create_range_commitment(&owner, &150, &150, &salt)
+    );
+}
+
+#[test]
+fn test_range_commitment_invalid_bounds() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"range");
+
+    let result = client.try_create_range_commitment(&owner, &200, &100, &salt);
+    assert_eq!(result, Err(Ok(QuickexError::InvalidAmount)));
+    let result = client.try_create_range_commitment(&owner, &-1, &100, &salt);
+    assert_eq!(result, Err(Ok(QuickexError::InvalidAmount)));
+}
+
+// ============================================================================
+// Constant-time comparison
+// ============================================================================
+
+#[test]
+fn test_commitments_equal_matches_byte_equality() {
+    use crate::commitment::commitments_equal;
+    use soroban_sdk::BytesN;
+
+    let env = Env::default();
+    let a = BytesN::from_array(&env, &[7u8; 32]);
+    assert!(commitments_equal(&a, &a.clone()));
+
+    // A mismatch anywhere, including the first and last byte, is detected.
+    for i in [0usize, 15, 31] {
+        let mut bytes = [7u8; 32];
+        bytes[i] ^= 0x80;
+        let b = BytesN::from_array(&env, &bytes);
+        assert!(!commitments_equal(&a, &b));
+        assert!(!commitments_equal(&b, &a));
+    }
+    assert!(!commitments_equal(
+        &a,
+        &BytesN::from_array(&env, &[0u8; 32])
+    ));
+}
@@ -0,0 +1,23 @@
+//! Client interface for external DEX contracts used by `withdraw_and_swap`.
+
+use soroban_sdk::{contractclient, Address, Env};
+
+/// Swap interface a DEX must expose to be used with `withdraw_and_swap`.
+///
+/// QuickEx pre-authorizes the DEX to pull `amount_in` of `token_in` from `from`
+/// (the QuickEx contract) with a `transfer` call.
+#[allow(dead_code)]
+#[contractclient(name = "DexClient")]
+pub trait Dex {
+    /// Swap `amount_in` of `token_in` held by `from` into `token_out`, sending the
+    /// proceeds to `to`. Returns the amount of `token_out` delivered.
+    fn swap(
+        env: Env,
+        from: Address,
+        token_in: Address,
+        token_out: Address,
+        amount_in: i128,
+        min_out: i128,
+        to: Address,
+    ) -> i128;
+}
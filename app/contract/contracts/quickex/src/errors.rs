@@ -1,4 +1,4 @@
-use soroban_sdk::contracterror;
+use soroban_sdk::{contracterror, Env, Symbol, Vec};
 
 /// Canonical contract error codes.
 ///
@@ -15,6 +15,14 @@ pub enum QuickexError {
     InvalidAmount = 100,
     InvalidSalt = 101,
     InvalidPrivacyLevel = 102,
+    /// Batch input exceeds the maximum number of items per call.
+    BatchTooLarge = 103,
+    /// Installment schedule is empty, too long, or not strictly ascending, a cliff
+    /// does not end before the escrow's timeout, or a pool release time is not in
+    /// the future.
+    InvalidSchedule = 104,
+    /// Fee rate exceeds 10 000 basis points.
+    InvalidFee = 105,
     // Auth/admin failures (200-299)
     Unauthorized = 200,
     AlreadyInitialized = 201,
@@ -32,6 +40,113 @@ pub enum QuickexError {
     EscrowNotExpired = 308,
     /// Caller is not the original owner of the escrow.
     InvalidOwner = 309,
+    /// Destination address is not permitted for this escrow.
+    RecipientNotAllowed = 310,
+    /// Token supplied by the caller does not match the escrow's token.
+    TokenMismatch = 311,
+    /// Not enough ledgers have closed since the deposit to allow withdrawal.
+    InsufficientConfirmations = 312,
+    /// Escrow forwards to a designated recipient on expiry and cannot be refunded.
+    RefundDisabled = 313,
+    /// Escrow has no on-expiry recipient to forward to.
+    NoExpiryRecipient = 314,
+    /// No unclaimed installment has reached its unlock time.
+    NoMaturedInstallments = 315,
+    /// Escrow is still pending; only spent or refunded escrows can be purged.
+    EscrowNotTerminal = 316,
+    /// Signed withdrawal nonce is not the recipient's current nonce.
+    StaleNonce = 317,
+    /// Owner has already granted the maximum number of viewers.
+    TooManyViewers = 318,
+    /// Escrow has used all of its permitted expiry extensions.
+    ExtensionLimitReached = 319,
+    /// Escrow has no expiry, so there is nothing to extend.
+    EscrowNotExpirable = 320,
+    /// DEX swap returned less than the requested minimum output.
+    SlippageExceeded = 321,
+    /// Escrow's cliff has not passed; withdrawal is not yet possible.
+    EscrowNotYetClaimable = 322,
+    /// Contributor holds no share of the pool.
+    NoPoolShare = 323,
+    /// Partial withdrawal requests more than the escrow's remaining balance.
+    PartialWithdrawExceedsBalance = 324,
+    /// Chained escrow's prerequisite has not been spent yet.
+    PrerequisiteNotMet = 325,
+    /// Activity snapshot requested before the snapshot interval has elapsed.
+    SnapshotTooSoon = 326,
+    /// Token is not on the admin's allow-list of deposit tokens.
+    TokenNotAllowed = 327,
+    /// Operation is only allowed while the contract is paused.
+    ContractNotPaused = 328,
+    /// Escrow is not stuck: it expires, is settled, or is younger than the stuck age.
+    EscrowNotStuck = 329,
+    /// DEX swap did not take exactly the net amount of the escrowed token.
+    SwapInputMismatch = 330,
     // Internal/unexpected conditions (900-999)
     InternalError = 900,
 }
+
+/// Every error paired with its symbolic name, in code order.
+///
+/// Add new variants here as well as to [`QuickexError`]; `error_catalog`
+/// exposes this table on-chain.
+const CATALOG: &[(QuickexError, &str)] = &[
+    (QuickexError::InvalidAmount, "InvalidAmount"),
+    (QuickexError::InvalidSalt, "InvalidSalt"),
+    (QuickexError::InvalidPrivacyLevel, "InvalidPrivacyLevel"),
+    (QuickexError::BatchTooLarge, "BatchTooLarge"),
+    (QuickexError::InvalidSchedule, "InvalidSchedule"),
+    (QuickexError::InvalidFee, "InvalidFee"),
+    (QuickexError::Unauthorized, "Unauthorized"),
+    (QuickexError::AlreadyInitialized, "AlreadyInitialized"),
+    (QuickexError::ContractPaused, "ContractPaused"),
+    (QuickexError::PrivacyAlreadySet, "PrivacyAlreadySet"),
+    (QuickexError::CommitmentNotFound, "CommitmentNotFound"),
+    (
+        QuickexError::CommitmentAlreadyExists,
+        "CommitmentAlreadyExists",
+    ),
+    (QuickexError::AlreadySpent, "AlreadySpent"),
+    (QuickexError::InvalidCommitment, "InvalidCommitment"),
+    (QuickexError::CommitmentMismatch, "CommitmentMismatch"),
+    (QuickexError::EscrowExpired, "EscrowExpired"),
+    (QuickexError::EscrowNotExpired, "EscrowNotExpired"),
+    (QuickexError::InvalidOwner, "InvalidOwner"),
+    (QuickexError::RecipientNotAllowed, "RecipientNotAllowed"),
+    (QuickexError::TokenMismatch, "TokenMismatch"),
+    (
+        QuickexError::InsufficientConfirmations,
+        "InsufficientConfirmations",
+    ),
+    (QuickexError::RefundDisabled, "RefundDisabled"),
+    (QuickexError::NoExpiryRecipient, "NoExpiryRecipient"),
+    (QuickexError::NoMaturedInstallments, "NoMaturedInstallments"),
+    (QuickexError::EscrowNotTerminal, "EscrowNotTerminal"),
+    (QuickexError::StaleNonce, "StaleNonce"),
+    (QuickexError::TooManyViewers, "TooManyViewers"),
+    (QuickexError::ExtensionLimitReached, "ExtensionLimitReached"),
+    (QuickexError::EscrowNotExpirable, "EscrowNotExpirable"),
+    (QuickexError::SlippageExceeded, "SlippageExceeded"),
+    (QuickexError::EscrowNotYetClaimable, "EscrowNotYetClaimable"),
+    (QuickexError::NoPoolShare, "NoPoolShare"),
+    (
+        QuickexError::PartialWithdrawExceedsBalance,
+        "PartialWithdrawExceedsBalance",
+    ),
+    (QuickexError::PrerequisiteNotMet, "PrerequisiteNotMet"),
+    (QuickexError::SnapshotTooSoon, "SnapshotTooSoon"),
+    (QuickexError::TokenNotAllowed, "TokenNotAllowed"),
+    (QuickexError::ContractNotPaused, "ContractNotPaused"),
+    (QuickexError::EscrowNotStuck, "EscrowNotStuck"),
+    (QuickexError::SwapInputMismatch, "SwapInputMismatch"),
+    (QuickexError::InternalError, "InternalError"),
+];
+
+/// Build the `(code, name)` catalog of all contract errors.
+pub fn error_catalog(env: &Env) -> Vec<(u32, Symbol)> {
+    let mut catalog = Vec::new(env);
+    for (error, name) in CATALOG {
+        catalog.push_back((*error as u32, Symbol::new(env, name)));
+    }
+    catalog
+}
@@ -4,24 +4,60 @@
 //!
 //! ```text
 //! [*] --> Pending  : deposit() / deposit_with_commitment()
-//! Pending --> Spent    : withdraw(proof)  [current_time < expires_at OR no expiry]
-//! Pending --> Refunded : refund(owner)    [current_time >= expires_at]
+//! Pending --> Spent    : withdraw(proof)  [now < expires_at, or no expiry]
+//! Pending --> Expired  : mark_expired()   [now >= expires_at]
+//! Pending --> Refunded : refund(owner)    [now >= expires_at]
+//! Expired --> Refunded : refund(owner)
+//! Pending --> Refunded : rescue_stuck(admin)  [no expiry, stuck age reached, paused]
+//! Spent    --> Pending : re-deposit  [terminal re-deposit enabled]
+//! Refunded --> Pending : re-deposit  [terminal re-deposit enabled]
 //! ```
 //!
+//! Refunds from `Pending` or `Expired` also cover `sweep_expired_forward` (to the
+//! escrow's `on_expiry_recipient`) and the admin's `sweep_expired` (once the sweep
+//! grace period has passed). `force_expire` keeps the escrow `Pending` but sets
+//! `expires_at` to now, so it becomes refundable at once.
+//!
 //! Guard rails:
-//! - `withdraw` fails with [`EscrowExpired`] if `expires_at > 0` and `now >= expires_at`.
-//! - `refund` fails with [`EscrowNotExpired`] if `expires_at == 0` or `now < expires_at`.
-//! - Both fail with [`AlreadySpent`] if status is not `Pending`.
+//! - `withdraw` fails with [`EscrowExpired`] if the escrow is `Expired`, or if
+//!   `expires_at > 0` and `now >= expires_at`.
+//! - `refund` fails with [`EscrowNotExpired`] if a `Pending` escrow has
+//!   `expires_at == 0` or `now < expires_at`.
+//! - Both fail with [`AlreadySpent`] if the escrow is `Spent` or `Refunded`.
 //! - `refund` fails with [`InvalidOwner`] if caller ≠ `entry.owner`.
+//! - New escrows fail with [`CommitmentAlreadyExists`] if an escrow is stored under
+//!   the commitment, unless terminal re-deposit is on and it is `Spent` or
+//!   `Refunded`.
+//! - With the strict owner check enabled, `withdraw` fails with [`InvalidOwner`]
+//!   if the recipient ≠ `entry.owner`.
+//! - With auto-pause enabled, any payout finding the contract's token balance below
+//!   the tracked TVL pauses the contract and returns without moving funds.
 
-use soroban_sdk::{token, Address, Bytes, BytesN, Env};
+use soroban_sdk::{
+    auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
+    token, vec, Address, Bytes, BytesN, Env, IntoVal, Symbol, Vec,
+};
 
 use crate::{
     commitment,
+    dex::DexClient,
     errors::QuickexError,
-    events,
-    storage::{get_escrow, has_escrow, put_escrow},
-    types::{EscrowEntry, EscrowStatus},
+    events, fees, privacy,
+    storage::{
+        add_activity_snapshot, add_owner_escrow, adjust_token_tvl, decrement_token_escrow_count,
+        get_activity_snapshots, get_escrow, get_escrow_commitment_by_id, get_escrow_counter,
+        get_known_tokens, get_max_batch_size, get_owner_escrows, get_owner_escrows_created,
+        get_placeholder_id_count, get_snapshot_interval, get_status_counts, get_stuck_age,
+        get_sweep_grace_period, get_token_tvl, increment_escrow_counter,
+        increment_token_escrow_count, is_auto_pause, is_refund_recipient_allowed, is_salt_tracking,
+        is_salt_used, is_strict_owner_check, is_terminal_redeposit, is_token_allowed,
+        mark_salt_used, put_escrow, remove_escrow, remove_escrow_commitment_by_id,
+        remove_owner_escrow, set_escrow_commitment_by_id, set_paused, take_owner_nonce,
+        take_withdraw_nonce, update_status_counts,
+    },
+    types::{
+        ActivitySnapshot, CommitmentOpening, EscrowEntry, EscrowOptions, EscrowStatus, OwnerStats,
+    },
 };
 
 // ---------------------------------------------------------------------------
@@ -35,35 +71,215 @@ fn is_expired(env: &Env, entry: &EscrowEntry) -> bool {
     entry.expires_at > 0 && env.ledger().timestamp() >= entry.expires_at
 }
 
+/// Expiry applied by [`gift_deposit`]: 30 days, after which an unclaimed gift can
+/// be refunded to the giver.
+pub const GIFT_TIMEOUT_SECS: u64 = 30 * 24 * 60 * 60;
+
+/// Default maximum number of items accepted by batch operations, used until the
+/// admin configures another with `set_max_batch_size`.
+pub const MAX_BATCH_SIZE: u32 = 50;
+
+/// Maximum number of items accepted by batch operations and returned per page.
+pub fn max_batch_size(env: &Env) -> u32 {
+    get_max_batch_size(env).unwrap_or(MAX_BATCH_SIZE)
+}
+
+/// Ensure a batch of `len` items is within [`max_batch_size`].
+///
+/// # Errors
+/// - [`BatchTooLarge`] – `len` exceeds the configured maximum.
+pub fn check_batch_size(env: &Env, len: u32) -> Result<(), QuickexError> {
+    if len > max_batch_size(env) {
+        return Err(QuickexError::BatchTooLarge);
+    }
+    Ok(())
+}
+
+/// Status of an escrow as of the current ledger time.
+///
+/// A `Pending` escrow that has passed its expiry is reported as `Expired`, without
+/// writing the change to storage.
+fn effective_status(env: &Env, entry: &EscrowEntry) -> EscrowStatus {
+    if entry.status == EscrowStatus::Pending && is_expired(env, entry) {
+        EscrowStatus::Expired
+    } else {
+        entry.status
+    }
+}
+
+/// Ensure an escrow is in a state from which its owner may refund it.
+///
+/// `Pending` escrows must have reached their expiry. `Expired` escrows are
+/// already past expiry by definition and are refundable as-is.
+///
+/// # Errors
+/// - [`AlreadySpent`] – escrow already in a terminal state.
+/// - [`EscrowNotExpired`] – escrow has no timeout or timeout not yet reached.
+fn check_refundable(env: &Env, entry: &EscrowEntry) -> Result<(), QuickexError> {
+    match entry.status {
+        EscrowStatus::Pending if !is_expired(env, entry) => Err(QuickexError::EscrowNotExpired),
+        EscrowStatus::Pending | EscrowStatus::Expired => Ok(()),
+        EscrowStatus::Spent | EscrowStatus::Refunded => Err(QuickexError::AlreadySpent),
+    }
+}
+
+/// Check that refunds may be paid to `to` under the refund allow-list.
+///
+/// # Errors
+/// - [`RecipientNotAllowed`] – the allow-list is non-empty and `to` is not on it.
+fn check_refund_recipient(env: &Env, to: &Address) -> Result<(), QuickexError> {
+    if !is_refund_recipient_allowed(env, to) {
+        return Err(QuickexError::RecipientNotAllowed);
+    }
+    Ok(())
+}
+
+/// Validate deposit-time options.
+///
+/// # Errors
+/// - [`InvalidSchedule`] – installment schedule is empty, longer than
+///   [`MAX_BATCH_SIZE`], or not strictly ascending, or the cliff does not end
+///   before the timeout.
+fn check_options(options: &EscrowOptions, timeout_secs: u64) -> Result<(), QuickexError> {
+    if timeout_secs > 0 && options.cliff_secs >= timeout_secs {
+        return Err(QuickexError::InvalidSchedule);
+    }
+    if let Some(schedule) = &options.installments {
+        if schedule.is_empty() || schedule.len() > MAX_BATCH_SIZE {
+            return Err(QuickexError::InvalidSchedule);
+        }
+        for i in 1..schedule.len() {
+            if schedule.get_unchecked(i) <= schedule.get_unchecked(i - 1) {
+                return Err(QuickexError::InvalidSchedule);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Ensure `to` is permitted by the escrow's `allowed_recipient` restriction.
+///
+/// # Errors
+/// - [`RecipientNotAllowed`] – a recipient is set and `to` is a different address.
+fn check_recipient_allowed(entry: &EscrowEntry, to: &Address) -> Result<(), QuickexError> {
+    match &entry.allowed_recipient {
+        Some(allowed) if allowed != to => Err(QuickexError::RecipientNotAllowed),
+        _ => Ok(()),
+    }
+}
+
+/// Push `expires_at` out by the escrow's `extend_on_withdraw` increment.
+///
+/// Non-expiring escrows (`expires_at == 0`) are left untouched.
+fn extend_expiry_on_withdraw(entry: &mut EscrowEntry) {
+    if entry.expires_at > 0 && entry.extend_on_withdraw > 0 {
+        entry.expires_at = entry.expires_at.saturating_add(entry.extend_on_withdraw);
+    }
+}
+
+/// Persist a newly created escrow, assign it a sequential ID, and update the
+/// per-token and status bookkeeping. Returns the assigned escrow ID.
+///
+/// Every code path that creates an escrow must go through this helper. If a
+/// terminal escrow is being replaced under terminal re-deposit, its ID stops
+/// resolving to the commitment.
+///
+/// # Errors
+/// - [`CommitmentAlreadyExists`] – an escrow is stored under the commitment and
+///   terminal re-deposit does not allow replacing it.
+/// - [`TokenNotAllowed`] – the token allow-list is set and `entry.token` is not on it.
+pub fn record_new_escrow(
+    env: &Env,
+    commitment: &BytesN<32>,
+    entry: &EscrowEntry,
+) -> Result<u64, QuickexError> {
+    check_commitment_free(env, commitment)?;
+    check_token_allowed(env, &entry.token)?;
+    let key: Bytes = commitment.clone().into();
+    if let Some(previous) = get_escrow(env, &key) {
+        remove_escrow_commitment_by_id(env, previous.escrow_id);
+    }
+
+    let escrow_id = increment_escrow_counter(env);
+    let mut entry = entry.clone();
+    entry.escrow_id = escrow_id;
+    put_escrow(env, &key, &entry);
+    increment_token_escrow_count(env, &entry.token)?;
+    adjust_token_tvl(env, &entry.token, entry.amount);
+    add_owner_escrow(env, &entry.owner, commitment);
+    update_status_counts(env, None, entry.status);
+    set_escrow_commitment_by_id(env, escrow_id, commitment);
+    Ok(escrow_id)
+}
+
+/// Check that deposits in `token` are accepted under the token allow-list.
+///
+/// # Errors
+/// - [`TokenNotAllowed`] – the allow-list is non-empty and `token` is not on it.
+pub fn check_token_allowed(env: &Env, token: &Address) -> Result<(), QuickexError> {
+    if !is_token_allowed(env, token) {
+        return Err(QuickexError::TokenNotAllowed);
+    }
+    Ok(())
+}
+
+/// Persist an escrow that has just moved from `previous` into a terminal state
+/// (`Spent` or `Refunded`) and update the per-token, per-owner, and status
+/// bookkeeping. `entry.amount` is released from the token's TVL.
+fn record_terminal_escrow(
+    env: &Env,
+    commitment: &BytesN<32>,
+    previous: EscrowStatus,
+    entry: &EscrowEntry,
+) -> Result<(), QuickexError> {
+    put_escrow(env, &commitment.clone().into(), entry);
+    remove_owner_escrow(env, &entry.owner, commitment);
+    update_status_counts(env, Some(previous), entry.status);
+    adjust_token_tvl(env, &entry.token, -entry.amount);
+    decrement_token_escrow_count(env, &entry.token)
+}
+
+/// Circuit breaker run before every payout.
+///
+/// If auto-pause is enabled and the contract holds less of `token` than its
+/// tracked TVL, an accounting bug has been detected: pause the contract, publish
+/// `AutoPaused`, and return `true`. A payout moves balance and TVL by the same
+/// amount, so checking before it is equivalent to checking after it.
+///
+/// When this returns `true` the caller must return `Ok` without touching the
+/// escrow or moving funds; returning an error would roll back the pause.
+pub(crate) fn trip_if_insolvent(env: &Env, token: &Address) -> bool {
+    if !is_auto_pause(env) {
+        return false;
+    }
+
+    let balance = token::Client::new(env, token).balance(&env.current_contract_address());
+    let tvl = get_token_tvl(env, token);
+    if balance >= tvl {
+        return false;
+    }
+
+    set_paused(env, true);
+    events::publish_auto_paused(env, token.clone(), tvl, balance);
+    true
+}
+
 // ---------------------------------------------------------------------------
 // deposit
 // ---------------------------------------------------------------------------
 
-/// Deposit funds and create an escrow entry keyed by `SHA256(owner || amount || salt)`.
-///
-/// - Transfers `amount` from `owner` to the contract.
-/// - Sets status to `Pending`.
-/// - If `timeout_secs > 0`, the escrow expires `timeout_secs` seconds after creation.
-///   Pass `0` for a non-expiring escrow.
+/// Build a fresh `Pending` escrow entry created at the current ledger time.
 ///
-/// # Errors
-/// - [`InvalidAmount`] – amount ≤ 0.
-/// - [`InvalidSalt`] – salt > 1024 bytes.
-pub fn deposit(
+/// If `timeout_secs > 0`, the escrow expires `timeout_secs` seconds after creation;
+/// `0` yields a non-expiring escrow.
+fn new_entry(
     env: &Env,
     token: Address,
     amount: i128,
     owner: Address,
-    salt: Bytes,
     timeout_secs: u64,
-) -> Result<BytesN<32>, QuickexError> {
-    if amount <= 0 {
-        return Err(QuickexError::InvalidAmount);
-    }
-
-    owner.require_auth();
-
-    let commitment = commitment::create_amount_commitment(env, owner.clone(), amount, salt)?;
+    options: EscrowOptions,
+) -> EscrowEntry {
     let now = env.ledger().timestamp();
     let expires_at = if timeout_secs > 0 {
         now.saturating_add(timeout_secs)
@@ -71,175 +287,1785 @@ pub fn deposit(
         0
     };
 
-    let entry = EscrowEntry {
-        token: token.clone(),
+    EscrowEntry {
+        token,
         amount,
-        owner: owner.clone(),
+        owner,
         status: EscrowStatus::Pending,
         created_at: now,
         expires_at,
-    };
+        extend_on_withdraw: options.extend_on_withdraw,
+        allowed_recipient: options.allowed_recipient,
+        salt_hash: None,
+        nonce: None,
+        created_ledger: env.ledger().sequence(),
+        min_confirmations: options.min_confirmations,
+        on_expiry_recipient: options.on_expiry_recipient,
+        installments: options.installments,
+        installments_claimed: 0,
+        max_extensions: options.max_extensions,
+        extensions_used: 0,
+        earliest_withdraw: if options.cliff_secs > 0 {
+            now.saturating_add(options.cliff_secs)
+        } else {
+            0
+        },
+        next_of: options.next_of,
+        beneficiary: None,
+        escrow_id: 0,
+    }
+}
 
-    put_escrow(env, &commitment.clone().into(), &entry);
+/// Record `entry` under `commitment`, pull the funds from `entry.owner`, and
+/// publish the deposit event.
+fn open_escrow(
+    env: &Env,
+    commitment: BytesN<32>,
+    entry: EscrowEntry,
+) -> Result<BytesN<32>, QuickexError> {
+    let escrow_id = record_new_escrow(env, &commitment, &entry)?;
 
-    let token_client = token::Client::new(env, &token);
-    token_client.transfer(&owner, env.current_contract_address(), &amount);
+    let token_client = token::Client::new(env, &entry.token);
+    token_client.transfer(&entry.owner, env.current_contract_address(), &entry.amount);
 
-    events::publish_deposit(env, commitment.clone(), token, amount);
+    events::publish_deposit(
+        env,
+        commitment.clone(),
+        entry.token,
+        entry.amount,
+        escrow_id,
+    );
 
     Ok(commitment)
 }
 
-// ---------------------------------------------------------------------------
-// deposit_with_commitment
-// ---------------------------------------------------------------------------
-
-/// Deposit using a pre-generated 32-byte commitment hash.
+/// Deposit funds and create an escrow entry keyed by `SHA256(owner || amount || salt)`.
 ///
-/// - Validates commitment uniqueness.
-/// - If `timeout_secs > 0`, the escrow expires after that many seconds.
+/// - Transfers `amount` from `owner` to the contract.
+/// - Sets status to `Pending`.
+/// - If `timeout_secs > 0`, the escrow expires `timeout_secs` seconds after creation.
+///   Pass `0` for a non-expiring escrow.
+/// - Per-escrow settings are taken from `options`; see [`EscrowOptions`].
+/// - If `owner` has opted in to salt tracking, records `SHA256(salt)` for them.
 ///
 /// # Errors
 /// - [`InvalidAmount`] – amount ≤ 0.
-/// - [`CommitmentAlreadyExists`] – commitment already in storage.
-pub fn deposit_with_commitment(
+/// - [`InvalidSalt`] – salt > 1024 bytes.
+/// - [`InvalidSchedule`] – malformed installment schedule, or cliff ≥ timeout.
+/// - [`CommitmentAlreadyExists`] – an escrow for the commitment already exists.
+pub fn deposit(
     env: &Env,
-    from: Address,
     token: Address,
     amount: i128,
-    commitment: BytesN<32>,
+    owner: Address,
+    salt: Bytes,
     timeout_secs: u64,
-) -> Result<(), QuickexError> {
+    options: EscrowOptions,
+) -> Result<BytesN<32>, QuickexError> {
     if amount <= 0 {
         return Err(QuickexError::InvalidAmount);
     }
 
-    from.require_auth();
-
-    if has_escrow(env, &commitment.clone().into()) {
-        return Err(QuickexError::CommitmentAlreadyExists);
-    }
-
-    let token_client = token::Client::new(env, &token);
-    token_client.transfer(&from, env.current_contract_address(), &amount);
-
-    let now = env.ledger().timestamp();
-    let expires_at = if timeout_secs > 0 {
-        now.saturating_add(timeout_secs)
-    } else {
-        0
-    };
+    check_options(&options, timeout_secs)?;
 
-    let entry = EscrowEntry {
-        token: token.clone(),
-        amount,
-        owner: from.clone(),
-        status: EscrowStatus::Pending,
-        created_at: now,
-        expires_at,
-    };
+    owner.require_auth();
 
-    put_escrow(env, &commitment.clone().into(), &entry);
-    events::publish_deposit(env, commitment, token, amount);
+    let commitment =
+        commitment::create_amount_commitment(env, owner.clone(), amount, salt.clone())?;
+    if is_salt_tracking(env, &owner) {
+        mark_salt_used(env, &owner, &env.crypto().sha256(&salt).into());
+    }
+    let entry = new_entry(env, token, amount, owner, timeout_secs, options);
 
-    Ok(())
+    open_escrow(env, commitment, entry)
 }
 
-// ---------------------------------------------------------------------------
-// withdraw
-// ---------------------------------------------------------------------------
-
-/// Withdraw escrowed funds by proving commitment ownership.
+/// Deposit funds from `owner` into an escrow that only `beneficiary` can withdraw.
 ///
-/// The caller (`to`) must authorize. The commitment is recomputed from
-/// `to`, `amount`, and `salt` and must match an existing pending escrow.
+/// The commitment is `SHA256(beneficiary || amount || salt)`, so the beneficiary
+/// withdraws with the ordinary [`withdraw`]. `owner` pays, keeps refund rights, and
+/// receives any refund; the beneficiary is recorded on the entry.
 ///
 /// # Errors
 /// - [`InvalidAmount`] – amount ≤ 0.
-/// - [`CommitmentNotFound`] – no escrow for computed commitment.
-/// - [`EscrowExpired`] – escrow has passed its expiry.
-/// - [`AlreadySpent`] – escrow already spent or refunded.
-/// - [`InvalidCommitment`] – stored amount ≠ requested amount.
-pub fn withdraw(env: &Env, amount: i128, to: Address, salt: Bytes) -> Result<bool, QuickexError> {
+/// - [`InvalidSalt`] – salt > 1024 bytes.
+/// - [`CommitmentAlreadyExists`] – an escrow for the commitment already exists.
+pub fn deposit_to(
+    env: &Env,
+    token: Address,
+    amount: i128,
+    owner: Address,
+    beneficiary: Address,
+    salt: Bytes,
+    timeout_secs: u64,
+) -> Result<BytesN<32>, QuickexError> {
     if amount <= 0 {
         return Err(QuickexError::InvalidAmount);
     }
 
-    to.require_auth();
+    owner.require_auth();
 
-    let commitment = commitment::create_amount_commitment(env, to.clone(), amount, salt)?;
-    let commitment_bytes: Bytes = commitment.clone().into();
+    let commitment = commitment::create_amount_commitment(env, beneficiary.clone(), amount, salt)?;
 
-    let entry: EscrowEntry =
-        get_escrow(env, &commitment_bytes).ok_or(QuickexError::CommitmentNotFound)?;
+    let mut entry = new_entry(
+        env,
+        token,
+        amount,
+        owner,
+        timeout_secs,
+        EscrowOptions::default(),
+    );
+    entry.beneficiary = Some(beneficiary);
+    open_escrow(env, commitment, entry)
+}
 
-    if entry.status != EscrowStatus::Pending {
-        return Err(QuickexError::AlreadySpent);
-    }
+/// Create one [`deposit`]-style escrow per `(amount, salt, timeout_secs)` entry,
+/// pulling the summed amount from `owner` in a single transfer.
+///
+/// Commitments are returned in entry order. Every entry is validated, and any
+/// failure reverts the whole batch, so no escrow is written unless all are.
+///
+/// # Errors
+/// - [`BatchTooLarge`] – more than [`max_batch_size`] entries.
+/// - [`InvalidAmount`] – an amount ≤ 0.
+/// - [`InvalidSalt`] – a salt > 1024 bytes.
+/// - [`CommitmentAlreadyExists`] – an entry's commitment already exists, including
+///   one created earlier in the same batch.
+/// - [`InternalError`] – the summed amount overflows.
+pub fn deposit_batch(
+    env: &Env,
+    token: Address,
+    owner: Address,
+    entries: Vec<(i128, Bytes, u64)>,
+) -> Result<Vec<BytesN<32>>, QuickexError> {
+    check_batch_size(env, entries.len())?;
 
-    // Guard: block withdrawal if expired.
-    if is_expired(env, &entry) {
-        return Err(QuickexError::EscrowExpired);
-    }
+    owner.require_auth();
 
-    if entry.amount != amount {
-        return Err(QuickexError::InvalidCommitment);
-    }
+    let tracking = is_salt_tracking(env, &owner);
+    let mut total: i128 = 0;
+    let mut commitments = Vec::new(env);
+    for (amount, salt, timeout_secs) in entries.iter() {
+        if amount <= 0 {
+            return Err(QuickexError::InvalidAmount);
+        }
 
-    let mut updated = entry.clone();
-    updated.status = EscrowStatus::Spent;
-    put_escrow(env, &commitment_bytes, &updated);
+        let commitment =
+            commitment::create_amount_commitment(env, owner.clone(), amount, salt.clone())?;
+        if tracking {
+            mark_salt_used(env, &owner, &env.crypto().sha256(&salt).into());
+        }
 
-    let token_client = token::Client::new(env, &entry.token);
-    token_client.transfer(&env.current_contract_address(), &to, &amount);
+        let entry = new_entry(
+            env,
+            token.clone(),
+            amount,
+            owner.clone(),
+            timeout_secs,
+            EscrowOptions::default(),
+        );
+        let escrow_id = record_new_escrow(env, &commitment, &entry)?;
+        events::publish_deposit(env, commitment.clone(), token.clone(), amount, escrow_id);
 
-    events::publish_withdraw_toggled(env, to, commitment);
+        total = total
+            .checked_add(amount)
+            .ok_or(QuickexError::InternalError)?;
+        commitments.push_back(commitment);
+    }
 
-    Ok(true)
-}
+    if total > 0 {
+        token::Client::new(env, &token).transfer(&owner, env.current_contract_address(), &total);
+    }
 
-// ---------------------------------------------------------------------------
-// refund
-// ---------------------------------------------------------------------------
+    Ok(commitments)
+}
 
-/// Refund an expired escrow back to its original owner.
+/// Deposit funds into an escrow keyed by a range commitment over `[lower, upper]`.
 ///
-/// - Only callable after `expires_at` has been reached (and `expires_at > 0`).
-/// - Caller must be the original depositor (`entry.owner`).
-/// - Escrow must still be `Pending`.
+/// The commitment is [`commitment::create_range_commitment`] for `owner`, so the
+/// owner withdraws it with [`withdraw_in_range`] by revealing the bounds rather
+/// than the exact amount.
 ///
 /// # Errors
-/// - [`CommitmentNotFound`] – no escrow for the given commitment.
-/// - [`AlreadySpent`] – escrow already in a terminal state.
-/// - [`EscrowNotExpired`] – escrow has no timeout or timeout not yet reached.
-/// - [`InvalidOwner`] – caller is not the original owner.
-pub fn refund(env: &Env, commitment: BytesN<32>, caller: Address) -> Result<(), QuickexError> {
-    caller.require_auth();
+/// - [`InvalidAmount`] – amount ≤ 0, the range is invalid, or `amount` is outside it.
+/// - [`InvalidSalt`] – salt > 1024 bytes.
+/// - [`CommitmentAlreadyExists`] – an escrow for the commitment already exists.
+#[allow(clippy::too_many_arguments)]
+pub fn deposit_in_range(
+    env: &Env,
+    token: Address,
+    amount: i128,
+    owner: Address,
+    lower: i128,
+    upper: i128,
+    salt: Bytes,
+    timeout_secs: u64,
+) -> Result<BytesN<32>, QuickexError> {
+    if amount <= 0 || !(lower..=upper).contains(&amount) {
+        return Err(QuickexError::InvalidAmount);
+    }
 
-    let commitment_bytes: Bytes = commitment.clone().into();
-    let entry: EscrowEntry =
-        get_escrow(env, &commitment_bytes).ok_or(QuickexError::CommitmentNotFound)?;
+    owner.require_auth();
 
-    if entry.status != EscrowStatus::Pending {
-        return Err(QuickexError::AlreadySpent);
-    }
+    let commitment = commitment::create_range_commitment(env, owner.clone(), lower, upper, salt)?;
 
-    if !is_expired(env, &entry) {
-        return Err(QuickexError::EscrowNotExpired);
+    let entry = new_entry(
+        env,
+        token,
+        amount,
+        owner,
+        timeout_secs,
+        EscrowOptions::default(),
+    );
+    open_escrow(env, commitment, entry)
+}
+
+/// [`deposit`] with default options, but only if the commitment it produces equals
+/// `expected_commitment`.
+///
+/// Checked before any funds move, so a depositor can bind the deposit to a
+/// commitment they published earlier.
+///
+/// # Errors
+/// - [`CommitmentMismatch`] – `SHA256(owner || amount || salt)` ≠ `expected_commitment`.
+/// - Otherwise as [`deposit`].
+pub fn deposit_matching_commitment(
+    env: &Env,
+    token: Address,
+    amount: i128,
+    owner: Address,
+    salt: Bytes,
+    expected_commitment: BytesN<32>,
+    timeout_secs: u64,
+) -> Result<BytesN<32>, QuickexError> {
+    if amount <= 0 {
+        return Err(QuickexError::InvalidAmount);
     }
 
-    if caller != entry.owner {
-        return Err(QuickexError::InvalidOwner);
+    let commitment =
+        commitment::create_amount_commitment(env, owner.clone(), amount, salt.clone())?;
+    if commitment != expected_commitment {
+        return Err(QuickexError::CommitmentMismatch);
     }
 
-    let mut updated = entry.clone();
-    updated.status = EscrowStatus::Refunded;
-    put_escrow(env, &commitment_bytes, &updated);
+    deposit(
+        env,
+        token,
+        amount,
+        owner,
+        salt,
+        timeout_secs,
+        EscrowOptions::default(),
+    )
+}
 
-    let token_client = token::Client::new(env, &entry.token);
-    token_client.transfer(&env.current_contract_address(), &entry.owner, &entry.amount);
+/// Open `commitment` with `owner`, `salt` and the stored escrow amount.
+///
+/// Returns `None` if no escrow exists, the salt is invalid, or the recomputed
+/// commitment does not match.
+pub fn commitment_opening(
+    env: &Env,
+    commitment: BytesN<32>,
+    owner: Address,
+    salt: Bytes,
+) -> Option<CommitmentOpening> {
+    let entry = get_escrow(env, &commitment.clone().into())?;
+    let recomputed =
+        commitment::create_amount_commitment(env, owner.clone(), entry.amount, salt.clone())
+            .ok()?;
+    if recomputed != commitment {
+        return None;
+    }
+    Some(CommitmentOpening {
+        owner,
+        amount: entry.amount,
+        salt,
+        commitment,
+    })
+}
 
-    events::publish_refunded(env, entry.owner, commitment, entry.amount);
+/// Whether `owner` has made a salt-tracked [`deposit`] with `salt`.
+///
+/// Always `false` for deposits made while the owner was not opted in.
+pub fn salt_used(env: &Env, owner: &Address, salt: &Bytes) -> bool {
+    is_salt_used(env, owner, &env.crypto().sha256(salt).into())
+}
 
-    Ok(())
+// ---------------------------------------------------------------------------
+// deposit_with_salt_hash
+// ---------------------------------------------------------------------------
+
+/// Deposit funds keyed by `SHA256(owner || amount || salt_hash)`, where
+/// `salt_hash = SHA256(salt)`.
+///
+/// The raw salt never appears in the deposit transaction; it is revealed only once,
+/// at withdrawal, via [`withdraw_with_salt_hash`]. The salt hash is stored on the
+/// entry, and the plain [`withdraw`] path refuses such escrows so the preimage must
+/// always be presented.
+///
+/// # Errors
+/// - [`InvalidAmount`] – amount ≤ 0.
+/// - [`CommitmentAlreadyExists`] – an escrow for the commitment already exists.
+pub fn deposit_with_salt_hash(
+    env: &Env,
+    token: Address,
+    amount: i128,
+    owner: Address,
+    salt_hash: BytesN<32>,
+    timeout_secs: u64,
+) -> Result<BytesN<32>, QuickexError> {
+    if amount <= 0 {
+        return Err(QuickexError::InvalidAmount);
+    }
+
+    owner.require_auth();
+
+    let commitment =
+        commitment::create_amount_commitment(env, owner.clone(), amount, salt_hash.clone().into())?;
+    let mut entry = new_entry(
+        env,
+        token,
+        amount,
+        owner,
+        timeout_secs,
+        EscrowOptions::default(),
+    );
+    entry.salt_hash = Some(salt_hash);
+
+    open_escrow(env, commitment, entry)
+}
+
+// ---------------------------------------------------------------------------
+// deposit_nonced
+// ---------------------------------------------------------------------------
+
+/// Deposit funds keyed by a commitment that also binds the owner's next nonce.
+///
+/// The nonce is consumed from the owner's counter and stored on the entry, so two
+/// deposits with identical `(owner, amount, salt)` still produce distinct
+/// commitments. Withdraw with [`withdraw_nonced`].
+///
+/// # Errors
+/// - [`InvalidAmount`] – amount ≤ 0.
+/// - [`InvalidSalt`] – salt > 1024 bytes.
+/// - [`CommitmentAlreadyExists`] – an escrow for the commitment already exists.
+pub fn deposit_nonced(
+    env: &Env,
+    token: Address,
+    amount: i128,
+    owner: Address,
+    salt: Bytes,
+    timeout_secs: u64,
+) -> Result<BytesN<32>, QuickexError> {
+    if amount <= 0 {
+        return Err(QuickexError::InvalidAmount);
+    }
+
+    owner.require_auth();
+
+    let nonce = take_owner_nonce(env, &owner)?;
+    let commitment = commitment::create_nonced_commitment(env, owner.clone(), amount, salt, nonce)?;
+    let mut entry = new_entry(
+        env,
+        token,
+        amount,
+        owner,
+        timeout_secs,
+        EscrowOptions::default(),
+    );
+    entry.nonce = Some(nonce);
+
+    open_escrow(env, commitment, entry)
+}
+
+// ---------------------------------------------------------------------------
+// deposit_with_commitment
+// ---------------------------------------------------------------------------
+
+/// Deposit using a pre-generated 32-byte commitment hash.
+///
+/// - Validates commitment uniqueness. With terminal re-deposit enabled, a `Spent`
+///   or `Refunded` escrow under the commitment is replaced by the new one.
+/// - If `timeout_secs > 0`, the escrow expires after that many seconds.
+///
+/// # Errors
+/// - [`InvalidAmount`] – amount ≤ 0.
+/// - [`CommitmentAlreadyExists`] – commitment already in storage and not reusable.
+pub fn deposit_with_commitment(
+    env: &Env,
+    from: Address,
+    token: Address,
+    amount: i128,
+    commitment: BytesN<32>,
+    timeout_secs: u64,
+) -> Result<(), QuickexError> {
+    if amount <= 0 {
+        return Err(QuickexError::InvalidAmount);
+    }
+
+    from.require_auth();
+
+    let entry = new_entry(
+        env,
+        token,
+        amount,
+        from,
+        timeout_secs,
+        EscrowOptions::default(),
+    );
+    open_escrow(env, commitment, entry)?;
+
+    Ok(())
+}
+
+/// Ensure a new escrow may be opened under `commitment`.
+///
+/// # Errors
+/// - [`CommitmentAlreadyExists`] – an escrow is stored under the commitment and
+///   terminal re-deposit does not allow replacing it.
+fn check_commitment_free(env: &Env, commitment: &BytesN<32>) -> Result<(), QuickexError> {
+    if let Some(existing) = get_escrow(env, &commitment.clone().into()) {
+        let reusable = is_terminal_redeposit(env)
+            && matches!(
+                existing.status,
+                EscrowStatus::Spent | EscrowStatus::Refunded
+            );
+        if !reusable {
+            return Err(QuickexError::CommitmentAlreadyExists);
+        }
+    }
+    Ok(())
+}
+
+/// Fund a gift: a [`deposit_with_commitment`] that expires after [`GIFT_TIMEOUT_SECS`].
+///
+/// `commitment` should be `SHA256(recipient || amount || salt)`, with the salt known
+/// only to the recipient. The giver can never withdraw, since the commitment binds
+/// the recipient, but remains the owner and can refund an unclaimed gift once it
+/// expires.
+pub fn gift_deposit(
+    env: &Env,
+    from: Address,
+    token: Address,
+    amount: i128,
+    commitment: BytesN<32>,
+) -> Result<(), QuickexError> {
+    deposit_with_commitment(env, from, token, amount, commitment, GIFT_TIMEOUT_SECS)
+}
+
+// ---------------------------------------------------------------------------
+// create_escrow_cpi
+// ---------------------------------------------------------------------------
+
+/// Fund an escrow from `payer` that `beneficiary` can withdraw with `salt`.
+///
+/// The commitment is `SHA256(beneficiary || amount || salt)`; `payer` becomes the
+/// escrow owner and may refund after expiry. Designed to be invoked by another
+/// contract; see [`crate::QuickexContract::create_escrow_cpi`] for auth rules.
+///
+/// # Errors
+/// - [`InvalidAmount`] – amount ≤ 0.
+/// - [`InvalidSalt`] – salt > 1024 bytes.
+/// - [`CommitmentAlreadyExists`] – an escrow for the commitment already exists.
+pub fn create_escrow_cpi(
+    env: &Env,
+    payer: Address,
+    token: Address,
+    amount: i128,
+    beneficiary: Address,
+    salt: Bytes,
+    timeout_secs: u64,
+) -> Result<BytesN<32>, QuickexError> {
+    let commitment = commitment::create_amount_commitment(env, beneficiary, amount, salt)?;
+    deposit_with_commitment(env, payer, token, amount, commitment.clone(), timeout_secs)?;
+    Ok(commitment)
+}
+
+// ---------------------------------------------------------------------------
+// withdraw
+// ---------------------------------------------------------------------------
+
+/// Withdraw escrowed funds by proving commitment ownership.
+///
+/// The caller (`to`) must authorize. The commitment is recomputed from
+/// `to`, `amount`, and `salt` and must match an existing pending escrow.
+/// If the escrow was created with `extend_on_withdraw > 0` and has an expiry,
+/// `expires_at` is pushed out by that many seconds.
+///
+/// # Errors
+/// - [`InvalidAmount`] – amount ≤ 0.
+/// - [`CommitmentNotFound`] – no escrow for computed commitment.
+/// - [`CommitmentMismatch`] – escrow was created with a salt hash or a nonce; use
+///   [`withdraw_with_salt_hash`] or [`withdraw_nonced`].
+/// - [`TokenMismatch`] – `token` is not the escrow's token.
+/// - [`EscrowExpired`] – escrow has passed its expiry.
+/// - [`InsufficientConfirmations`] – fewer than `min_confirmations` ledgers have
+///   closed since the deposit.
+/// - [`EscrowNotYetClaimable`] – the escrow's cliff has not passed.
+/// - [`AlreadySpent`] – escrow already spent or refunded.
+/// - [`InvalidCommitment`] – stored amount ≠ requested amount.
+/// - [`InvalidOwner`] – strict owner check is on and `entry.owner` ≠ `to`.
+/// - [`RecipientNotAllowed`] – escrow restricts its recipient and `to` is not it.
+pub fn withdraw(
+    env: &Env,
+    token: &Address,
+    amount: i128,
+    to: Address,
+    salt: Bytes,
+) -> Result<bool, QuickexError> {
+    if amount <= 0 {
+        return Err(QuickexError::InvalidAmount);
+    }
+
+    to.require_auth();
+
+    settle_plain_withdrawal(env, token, amount, to, salt)
+}
+
+/// Withdraw like [`withdraw`], authorized by a signature relayed by a third party.
+///
+/// `to` signs the invocation, including `nonce`, so a relayer can submit it and pay
+/// the fees. `nonce` must equal `to`'s current withdrawal nonce, which is then
+/// advanced; replaying the signed call fails.
+///
+/// # Errors
+/// - [`StaleNonce`] – `nonce` is not `to`'s current withdrawal nonce.
+/// - Otherwise as [`withdraw`].
+pub fn withdraw_signed(
+    env: &Env,
+    token: &Address,
+    amount: i128,
+    to: Address,
+    salt: Bytes,
+    nonce: u64,
+) -> Result<bool, QuickexError> {
+    if amount <= 0 {
+        return Err(QuickexError::InvalidAmount);
+    }
+
+    to.require_auth();
+    take_withdraw_nonce(env, &to, nonce)?;
+
+    settle_plain_withdrawal(env, token, amount, to, salt)
+}
+
+/// Locate the escrow keyed by `SHA256(to || amount || salt)` and settle it.
+///
+/// The caller must already have authenticated `to`.
+fn settle_plain_withdrawal(
+    env: &Env,
+    token: &Address,
+    amount: i128,
+    to: Address,
+    salt: Bytes,
+) -> Result<bool, QuickexError> {
+    let commitment = commitment::create_amount_commitment(env, to.clone(), amount, salt)?;
+    let entry: EscrowEntry =
+        get_escrow(env, &commitment.clone().into()).ok_or(QuickexError::CommitmentNotFound)?;
+
+    if entry.salt_hash.is_some() || entry.nonce.is_some() {
+        return Err(QuickexError::CommitmentMismatch);
+    }
+
+    if entry.token != *token {
+        return Err(QuickexError::TokenMismatch);
+    }
+
+    settle_withdrawal(env, commitment, entry, amount, to)
+}
+
+/// Withdraw a range-commitment escrow by revealing its bounds.
+///
+/// The commitment is recomputed from `to`, `lower`, `upper` and `salt`, and the
+/// escrow's stored amount is paid out (less the withdrawal fee), so `to` does not
+/// need to know the exact amount. Returns `false` without paying out if the
+/// auto-pause circuit breaker tripped.
+///
+/// # Errors
+/// - [`InvalidAmount`] – the range is invalid.
+/// - [`InvalidSalt`] – salt > 1024 bytes.
+/// - [`CommitmentNotFound`] – no escrow for the recomputed commitment, e.g. the
+///   bounds do not match the committed range.
+/// - [`InvalidCommitment`] – the stored amount has left the committed range.
+/// - Otherwise as [`withdraw`], except that there is no token argument to check.
+pub fn withdraw_in_range(
+    env: &Env,
+    lower: i128,
+    upper: i128,
+    salt: Bytes,
+    to: Address,
+) -> Result<bool, QuickexError> {
+    to.require_auth();
+
+    let commitment = commitment::create_range_commitment(env, to.clone(), lower, upper, salt)?;
+    let entry: EscrowEntry =
+        get_escrow(env, &commitment.clone().into()).ok_or(QuickexError::CommitmentNotFound)?;
+
+    if !(lower..=upper).contains(&entry.amount) {
+        return Err(QuickexError::InvalidCommitment);
+    }
+
+    let amount = entry.amount;
+    settle_withdrawal(env, commitment, entry, amount, to)
+}
+
+/// Withdraw `requested` out of an escrow, leaving the rest claimable.
+///
+/// The commitment is recomputed from `to`, the originally deposited `amount`, and
+/// `salt`, exactly as in [`withdraw`]. `requested` is deducted from the stored
+/// amount and paid out (less the withdrawal fee); the escrow stays `Pending` until
+/// its balance reaches zero, then becomes `Spent`. Once part of an escrow has been
+/// withdrawn, the remainder can only be claimed through this function.
+///
+/// Returns `false` without paying out if the auto-pause circuit breaker tripped.
+///
+/// # Errors
+/// - [`InvalidAmount`] – `amount` or `requested` ≤ 0.
+/// - [`PartialWithdrawExceedsBalance`] – `requested` is more than the remaining balance.
+/// - Otherwise as [`withdraw`], except that there is no token argument to check and
+///   the stored amount need not equal `amount`.
+pub fn withdraw_partial(
+    env: &Env,
+    amount: i128,
+    to: Address,
+    salt: Bytes,
+    requested: i128,
+) -> Result<bool, QuickexError> {
+    if amount <= 0 || requested <= 0 {
+        return Err(QuickexError::InvalidAmount);
+    }
+
+    to.require_auth();
+
+    let commitment = commitment::create_amount_commitment(env, to.clone(), amount, salt)?;
+    let entry: EscrowEntry =
+        get_escrow(env, &commitment.clone().into()).ok_or(QuickexError::CommitmentNotFound)?;
+
+    if entry.salt_hash.is_some() || entry.nonce.is_some() {
+        return Err(QuickexError::CommitmentMismatch);
+    }
+
+    check_withdrawable(env, &entry)?;
+
+    // Installment escrows are released only through `withdraw_matured`.
+    if entry.installments.is_some() {
+        return Err(QuickexError::CommitmentMismatch);
+    }
+
+    if requested > entry.amount {
+        return Err(QuickexError::PartialWithdrawExceedsBalance);
+    }
+
+    check_payee(env, &entry, &to)?;
+
+    if trip_if_insolvent(env, &entry.token) {
+        return Ok(false);
+    }
+
+    let mut updated = entry.clone();
+    updated.amount -= requested;
+    extend_expiry_on_withdraw(&mut updated);
+    adjust_token_tvl(env, &entry.token, -requested);
+    if updated.amount == 0 {
+        updated.status = EscrowStatus::Spent;
+        record_terminal_escrow(env, &commitment, entry.status, &updated)?;
+    } else {
+        put_escrow(env, &commitment.clone().into(), &updated);
+    }
+
+    fees::pay_out(env, &entry.token, &to, requested)?;
+
+    events::publish_partial_withdraw(env, commitment, to, requested, updated.amount);
+
+    Ok(true)
+}
+
+/// Withdraw an escrow like [`withdraw`] and swap the proceeds into `out_token`
+/// through `dex`, delivering them to `to`. Returns the amount of `out_token` received.
+///
+/// The withdrawal fee is taken in the escrowed token before the swap. The DEX is
+/// pre-authorized to pull the net amount from this contract and must deliver at
+/// least `min_out`; otherwise the whole call reverts. Both are checked against
+/// token balances rather than the DEX's return value, and the amount `to` actually
+/// received is returned. Returns 0 without swapping if the auto-pause circuit
+/// breaker tripped.
+///
+/// # Errors
+/// - [`SwapInputMismatch`] – this contract's balance of the escrowed token did not
+///   drop by exactly the net amount.
+/// - [`SlippageExceeded`] – `to` received less than `min_out` of `out_token`.
+/// - Otherwise as [`withdraw`], except that there is no token argument to check.
+pub fn withdraw_and_swap(
+    env: &Env,
+    amount: i128,
+    salt: Bytes,
+    to: Address,
+    dex: Address,
+    min_out: i128,
+    out_token: Address,
+) -> Result<i128, QuickexError> {
+    if amount <= 0 {
+        return Err(QuickexError::InvalidAmount);
+    }
+
+    to.require_auth();
+
+    let commitment = commitment::create_amount_commitment(env, to.clone(), amount, salt)?;
+    let entry: EscrowEntry =
+        get_escrow(env, &commitment.clone().into()).ok_or(QuickexError::CommitmentNotFound)?;
+
+    if entry.salt_hash.is_some() || entry.nonce.is_some() {
+        return Err(QuickexError::CommitmentMismatch);
+    }
+
+    if !spend_escrow(env, &commitment, &entry, amount, &to)? {
+        return Ok(0);
+    }
+
+    let net = fees::take_fee(env, &entry.token, &to, amount)?;
+
+    let contract = env.current_contract_address();
+    let in_client = token::Client::new(env, &entry.token);
+    let out_client = token::Client::new(env, &out_token);
+    let in_before = in_client.balance(&contract);
+    let out_before = out_client.balance(&to);
+    env.authorize_as_current_contract(vec![
+        env,
+        InvokerContractAuthEntry::Contract(SubContractInvocation {
+            context: ContractContext {
+                contract: entry.token.clone(),
+                fn_name: Symbol::new(env, "transfer"),
+                args: (contract.clone(), dex.clone(), net).into_val(env),
+            },
+            sub_invocations: vec![env],
+        }),
+    ]);
+    DexClient::new(env, &dex).swap(&contract, &entry.token, &out_token, &net, &min_out, &to);
+    if in_before.saturating_sub(in_client.balance(&contract)) != net {
+        return Err(QuickexError::SwapInputMismatch);
+    }
+    let out = out_client.balance(&to).saturating_sub(out_before);
+    if out < min_out {
+        return Err(QuickexError::SlippageExceeded);
+    }
+
+    events::publish_swapped(env, commitment, to, net, out_token, out);
+
+    Ok(out)
+}
+
+/// Withdraw an escrow created by [`deposit_with_salt_hash`].
+///
+/// Hashes the provided `salt`, recomputes the commitment from `to`, `amount`, and
+/// `SHA256(salt)`, and requires the stored salt hash to match before settling with
+/// the same rules as [`withdraw`].
+///
+/// # Errors
+/// - [`InvalidSalt`] – salt > 1024 bytes.
+/// - [`CommitmentMismatch`] – the escrow's stored salt hash is not `SHA256(salt)`.
+/// - Otherwise as [`withdraw`].
+pub fn withdraw_with_salt_hash(
+    env: &Env,
+    amount: i128,
+    to: Address,
+    salt: Bytes,
+) -> Result<bool, QuickexError> {
+    if amount <= 0 {
+        return Err(QuickexError::InvalidAmount);
+    }
+
+    to.require_auth();
+
+    let salt_hash = commitment::hash_salt(env, &salt)?;
+    let commitment =
+        commitment::create_amount_commitment(env, to.clone(), amount, salt_hash.clone().into())?;
+    let entry: EscrowEntry =
+        get_escrow(env, &commitment.clone().into()).ok_or(QuickexError::CommitmentNotFound)?;
+
+    if entry.salt_hash != Some(salt_hash) {
+        return Err(QuickexError::CommitmentMismatch);
+    }
+
+    settle_withdrawal(env, commitment, entry, amount, to)
+}
+
+/// Withdraw an escrow created by [`deposit_nonced`].
+///
+/// Loads the escrow at `commitment`, recomputes the commitment from `to`, `amount`,
+/// `salt`, and the nonce stored on the entry, and requires it to match before
+/// settling with the same rules as [`withdraw`].
+///
+/// # Errors
+/// - [`CommitmentMismatch`] – the escrow has no nonce, or the recomputed commitment
+///   differs from `commitment`.
+/// - Otherwise as [`withdraw`].
+pub fn withdraw_nonced(
+    env: &Env,
+    commitment: BytesN<32>,
+    amount: i128,
+    to: Address,
+    salt: Bytes,
+) -> Result<bool, QuickexError> {
+    if amount <= 0 {
+        return Err(QuickexError::InvalidAmount);
+    }
+
+    to.require_auth();
+
+    let entry: EscrowEntry =
+        get_escrow(env, &commitment.clone().into()).ok_or(QuickexError::CommitmentNotFound)?;
+    let nonce = entry.nonce.ok_or(QuickexError::CommitmentMismatch)?;
+
+    let expected = commitment::create_nonced_commitment(env, to.clone(), amount, salt, nonce)?;
+    if expected != commitment {
+        return Err(QuickexError::CommitmentMismatch);
+    }
+
+    settle_withdrawal(env, commitment, entry, amount, to)
+}
+
+/// Ensure an escrow can currently be withdrawn from.
+///
+/// # Errors
+/// - [`AlreadySpent`] – escrow already spent or refunded.
+/// - [`EscrowExpired`] – escrow has passed its expiry.
+/// - [`InsufficientConfirmations`] – `min_confirmations` ledgers have not closed.
+/// - [`EscrowNotYetClaimable`] – `earliest_withdraw` has not been reached.
+/// - [`PrerequisiteNotMet`] – the escrow named by `next_of` is missing or not spent.
+fn check_withdrawable(env: &Env, entry: &EscrowEntry) -> Result<(), QuickexError> {
+    // Guard: block withdrawal if expired, whether or not it has been marked.
+    match entry.status {
+        EscrowStatus::Pending if is_expired(env, entry) => return Err(QuickexError::EscrowExpired),
+        EscrowStatus::Pending => {}
+        EscrowStatus::Expired => return Err(QuickexError::EscrowExpired),
+        EscrowStatus::Spent | EscrowStatus::Refunded => return Err(QuickexError::AlreadySpent),
+    }
+
+    let confirmed_at = entry.created_ledger.saturating_add(entry.min_confirmations);
+    if env.ledger().sequence() < confirmed_at {
+        return Err(QuickexError::InsufficientConfirmations);
+    }
+
+    if env.ledger().timestamp() < entry.earliest_withdraw {
+        return Err(QuickexError::EscrowNotYetClaimable);
+    }
+
+    if let Some(prerequisite) = &entry.next_of {
+        let spent = get_escrow(env, &prerequisite.clone().into())
+            .is_some_and(|prior| prior.status == EscrowStatus::Spent);
+        if !spent {
+            return Err(QuickexError::PrerequisiteNotMet);
+        }
+    }
+
+    Ok(())
+}
+
+/// Release every installment of an escrow whose unlock time has passed.
+///
+/// The beneficiary is `allowed_recipient` if set, otherwise the owner, and must
+/// authorize. The remaining balance is split evenly over the unclaimed
+/// installments, with the final installment taking any rounding remainder. Once
+/// all installments are released the escrow becomes `Spent`.
+///
+/// Returns the amount released, or 0 if the auto-pause circuit breaker tripped.
+///
+/// # Errors
+/// - [`CommitmentNotFound`] – no escrow for the given commitment.
+/// - [`CommitmentMismatch`] – escrow has no installment schedule.
+/// - [`RecipientNotAllowed`] – `to` is not the beneficiary.
+/// - [`NoMaturedInstallments`] – no unclaimed installment has unlocked yet.
+/// - Otherwise as [`check_withdrawable`].
+pub fn withdraw_matured(
+    env: &Env,
+    commitment: BytesN<32>,
+    to: Address,
+) -> Result<i128, QuickexError> {
+    to.require_auth();
+
+    let entry: EscrowEntry =
+        get_escrow(env, &commitment.clone().into()).ok_or(QuickexError::CommitmentNotFound)?;
+    let schedule = entry
+        .installments
+        .clone()
+        .ok_or(QuickexError::CommitmentMismatch)?;
+
+    check_withdrawable(env, &entry)?;
+
+    let beneficiary = entry
+        .allowed_recipient
+        .clone()
+        .unwrap_or(entry.owner.clone());
+    if to != beneficiary {
+        return Err(QuickexError::RecipientNotAllowed);
+    }
+
+    let now = env.ledger().timestamp();
+    let matured = schedule.iter().filter(|unlock| *unlock <= now).count() as u32;
+    let newly_matured = matured.saturating_sub(entry.installments_claimed);
+    if newly_matured == 0 {
+        return Err(QuickexError::NoMaturedInstallments);
+    }
+
+    let unclaimed = schedule.len() - entry.installments_claimed;
+    let payout = if newly_matured == unclaimed {
+        entry.amount
+    } else {
+        entry
+            .amount
+            .checked_mul(newly_matured as i128)
+            .ok_or(QuickexError::InternalError)?
+            / unclaimed as i128
+    };
+
+    if trip_if_insolvent(env, &entry.token) {
+        return Ok(0);
+    }
+
+    let mut updated = entry.clone();
+    updated.amount -= payout;
+    adjust_token_tvl(env, &entry.token, -payout);
+    updated.installments_claimed = matured;
+    if matured == schedule.len() {
+        updated.status = EscrowStatus::Spent;
+        record_terminal_escrow(env, &commitment, entry.status, &updated)?;
+    } else {
+        put_escrow(env, &commitment.clone().into(), &updated);
+    }
+
+    fees::pay_out(env, &entry.token, &to, payout)?;
+
+    events::publish_installments_released(env, commitment, to, payout, matured);
+
+    Ok(payout)
+}
+
+/// Validate a located escrow against a withdrawal request and pay out `amount` to `to`.
+///
+/// Returns `false` without paying out if the auto-pause circuit breaker tripped.
+fn settle_withdrawal(
+    env: &Env,
+    commitment: BytesN<32>,
+    entry: EscrowEntry,
+    amount: i128,
+    to: Address,
+) -> Result<bool, QuickexError> {
+    if !spend_escrow(env, &commitment, &entry, amount, &to)? {
+        return Ok(false);
+    }
+
+    fees::pay_out(env, &entry.token, &to, amount)?;
+
+    events::publish_withdraw_toggled(env, to, commitment);
+
+    Ok(true)
+}
+
+/// Ensure `to` may receive funds from the escrow.
+///
+/// # Errors
+/// - [`InvalidOwner`] – strict owner check is on and `to` is not the beneficiary,
+///   or the owner when no beneficiary is set.
+/// - [`RecipientNotAllowed`] – `to` is not the escrow's beneficiary, or the escrow
+///   restricts its recipient to another address.
+fn check_payee(env: &Env, entry: &EscrowEntry, to: &Address) -> Result<(), QuickexError> {
+    if entry.beneficiary.as_ref().is_some_and(|b| b != to) {
+        return Err(QuickexError::RecipientNotAllowed);
+    }
+
+    // Defense in depth: the commitment already binds `to`, but in strict mode we
+    // also require the stored entry to agree, guarding against a corrupted entry.
+    let payee = entry.beneficiary.as_ref().unwrap_or(&entry.owner);
+    if is_strict_owner_check(env) && payee != to {
+        return Err(QuickexError::InvalidOwner);
+    }
+
+    check_recipient_allowed(entry, to)
+}
+
+/// Validate a located escrow against a withdrawal of `amount` to `to` and mark it
+/// `Spent`, leaving the funds in the contract for the caller to pay out.
+///
+/// Returns `false`, with the escrow untouched, if the auto-pause circuit breaker
+/// tripped.
+fn spend_escrow(
+    env: &Env,
+    commitment: &BytesN<32>,
+    entry: &EscrowEntry,
+    amount: i128,
+    to: &Address,
+) -> Result<bool, QuickexError> {
+    check_withdrawable(env, entry)?;
+
+    // Installment escrows are released only through `withdraw_matured`.
+    if entry.installments.is_some() {
+        return Err(QuickexError::CommitmentMismatch);
+    }
+
+    if entry.amount != amount {
+        return Err(QuickexError::InvalidCommitment);
+    }
+
+    check_payee(env, entry, to)?;
+
+    if trip_if_insolvent(env, &entry.token) {
+        return Ok(false);
+    }
+
+    let mut updated = entry.clone();
+    updated.status = EscrowStatus::Spent;
+    extend_expiry_on_withdraw(&mut updated);
+    record_terminal_escrow(env, commitment, entry.status, &updated)?;
+
+    Ok(true)
+}
+
+// ---------------------------------------------------------------------------
+// refund
+// ---------------------------------------------------------------------------
+
+/// Refund an expired escrow back to its original owner.
+///
+/// - Only callable after `expires_at` has been reached (and `expires_at > 0`).
+/// - Caller must be the original depositor (`entry.owner`).
+/// - Escrow must be `Pending` or `Expired`.
+///
+/// # Errors
+/// - [`CommitmentNotFound`] – no escrow for the given commitment.
+/// - [`AlreadySpent`] – escrow already in a terminal state.
+/// - [`EscrowNotExpired`] – escrow has no timeout or timeout not yet reached.
+/// - [`InvalidOwner`] – caller is not the original owner.
+/// - [`RefundDisabled`] – escrow forwards to its `on_expiry_recipient` instead.
+/// - [`RecipientNotAllowed`] – the refund allow-list is set and the owner is not on it.
+///
+/// If the auto-pause circuit breaker trips, returns `Ok` with the escrow untouched.
+pub fn refund(env: &Env, commitment: BytesN<32>, caller: Address) -> Result<(), QuickexError> {
+    let Some(entry) = begin_refund(env, &commitment, &caller)? else {
+        return Ok(());
+    };
+    check_refund_recipient(env, &entry.owner)?;
+
+    let token_client = token::Client::new(env, &entry.token);
+    token_client.transfer(&env.current_contract_address(), &entry.owner, &entry.amount);
+
+    events::publish_refunded(env, entry.owner, commitment, entry.amount);
+
+    Ok(())
+}
+
+/// Refund an expired escrow, paying `fallback` if the owner cannot receive the token.
+///
+/// Eligibility is identical to [`refund`]. The transfer to the owner is attempted
+/// first; if the token contract rejects it (e.g. the owner's trustline was removed
+/// or deauthorized), the funds go to `fallback` instead. An owner that is not on
+/// a non-empty refund allow-list is skipped straight to `fallback`, which must
+/// itself be allowed.
+pub fn refund_with_fallback(
+    env: &Env,
+    commitment: BytesN<32>,
+    caller: Address,
+    fallback: Address,
+) -> Result<(), QuickexError> {
+    let Some(entry) = begin_refund(env, &commitment, &caller)? else {
+        return Ok(());
+    };
+
+    let token_client = token::Client::new(env, &entry.token);
+    let contract = env.current_contract_address();
+    let paid_owner = is_refund_recipient_allowed(env, &entry.owner)
+        && matches!(
+            token_client.try_transfer(&contract, &entry.owner, &entry.amount),
+            Ok(Ok(()))
+        );
+    if !paid_owner {
+        check_refund_recipient(env, &fallback)?;
+        token_client.transfer(&contract, &fallback, &entry.amount);
+    }
+
+    events::publish_refunded(env, entry.owner, commitment, entry.amount);
+
+    Ok(())
+}
+
+/// Refund an expired escrow and re-escrow the same amount for `new_beneficiary`.
+///
+/// Eligibility is identical to [`refund`]. The funds never leave the contract: the
+/// old escrow is marked `Refunded` and a fresh `Pending` escrow keyed by
+/// `SHA256(new_beneficiary || amount || new_salt)` is opened with the same token
+/// and amount, owned by the original owner so they can refund it again if it
+/// lapses. Publishes both the refund and the deposit events.
+///
+/// Returns the new commitment, or `None` if the auto-pause circuit breaker tripped.
+///
+/// # Errors
+/// - As [`refund`].
+/// - [`InvalidSalt`] – `new_salt` > 1024 bytes.
+/// - [`CommitmentAlreadyExists`] – an escrow for the new commitment already exists.
+pub fn refund_and_reassign(
+    env: &Env,
+    commitment: BytesN<32>,
+    owner: Address,
+    new_beneficiary: Address,
+    new_salt: Bytes,
+    new_timeout: u64,
+) -> Result<Option<BytesN<32>>, QuickexError> {
+    let Some(entry) = begin_refund(env, &commitment, &owner)? else {
+        return Ok(None);
+    };
+
+    let new_commitment =
+        commitment::create_amount_commitment(env, new_beneficiary, entry.amount, new_salt)?;
+
+    events::publish_refunded(env, entry.owner.clone(), commitment, entry.amount);
+
+    let new_escrow = new_entry(
+        env,
+        entry.token,
+        entry.amount,
+        entry.owner,
+        new_timeout,
+        EscrowOptions::default(),
+    );
+    let escrow_id = record_new_escrow(env, &new_commitment, &new_escrow)?;
+    events::publish_deposit(
+        env,
+        new_commitment.clone(),
+        new_escrow.token,
+        new_escrow.amount,
+        escrow_id,
+    );
+
+    Ok(Some(new_commitment))
+}
+
+/// Validate a refund request and mark the escrow `Refunded`.
+///
+/// Returns the entry as it was before the status change so the caller can pay out,
+/// or `None` if the auto-pause circuit breaker tripped.
+fn begin_refund(
+    env: &Env,
+    commitment: &BytesN<32>,
+    caller: &Address,
+) -> Result<Option<EscrowEntry>, QuickexError> {
+    caller.require_auth();
+
+    let commitment_bytes: Bytes = commitment.clone().into();
+    let entry: EscrowEntry =
+        get_escrow(env, &commitment_bytes).ok_or(QuickexError::CommitmentNotFound)?;
+
+    check_refundable(env, &entry)?;
+
+    if *caller != entry.owner {
+        return Err(QuickexError::InvalidOwner);
+    }
+
+    if entry.on_expiry_recipient.is_some() {
+        return Err(QuickexError::RefundDisabled);
+    }
+
+    if trip_if_insolvent(env, &entry.token) {
+        return Ok(None);
+    }
+
+    let mut updated = entry.clone();
+    updated.status = EscrowStatus::Refunded;
+    record_terminal_escrow(env, commitment, entry.status, &updated)?;
+
+    Ok(Some(entry))
+}
+
+/// Push an escrow's expiry back by `additional_secs`. Owner only.
+///
+/// Only escrows that are `Pending`, expiring, and not yet expired can be extended,
+/// and at most `max_extensions` times if the escrow sets a cap.
+///
+/// # Errors
+/// - [`CommitmentNotFound`] – no escrow for the given commitment.
+/// - [`InvalidOwner`] – caller is not the escrow owner.
+/// - [`AlreadySpent`] – escrow already in a terminal state.
+/// - [`EscrowNotExpirable`] – escrow has no expiry.
+/// - [`EscrowExpired`] – escrow has already passed its expiry.
+/// - [`ExtensionLimitReached`] – escrow has used all of its extensions.
+pub fn extend_timeout(
+    env: &Env,
+    commitment: BytesN<32>,
+    caller: Address,
+    additional_secs: u64,
+) -> Result<(), QuickexError> {
+    caller.require_auth();
+
+    let commitment_bytes: Bytes = commitment.clone().into();
+    let mut entry: EscrowEntry =
+        get_escrow(env, &commitment_bytes).ok_or(QuickexError::CommitmentNotFound)?;
+
+    if caller != entry.owner {
+        return Err(QuickexError::InvalidOwner);
+    }
+    if entry.status == EscrowStatus::Expired {
+        return Err(QuickexError::EscrowExpired);
+    }
+    if entry.status != EscrowStatus::Pending {
+        return Err(QuickexError::AlreadySpent);
+    }
+    if entry.expires_at == 0 {
+        return Err(QuickexError::EscrowNotExpirable);
+    }
+    if is_expired(env, &entry) {
+        return Err(QuickexError::EscrowExpired);
+    }
+    if entry
+        .max_extensions
+        .is_some_and(|max| entry.extensions_used >= max)
+    {
+        return Err(QuickexError::ExtensionLimitReached);
+    }
+
+    let old_expires_at = entry.expires_at;
+    entry.expires_at = old_expires_at.saturating_add(additional_secs);
+    entry.extensions_used = entry.extensions_used.saturating_add(1);
+    put_escrow(env, &commitment_bytes, &entry);
+
+    events::publish_timeout_extended(env, commitment, old_expires_at, entry.expires_at);
+
+    Ok(())
+}
+
+/// Lapse a `Pending` escrow immediately by setting `expires_at` to now.
+///
+/// Only the time gate is lifted; refunds still require the owner. The caller must
+/// already have been checked to be the admin. A ledger timestamp of 0 is stored
+/// as 1, since `expires_at == 0` means "never expires".
+///
+/// # Errors
+/// - [`CommitmentNotFound`] – no escrow for the given commitment.
+/// - [`AlreadySpent`] – escrow is not `Pending`.
+pub fn force_expire(env: &Env, admin: Address, commitment: BytesN<32>) -> Result<(), QuickexError> {
+    let commitment_bytes: Bytes = commitment.clone().into();
+    let mut entry: EscrowEntry =
+        get_escrow(env, &commitment_bytes).ok_or(QuickexError::CommitmentNotFound)?;
+
+    if entry.status != EscrowStatus::Pending {
+        return Err(QuickexError::AlreadySpent);
+    }
+
+    entry.expires_at = env.ledger().timestamp().max(1);
+    put_escrow(env, &commitment_bytes, &entry);
+
+    events::publish_force_expired(env, commitment, admin, entry.expires_at);
+
+    Ok(())
+}
+
+/// Record that a `Pending` escrow has passed its expiry by moving it to `Expired`.
+/// Permissionless.
+///
+/// The escrow stays active: it can still be refunded or forwarded exactly as
+/// before. Marking an escrow that is already `Expired` is a no-op.
+///
+/// # Errors
+/// - [`CommitmentNotFound`] – no escrow for the given commitment.
+/// - [`AlreadySpent`] – escrow already in a terminal state.
+/// - [`EscrowNotExpired`] – escrow has no timeout or timeout not yet reached.
+pub fn mark_expired(env: &Env, commitment: BytesN<32>) -> Result<(), QuickexError> {
+    let commitment_bytes: Bytes = commitment.clone().into();
+    let mut entry: EscrowEntry =
+        get_escrow(env, &commitment_bytes).ok_or(QuickexError::CommitmentNotFound)?;
+
+    match entry.status {
+        EscrowStatus::Expired => return Ok(()),
+        EscrowStatus::Spent | EscrowStatus::Refunded => return Err(QuickexError::AlreadySpent),
+        EscrowStatus::Pending if !is_expired(env, &entry) => {
+            return Err(QuickexError::EscrowNotExpired)
+        }
+        EscrowStatus::Pending => {}
+    }
+
+    entry.status = EscrowStatus::Expired;
+    put_escrow(env, &commitment_bytes, &entry);
+    update_status_counts(env, Some(EscrowStatus::Pending), EscrowStatus::Expired);
+
+    events::publish_escrow_expired(env, commitment, entry.owner, entry.expires_at);
+
+    Ok(())
+}
+
+/// Delete a terminal escrow from storage to reclaim its entry.
+///
+/// `Spent` and `Refunded` escrows were already removed from the per-token and
+/// per-owner bookkeeping when they became terminal, so purging touches no
+/// counters; only `Pending` and `Expired` escrows count as active, and neither can
+/// be purged. The status counts keep including purged escrows. The escrow ID
+/// mapping is removed too, so a later deposit under the same commitment does not
+/// leave two IDs pointing at it. The caller must already have been checked to be
+/// the admin.
+///
+/// # Errors
+/// - [`CommitmentNotFound`] – no escrow for the given commitment.
+/// - [`EscrowNotTerminal`] – escrow is not `Spent` or `Refunded`.
+pub fn purge_escrow(env: &Env, commitment: BytesN<32>) -> Result<(), QuickexError> {
+    let commitment_bytes: Bytes = commitment.into();
+    let entry: EscrowEntry =
+        get_escrow(env, &commitment_bytes).ok_or(QuickexError::CommitmentNotFound)?;
+
+    match entry.status {
+        EscrowStatus::Spent | EscrowStatus::Refunded => {
+            remove_escrow_commitment_by_id(env, entry.escrow_id);
+            remove_escrow(env, &commitment_bytes);
+            Ok(())
+        }
+        EscrowStatus::Pending | EscrowStatus::Expired => Err(QuickexError::EscrowNotTerminal),
+    }
+}
+
+/// Look up the effective status of each commitment, positionally.
+///
+/// # Errors
+/// - [`BatchTooLarge`] – more than [`max_batch_size`] commitments.
+pub fn get_states(
+    env: &Env,
+    commitments: Vec<BytesN<32>>,
+) -> Result<Vec<Option<EscrowStatus>>, QuickexError> {
+    check_batch_size(env, commitments.len())?;
+
+    let mut states = Vec::new(env);
+    for commitment in commitments.iter() {
+        let state = get_escrow(env, &commitment.into()).map(|entry| effective_status(env, &entry));
+        states.push_back(state);
+    }
+    Ok(states)
+}
+
+/// Amount the owner would receive if they refunded the escrow now.
+///
+/// Uses the same eligibility rules as [`refund`]. No refund penalty or fee is
+/// currently deducted, so the net amount equals the escrowed amount.
+///
+/// Returns `None` if the escrow does not exist, is not refundable, or its amount
+/// is hidden from `caller` by the owner's privacy level or stealth mode.
+pub fn preview_refund(env: &Env, commitment: BytesN<32>, caller: &Address) -> Option<i128> {
+    let entry = get_escrow(env, &commitment.into())?;
+    if !privacy::can_view_amount(env, &entry.owner, caller)
+        || !privacy::can_see(env, &entry.owner, caller)
+    {
+        return None;
+    }
+    check_refundable(env, &entry).ok()?;
+    if entry.on_expiry_recipient.is_some() {
+        return None;
+    }
+    Some(entry.amount)
+}
+
+/// Commitment of `owner`'s oldest active escrow, including ones past expiry
+/// whether or not they have been marked `Expired`.
+///
+/// The owner index is kept in creation order, so the first active entry is the
+/// oldest.
+pub fn oldest_active_escrow(env: &Env, owner: &Address) -> Option<BytesN<32>> {
+    get_owner_escrows(env, owner).iter().find(|commitment| {
+        get_escrow(env, &commitment.clone().into()).is_some_and(|entry| {
+            matches!(entry.status, EscrowStatus::Pending | EscrowStatus::Expired)
+        })
+    })
+}
+
+/// Whether an escrow is likely stuck: `Pending`, with no expiry (so it can never
+/// be refunded), and at least the configured stuck age old.
+///
+/// Such an escrow can only ever be withdrawn, so if its salt was lost the funds
+/// are locked for good. Returns `false` for missing escrows.
+pub fn is_stuck(env: &Env, commitment: BytesN<32>) -> bool {
+    get_escrow(env, &commitment.into()).is_some_and(|entry| {
+        entry.status == EscrowStatus::Pending
+            && entry.expires_at == 0
+            && env.ledger().timestamp() >= entry.created_at.saturating_add(get_stuck_age(env))
+    })
+}
+
+/// Largest amount a withdrawal of the escrow could currently pay out:
+/// `min(entry.amount, contract balance of the token)`.
+///
+/// Returns 0 if the escrow does not exist, is not `Pending` (including escrows
+/// that have passed their expiry), or its amount is hidden from `caller` by the
+/// owner's privacy level or stealth mode.
+pub fn max_withdrawable(env: &Env, commitment: BytesN<32>, caller: &Address) -> i128 {
+    let Some(entry) = get_escrow(env, &commitment.into()) else {
+        return 0;
+    };
+    if !privacy::can_view_amount(env, &entry.owner, caller)
+        || !privacy::can_see(env, &entry.owner, caller)
+    {
+        return 0;
+    }
+    if effective_status(env, &entry) != EscrowStatus::Pending {
+        return 0;
+    }
+    let balance = token::Client::new(env, &entry.token).balance(&env.current_contract_address());
+    entry.amount.min(balance)
+}
+
+/// Forward an expired escrow to its `on_expiry_recipient`. Permissionless.
+///
+/// Uses the same expiry rules as [`refund`] and marks the escrow `Refunded`.
+///
+/// # Errors
+/// - [`CommitmentNotFound`] – no escrow for the given commitment.
+/// - [`AlreadySpent`] – escrow already in a terminal state.
+/// - [`EscrowNotExpired`] – escrow has no timeout or timeout not yet reached.
+/// - [`NoExpiryRecipient`] – escrow has no forward recipient; use [`refund`].
+pub fn sweep_expired_forward(env: &Env, commitment: BytesN<32>) -> Result<(), QuickexError> {
+    let entry: EscrowEntry =
+        get_escrow(env, &commitment.clone().into()).ok_or(QuickexError::CommitmentNotFound)?;
+
+    check_refundable(env, &entry)?;
+
+    let recipient = entry
+        .on_expiry_recipient
+        .clone()
+        .ok_or(QuickexError::NoExpiryRecipient)?;
+
+    if trip_if_insolvent(env, &entry.token) {
+        return Ok(());
+    }
+
+    let mut updated = entry.clone();
+    updated.status = EscrowStatus::Refunded;
+    record_terminal_escrow(env, &commitment, entry.status, &updated)?;
+
+    let token_client = token::Client::new(env, &entry.token);
+    token_client.transfer(&env.current_contract_address(), &recipient, &entry.amount);
+
+    events::publish_expiry_forwarded(env, commitment, recipient, entry.amount);
+
+    Ok(())
+}
+
+/// Send the funds of a long-abandoned expired escrow to `destination` and mark it
+/// `Refunded`.
+///
+/// The escrow must have been expired for at least the sweep grace period, which
+/// leaves the owner that long to refund it first. Escrows with an
+/// `on_expiry_recipient` can be forwarded by anyone and are never swept. The caller
+/// must already have been checked to be the admin.
+///
+/// # Errors
+/// - [`CommitmentNotFound`] – no escrow for the given commitment.
+/// - [`AlreadySpent`] – escrow already in a terminal state.
+/// - [`EscrowNotExpired`] – escrow has no expiry, or the grace period after it has
+///   not passed.
+/// - [`RefundDisabled`] – escrow forwards to its `on_expiry_recipient` instead.
+pub fn sweep_expired(
+    env: &Env,
+    admin: Address,
+    commitment: BytesN<32>,
+    destination: Address,
+) -> Result<(), QuickexError> {
+    let entry: EscrowEntry =
+        get_escrow(env, &commitment.clone().into()).ok_or(QuickexError::CommitmentNotFound)?;
+
+    check_refundable(env, &entry)?;
+
+    let sweepable_at = entry.expires_at.saturating_add(get_sweep_grace_period(env));
+    if entry.expires_at == 0 || env.ledger().timestamp() < sweepable_at {
+        return Err(QuickexError::EscrowNotExpired);
+    }
+
+    if entry.on_expiry_recipient.is_some() {
+        return Err(QuickexError::RefundDisabled);
+    }
+
+    if trip_if_insolvent(env, &entry.token) {
+        return Ok(());
+    }
+
+    let mut updated = entry.clone();
+    updated.status = EscrowStatus::Refunded;
+    record_terminal_escrow(env, &commitment, entry.status, &updated)?;
+
+    let token_client = token::Client::new(env, &entry.token);
+    token_client.transfer(&env.current_contract_address(), &destination, &entry.amount);
+
+    events::publish_swept(env, commitment, admin, destination, entry.amount);
+
+    Ok(())
+}
+
+/// Send the funds of a stuck escrow (see [`is_stuck`]) to `to` and mark it
+/// `Refunded`.
+///
+/// The caller must already have been checked to be the admin, and the contract to
+/// be paused.
+///
+/// # Errors
+/// - [`CommitmentNotFound`] – no escrow for the given commitment.
+/// - [`EscrowNotStuck`] – escrow is not `Pending`, has an expiry, or is younger
+///   than the stuck age.
+pub fn rescue_stuck(
+    env: &Env,
+    admin: Address,
+    commitment: BytesN<32>,
+    to: Address,
+) -> Result<(), QuickexError> {
+    let entry: EscrowEntry =
+        get_escrow(env, &commitment.clone().into()).ok_or(QuickexError::CommitmentNotFound)?;
+
+    if !is_stuck(env, commitment.clone()) {
+        return Err(QuickexError::EscrowNotStuck);
+    }
+
+    if trip_if_insolvent(env, &entry.token) {
+        return Ok(());
+    }
+
+    let mut updated = entry.clone();
+    updated.status = EscrowStatus::Refunded;
+    record_terminal_escrow(env, &commitment, entry.status, &updated)?;
+
+    let token_client = token::Client::new(env, &entry.token);
+    token_client.transfer(&env.current_contract_address(), &to, &entry.amount);
+
+    events::publish_rescued(env, commitment, admin, to, entry.amount);
+
+    Ok(())
+}
+
+/// Contract balance of `token` minus the amount tracked as locked in escrows.
+///
+/// Non-negative while the books are consistent; a negative value means escrows
+/// are owed more than the contract holds.
+pub fn solvency_check(env: &Env, token: &Address) -> i128 {
+    let balance = token::Client::new(env, token).balance(&env.current_contract_address());
+    balance - get_token_tvl(env, token)
+}
+
+/// Up to `limit` known tokens starting at index `start`, in first-use order.
+///
+/// `limit` is capped at [`max_batch_size`].
+pub fn list_tokens(env: &Env, start: u32, limit: u32) -> Vec<Address> {
+    let tokens = get_known_tokens(env);
+    let end = start
+        .saturating_add(limit.min(max_batch_size(env)))
+        .min(tokens.len());
+    if start >= end {
+        return Vec::new(env);
+    }
+    tokens.slice(start..end)
+}
+
+/// Escrow statistics for `owner`, or `None` if `caller` may not see their escrows'
+/// amounts.
+///
+/// # Errors
+/// - [`InternalError`] – the value sum overflows.
+pub fn owner_stats(
+    env: &Env,
+    owner: &Address,
+    caller: &Address,
+) -> Result<Option<OwnerStats>, QuickexError> {
+    if !privacy::can_view_amount(env, owner, caller) || !privacy::can_see(env, owner, caller) {
+        return Ok(None);
+    }
+    let commitments = get_owner_escrows(env, owner);
+    let mut total_value_locked: i128 = 0;
+    for commitment in commitments.iter() {
+        if let Some(entry) = get_escrow(env, &commitment.into()) {
+            total_value_locked = total_value_locked
+                .checked_add(entry.amount)
+                .ok_or(QuickexError::InternalError)?;
+        }
+    }
+    Ok(Some(OwnerStats {
+        active_count: commitments.len(),
+        total_created: get_owner_escrows_created(env, owner),
+        total_value_locked,
+    }))
+}
+
+/// Up to `limit` of `owner`'s non-terminal escrow commitments starting at index
+/// `start`, in creation order.
+///
+/// `limit` is capped at [`max_batch_size`]. Empty if `caller` may not see who owns
+/// `owner`'s escrows or may not see them at all.
+pub fn escrows_by_owner(
+    env: &Env,
+    owner: &Address,
+    start: u32,
+    limit: u32,
+    caller: &Address,
+) -> Vec<BytesN<32>> {
+    if !privacy::can_view_owner(env, owner, caller) || !privacy::can_see(env, owner, caller) {
+        return Vec::new(env);
+    }
+    let commitments = get_owner_escrows(env, owner);
+    let end = start
+        .saturating_add(limit.min(max_batch_size(env)))
+        .min(commitments.len());
+    if start >= end {
+        return Vec::new(env);
+    }
+    commitments.slice(start..end)
+}
+
+/// Commitments of escrows created with `from_ts <= created_at <= to_ts`, scanning
+/// escrow IDs `start..start + limit`.
+///
+/// `limit` is capped at [`max_batch_size`] and bounds the IDs scanned, not the
+/// matches returned. Purged escrows and those of owners in active stealth mode are
+/// skipped.
+pub fn escrows_created_between(
+    env: &Env,
+    from_ts: u64,
+    to_ts: u64,
+    start: u64,
+    limit: u32,
+) -> Vec<BytesN<32>> {
+    let mut matches = Vec::new(env);
+    for_each_escrow_by_id(env, start, limit, |commitment, entry| {
+        if (from_ts..=to_ts).contains(&entry.created_at)
+            && !privacy::is_stealth_active(env, &entry.owner)
+        {
+            matches.push_back(commitment);
+        }
+    });
+    matches
+}
+
+/// Total amount of `token` in escrows that have expired without being refunded,
+/// scanning escrow IDs `start..start + limit`.
+///
+/// `limit` is capped at [`max_batch_size`]. Counts `Pending` and legacy `Expired`
+/// escrows whose `expires_at` has passed.
+pub fn unrefunded_expired_value(
+    env: &Env,
+    token: &Address,
+    start: u64,
+    limit: u32,
+) -> Result<i128, QuickexError> {
+    let mut total: i128 = 0;
+    let mut overflow = false;
+    for_each_escrow_by_id(env, start, limit, |_, entry| {
+        let unrefunded = matches!(entry.status, EscrowStatus::Pending | EscrowStatus::Expired);
+        if entry.token == *token && unrefunded && is_expired(env, &entry) {
+            match total.checked_add(entry.amount) {
+                Some(sum) => total = sum,
+                None => overflow = true,
+            }
+        }
+    });
+    if overflow {
+        return Err(QuickexError::InternalError);
+    }
+    Ok(total)
+}
+
+/// Call `f` with the commitment and entry of every stored escrow whose ID is in
+/// `start..start + limit`, with `limit` capped at [`max_batch_size`].
+///
+/// IDs without an escrow, such as purged ones, are skipped.
+fn for_each_escrow_by_id(
+    env: &Env,
+    start: u64,
+    limit: u32,
+    mut f: impl FnMut(BytesN<32>, EscrowEntry),
+) {
+    let end = start
+        .saturating_add(limit.min(max_batch_size(env)) as u64)
+        .min(get_escrow_counter(env).saturating_add(1));
+    for escrow_id in start..end {
+        let Some(commitment) = get_escrow_commitment_by_id(env, escrow_id) else {
+            continue;
+        };
+        if let Some(entry) = get_escrow(env, &commitment.clone().into()) {
+            f(commitment, entry);
+        }
+    }
+}
+
+/// Check storage-wide bookkeeping invariants, returning each one's name and
+/// whether it holds.
+///
+/// - `counter_covers_active`: the escrow counter is at least the number of
+///   pending escrows.
+/// - `tvl_non_negative`: no known token has a negative stored TVL.
+/// - `status_counts_sum`: the status counts add up to exactly the number of
+///   escrows ever created, which is the escrow counter minus the IDs handed out by
+///   `create_escrow`.
+pub fn verify_storage_invariants(env: &Env) -> Vec<(Symbol, bool)> {
+    let counter = get_escrow_counter(env);
+    let counts = get_status_counts(env);
+    let total = counts
+        .pending
+        .checked_add(counts.spent)
+        .and_then(|sum| sum.checked_add(counts.refunded))
+        .and_then(|sum| sum.checked_add(counts.expired));
+    let tvl_non_negative = get_known_tokens(env)
+        .iter()
+        .all(|token| get_token_tvl(env, &token) >= 0);
+
+    vec![
+        env,
+        (
+            Symbol::new(env, "counter_covers_active"),
+            counter >= counts.pending,
+        ),
+        (Symbol::new(env, "tvl_non_negative"), tvl_non_negative),
+        (
+            Symbol::new(env, "status_counts_sum"),
+            total.is_some_and(|total| {
+                Some(total) == counter.checked_sub(get_placeholder_id_count(env))
+            }),
+        ),
+    ]
+}
+
+/// Record a snapshot of escrow activity, at most once per snapshot interval.
+///
+/// `tvl_sum` adds up the TVL of every known token, so it mixes units when several
+/// tokens are in use. Returns the recorded snapshot.
+///
+/// # Errors
+/// - [`SnapshotTooSoon`] – the previous snapshot is less than the interval old.
+/// - [`InternalError`] – a sum overflows.
+pub fn snapshot_activity(env: &Env) -> Result<ActivitySnapshot, QuickexError> {
+    let now = env.ledger().timestamp();
+    if let Some(last) = get_activity_snapshots(env).first() {
+        if now < last.timestamp.saturating_add(get_snapshot_interval(env)) {
+            return Err(QuickexError::SnapshotTooSoon);
+        }
+    }
+
+    let counts = get_status_counts(env);
+    let mut tvl_sum: i128 = 0;
+    for token in get_known_tokens(env).iter() {
+        tvl_sum = tvl_sum
+            .checked_add(get_token_tvl(env, &token))
+            .ok_or(QuickexError::InternalError)?;
+    }
+    let snapshot = ActivitySnapshot {
+        timestamp: now,
+        total_escrows: get_escrow_counter(env),
+        active_escrows: counts
+            .pending
+            .checked_add(counts.expired)
+            .ok_or(QuickexError::InternalError)?,
+        tvl_sum,
+    };
+    add_activity_snapshot(env, &snapshot);
+    Ok(snapshot)
+}
+
+/// Solvency delta of every known token, paged by position in the registry.
+///
+/// Returns up to [`max_batch_size`] `(token, balance - tvl)` pairs starting at
+/// index `start`; an empty vector means there are no more tokens.
+pub fn reconcile_all(env: &Env, start: u32) -> Vec<(Address, i128)> {
+    let tokens = get_known_tokens(env);
+    let end = start.saturating_add(max_batch_size(env)).min(tokens.len());
+    let mut deltas = Vec::new(env);
+    for i in start..end {
+        let token = tokens.get_unchecked(i);
+        let delta = solvency_check(env, &token);
+        deltas.push_back((token, delta));
+    }
+    deltas
 }
@@ -24,6 +24,7 @@ pub struct DepositToggledEvent {
     pub commitment: BytesN<32>,
     pub token: Address,
     pub amount: i128,
+    pub escrow_id: u64,
 }
 
 pub(crate) fn publish_privacy_toggled(env: &Env, owner: Address, enabled: bool, timestamp: u64) {
@@ -48,7 +49,6 @@ pub(crate) fn publish_contract_paused(env: &Env, paused: bool, timestamp: u64) {
     ContractPausedEvent { paused, timestamp }.publish(env);
 }
 
-#[allow(dead_code)]
 #[contractevent(topics = ["AdminChanged"])]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct AdminChangedEvent {
@@ -59,7 +59,6 @@ pub struct AdminChangedEvent {
     pub timestamp: u64,
 }
 
-#[allow(dead_code)]
 pub(crate) fn publish_admin_changed(
     env: &Env,
     old_admin: Address,
@@ -107,11 +106,18 @@ pub(crate) fn publish_withdraw_toggled(env: &Env, to: Address, commitment: Bytes
     .publish(env);
 }
 
-pub(crate) fn publish_deposit(env: &Env, commitment: BytesN<32>, token: Address, amount: i128) {
+pub(crate) fn publish_deposit(
+    env: &Env,
+    commitment: BytesN<32>,
+    token: Address,
+    amount: i128,
+    escrow_id: u64,
+) {
     DepositToggledEvent {
         commitment,
         token,
         amount,
+        escrow_id,
     }
     .publish(env);
 }
@@ -135,3 +141,358 @@ pub(crate) fn publish_refunded(env: &Env, owner: Address, commitment: BytesN<32>
     }
     .publish(env);
 }
+
+/// Emitted when an expired escrow is forwarded to its designated recipient
+/// instead of being refunded to the owner.
+#[contractevent(topics = ["ExpiryForwarded"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExpiryForwardedEvent {
+    #[topic]
+    pub commitment: BytesN<32>,
+    pub recipient: Address,
+    pub amount: i128,
+    pub timestamp: u64,
+}
+
+pub(crate) fn publish_expiry_forwarded(
+    env: &Env,
+    commitment: BytesN<32>,
+    recipient: Address,
+    amount: i128,
+) {
+    ExpiryForwardedEvent {
+        commitment,
+        recipient,
+        amount,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(env);
+}
+
+/// Emitted when a pending escrow past its expiry is marked `Expired`.
+#[contractevent(topics = ["EscrowExpired"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowExpiredEvent {
+    #[topic]
+    pub commitment: BytesN<32>,
+    pub owner: Address,
+    pub expires_at: u64,
+}
+
+pub(crate) fn publish_escrow_expired(
+    env: &Env,
+    commitment: BytesN<32>,
+    owner: Address,
+    expires_at: u64,
+) {
+    EscrowExpiredEvent {
+        commitment,
+        owner,
+        expires_at,
+    }
+    .publish(env);
+}
+
+/// Emitted when matured installments of an installment escrow are released.
+#[contractevent(topics = ["InstallmentsReleased"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InstallmentsReleasedEvent {
+    #[topic]
+    pub commitment: BytesN<32>,
+    pub to: Address,
+    pub amount: i128,
+    pub claimed: u32,
+}
+
+pub(crate) fn publish_installments_released(
+    env: &Env,
+    commitment: BytesN<32>,
+    to: Address,
+    amount: i128,
+    claimed: u32,
+) {
+    InstallmentsReleasedEvent {
+        commitment,
+        to,
+        amount,
+        claimed,
+    }
+    .publish(env);
+}
+
+/// Emitted when the contract pauses itself because the tracked TVL of a token
+/// exceeds the contract's actual balance of it.
+#[contractevent(topics = ["AutoPaused"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AutoPausedEvent {
+    #[topic]
+    pub token: Address,
+    pub tvl: i128,
+    pub balance: i128,
+    pub timestamp: u64,
+}
+
+pub(crate) fn publish_auto_paused(env: &Env, token: Address, tvl: i128, balance: i128) {
+    AutoPausedEvent {
+        token,
+        tvl,
+        balance,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(env);
+}
+
+/// Emitted by `attest_no_escrow`, giving a timestamped on-chain record of whether
+/// an escrow existed for a commitment.
+#[contractevent(topics = ["NonExistenceChecked"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NonExistenceCheckedEvent {
+    #[topic]
+    pub commitment: BytesN<32>,
+    pub ledger: u32,
+    pub exists: bool,
+    pub timestamp: u64,
+}
+
+pub(crate) fn publish_non_existence_checked(
+    env: &Env,
+    commitment: BytesN<32>,
+    ledger: u32,
+    exists: bool,
+) {
+    NonExistenceCheckedEvent {
+        commitment,
+        ledger,
+        exists,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(env);
+}
+
+/// Emitted when part of an escrow is withdrawn, with the balance left in it.
+#[contractevent(topics = ["PartialWithdraw"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PartialWithdrawEvent {
+    #[topic]
+    pub commitment: BytesN<32>,
+    pub to: Address,
+    pub amount: i128,
+    pub remaining: i128,
+}
+
+pub(crate) fn publish_partial_withdraw(
+    env: &Env,
+    commitment: BytesN<32>,
+    to: Address,
+    amount: i128,
+    remaining: i128,
+) {
+    PartialWithdrawEvent {
+        commitment,
+        to,
+        amount,
+        remaining,
+    }
+    .publish(env);
+}
+
+/// Emitted when a contributor adds funds to a pooled escrow.
+#[contractevent(topics = ["PoolContribution"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PoolContributionEvent {
+    #[topic]
+    pub commitment: BytesN<32>,
+    pub contributor: Address,
+    pub amount: i128,
+    pub share: i128,
+}
+
+pub(crate) fn publish_pool_contribution(
+    env: &Env,
+    commitment: BytesN<32>,
+    contributor: Address,
+    amount: i128,
+    share: i128,
+) {
+    PoolContributionEvent {
+        commitment,
+        contributor,
+        amount,
+        share,
+    }
+    .publish(env);
+}
+
+/// Emitted when a contributor withdraws their share of a released pool.
+#[contractevent(topics = ["PoolShareWithdrawn"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PoolShareWithdrawnEvent {
+    #[topic]
+    pub commitment: BytesN<32>,
+    pub contributor: Address,
+    pub share: i128,
+}
+
+pub(crate) fn publish_pool_share_withdrawn(
+    env: &Env,
+    commitment: BytesN<32>,
+    contributor: Address,
+    share: i128,
+) {
+    PoolShareWithdrawnEvent {
+        commitment,
+        contributor,
+        share,
+    }
+    .publish(env);
+}
+
+/// Emitted when the admin turns the global privacy kill-switch on or off.
+#[contractevent(topics = ["GlobalPrivacyDisabled"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GlobalPrivacyDisabledEvent {
+    pub admin: Address,
+    pub disabled: bool,
+    pub timestamp: u64,
+}
+
+pub(crate) fn publish_global_privacy_disabled(env: &Env, admin: Address, disabled: bool) {
+    GlobalPrivacyDisabledEvent {
+        admin,
+        disabled,
+        timestamp: env.ledger().timestamp(),
+    }
+    .publish(env);
+}
+
+/// Emitted when the admin lapses a pending escrow ahead of its natural expiry.
+#[contractevent(topics = ["ForceExpired"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ForceExpiredEvent {
+    #[topic]
+    pub commitment: BytesN<32>,
+    pub admin: Address,
+    pub expires_at: u64,
+}
+
+pub(crate) fn publish_force_expired(
+    env: &Env,
+    commitment: BytesN<32>,
+    admin: Address,
+    expires_at: u64,
+) {
+    ForceExpiredEvent {
+        commitment,
+        admin,
+        expires_at,
+    }
+    .publish(env);
+}
+
+/// Emitted when the admin sweeps a long-abandoned expired escrow.
+#[contractevent(topics = ["Swept"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SweptEvent {
+    #[topic]
+    pub commitment: BytesN<32>,
+    pub admin: Address,
+    pub destination: Address,
+    pub amount: i128,
+}
+
+pub(crate) fn publish_swept(
+    env: &Env,
+    commitment: BytesN<32>,
+    admin: Address,
+    destination: Address,
+    amount: i128,
+) {
+    SweptEvent {
+        commitment,
+        admin,
+        destination,
+        amount,
+    }
+    .publish(env);
+}
+
+/// Emitted when the admin rescues a stuck non-expiring escrow.
+#[contractevent(topics = ["Rescued"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RescuedEvent {
+    #[topic]
+    pub commitment: BytesN<32>,
+    pub admin: Address,
+    pub to: Address,
+    pub amount: i128,
+}
+
+pub(crate) fn publish_rescued(
+    env: &Env,
+    commitment: BytesN<32>,
+    admin: Address,
+    to: Address,
+    amount: i128,
+) {
+    RescuedEvent {
+        commitment,
+        admin,
+        to,
+        amount,
+    }
+    .publish(env);
+}
+
+/// Emitted when an escrow is withdrawn through a DEX with `withdraw_and_swap`.
+#[contractevent(topics = ["Swapped"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SwappedEvent {
+    #[topic]
+    pub commitment: BytesN<32>,
+    pub to: Address,
+    pub net: i128,
+    pub out_token: Address,
+    pub out: i128,
+}
+
+pub(crate) fn publish_swapped(
+    env: &Env,
+    commitment: BytesN<32>,
+    to: Address,
+    net: i128,
+    out_token: Address,
+    out: i128,
+) {
+    SwappedEvent {
+        commitment,
+        to,
+        net,
+        out_token,
+        out,
+    }
+    .publish(env);
+}
+
+/// Emitted when an owner pushes back an escrow's expiry with `extend_timeout`.
+#[contractevent(topics = ["TimeoutExtended"])]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TimeoutExtendedEvent {
+    #[topic]
+    pub commitment: BytesN<32>,
+    pub old_expires_at: u64,
+    pub new_expires_at: u64,
+}
+
+pub(crate) fn publish_timeout_extended(
+    env: &Env,
+    commitment: BytesN<32>,
+    old_expires_at: u64,
+    new_expires_at: u64,
+) {
+    TimeoutExtendedEvent {
+        commitment,
+        old_expires_at,
+        new_expires_at,
+    }
+    .publish(env);
+}
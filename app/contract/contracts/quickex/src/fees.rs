@@ -0,0 +1,81 @@
+//! Withdrawal fees.
+//!
+//! The admin configures a fee in basis points, with an optional reduced rate for
+//! recipients that have privacy enabled. Fees are deducted from each payout and
+//! sent to the admin.
+
+use soroban_sdk::{token, Address, Env};
+
+use crate::{
+    errors::QuickexError,
+    privacy,
+    storage::{add_fees_collected, get_admin, get_fee_config},
+    types::FeeConfig,
+};
+
+/// Denominator for basis-point rates (100% = 10 000 bps).
+pub const BPS_DENOMINATOR: u32 = 10_000;
+
+/// Validate a fee configuration.
+///
+/// # Errors
+/// - [`InvalidFee`] – a rate exceeds [`BPS_DENOMINATOR`].
+pub fn check_fee_config(config: &FeeConfig) -> Result<(), QuickexError> {
+    let private = config.fee_bps_private.unwrap_or(config.fee_bps);
+    if config.fee_bps > BPS_DENOMINATOR || private > BPS_DENOMINATOR {
+        return Err(QuickexError::InvalidFee);
+    }
+    Ok(())
+}
+
+/// Fee rate that applies to withdrawals paid to `to`.
+pub fn fee_bps_for(env: &Env, to: &Address) -> u32 {
+    let config = get_fee_config(env);
+    if privacy::get_privacy(env, to.clone()) {
+        config.fee_bps_private.unwrap_or(config.fee_bps)
+    } else {
+        config.fee_bps
+    }
+}
+
+/// Fee owed on `amount` at `fee_bps`, rounded down.
+pub fn compute_fee(amount: i128, fee_bps: u32) -> Result<i128, QuickexError> {
+    amount
+        .checked_mul(fee_bps as i128)
+        .map(|scaled| scaled / BPS_DENOMINATOR as i128)
+        .ok_or(QuickexError::InternalError)
+}
+
+/// Transfer `amount` of `token` from the contract to `to`, less the withdrawal fee.
+/// Returns the net amount received by `to`.
+pub fn pay_out(
+    env: &Env,
+    token: &Address,
+    to: &Address,
+    amount: i128,
+) -> Result<i128, QuickexError> {
+    let net = take_fee(env, token, to, amount)?;
+    token::Client::new(env, token).transfer(&env.current_contract_address(), to, &net);
+    Ok(net)
+}
+
+/// Send the withdrawal fee on a payout of `amount` to `to` to the admin and add it
+/// to the token's collected total. Returns the net amount left for `to`, which
+/// stays in the contract.
+pub fn take_fee(
+    env: &Env,
+    token: &Address,
+    to: &Address,
+    amount: i128,
+) -> Result<i128, QuickexError> {
+    let Some(admin) = get_admin(env) else {
+        return Ok(amount);
+    };
+
+    let fee = compute_fee(amount, fee_bps_for(env, to))?;
+    if fee > 0 {
+        token::Client::new(env, token).transfer(&env.current_contract_address(), &admin, &fee);
+        add_fees_collected(env, token, fee)?;
+    }
+    Ok(amount - fee)
+}
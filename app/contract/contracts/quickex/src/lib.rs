@@ -1,13 +1,19 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, Address, Bytes, BytesN, Env, Vec};
+use soroban_sdk::{
+    contract, contractimpl, symbol_short, token, Address, Bytes, BytesN, Env, Symbol, Vec,
+};
 
 mod admin;
 mod commitment;
 #[cfg(test)]
 mod commitment_test;
+mod dex;
 mod errors;
 mod escrow;
 mod events;
+mod fees;
+mod merkle;
+mod pool;
 mod privacy;
 mod storage;
 #[cfg(test)]
@@ -18,7 +24,11 @@ mod types;
 
 use errors::QuickexError;
 use storage::*;
-use types::{EscrowEntry, EscrowStatus, PrivacyAwareEscrowView};
+use types::{
+    AccountExport, ActivitySnapshot, CommitmentOpening, EscrowEntry, EscrowOptions, EscrowStatus,
+    FeeConfig, NonExistenceAttestation, OwnerStats, Pool, PrivacyAwareEscrowView, PrivacyState,
+    StatusCounts, UpgradeInfo,
+};
 
 /// QuickEx Privacy Contract
 ///
@@ -30,11 +40,47 @@ use types::{EscrowEntry, EscrowStatus, PrivacyAwareEscrowView};
 /// ```text
 /// [*] --> Pending  : deposit() / deposit_with_commitment()
 /// Pending --> Spent    : withdraw(proof)  [now < expires_at, or no expiry]
+/// Pending --> Expired  : mark_expired()   [now >= expires_at]
 /// Pending --> Refunded : refund(owner)    [now >= expires_at]
+/// Expired --> Refunded : refund(owner)
+/// Pending --> Refunded : rescue_stuck(admin)  [no expiry, stuck age reached, paused]
+/// Spent    --> Pending : re-deposit  [terminal re-deposit enabled]
+/// Refunded --> Pending : re-deposit  [terminal re-deposit enabled]
 /// ```
 #[contract]
 pub struct QuickexContract;
 
+/// Ensure `caller` is the stored admin and has authorized the invocation.
+fn require_admin(env: &Env, caller: &Address) -> Result<(), QuickexError> {
+    let admin = get_admin(env).ok_or(QuickexError::Unauthorized)?;
+    if *caller != admin {
+        return Err(QuickexError::Unauthorized);
+    }
+    caller.require_auth();
+    Ok(())
+}
+
+/// Operation symbol covering every entry point that creates an escrow.
+const OP_DEPOSIT: Symbol = symbol_short!("deposit");
+
+/// Require `caller` to be the admin if the admin has gated `operation`.
+///
+/// Ungated operations, the default, are open to everyone. Does not require auth
+/// itself; the gated entry points already authenticate `caller`.
+fn require_operation_allowed(
+    env: &Env,
+    operation: Symbol,
+    caller: &Address,
+) -> Result<(), QuickexError> {
+    if !is_operation_gated(env, &operation) {
+        return Ok(());
+    }
+    match get_admin(env) {
+        Some(admin) if admin == *caller => Ok(()),
+        _ => Err(QuickexError::Unauthorized),
+    }
+}
+
 #[contractimpl]
 impl QuickexContract {
     /// Withdraw escrowed funds by proving commitment ownership.
@@ -44,7 +90,7 @@ impl QuickexContract {
     ///
     /// # Arguments
     /// * `env` - The contract environment
-    /// * `_token` - Reserved; token is stored in the escrow entry
+    /// * `token` - Token the caller expects to receive; must match the escrow's token
     /// * `amount` - Amount to withdraw; must be positive and match the escrow amount
     /// * `commitment` - Commitment hash for the escrow being withdrawn
     /// * `to` - Recipient address (must authorize the call)
@@ -58,9 +104,16 @@ impl QuickexContract {
     /// * `EscrowExpired` - Escrow has passed its expiry timestamp
     /// * `AlreadySpent` - Escrow has already been withdrawn or refunded
     /// * `InvalidCommitment` - Escrow amount does not match the requested amount
+    /// * `CommitmentMismatch` - Escrow was created with a salt hash; use `withdraw_with_salt_hash`
+    /// * `InvalidOwner` - Strict owner check is enabled and the stored owner is not `to`
+    /// * `RecipientNotAllowed` - The escrow restricts its recipient and `to` is not it
+    /// * `TokenMismatch` - `token` is not the token held by the escrow
+    /// * `InsufficientConfirmations` - The escrow's `min_confirmations` ledgers have not yet closed
+    /// * `EscrowNotYetClaimable` - The escrow's cliff has not passed
+    /// * `PrerequisiteNotMet` - The escrow is chained after one that is not yet spent
     pub fn withdraw(
         env: Env,
-        _token: &Address,
+        token: &Address,
         amount: i128,
         _commitment: BytesN<32>,
         to: Address,
@@ -69,7 +122,7 @@ impl QuickexContract {
         if is_paused(&env) {
             return Err(QuickexError::ContractPaused);
         }
-        escrow::withdraw(&env, amount, to, salt)
+        escrow::withdraw(&env, token, amount, to, salt)
     }
 
     /// Set a numeric privacy level for an account (legacy/level-based API).
@@ -79,9 +132,12 @@ impl QuickexContract {
     ///
     /// # Arguments
     /// * `env` - The contract environment
-    /// * `account` - The account to configure
-    /// * `privacy_level` - Numeric level (0 = off, higher = more privacy; interpretation is application-specific)
+    /// * `account` - The account to configure (must authorize)
+    /// * `privacy_level` - Numeric level controlling what strangers see in
+    ///   [`get_escrow_details`](QuickexContract::get_escrow_details): 0 = everything,
+    ///   1 = amount hidden, 2 = amount and owner hidden, 3+ = only the status
     pub fn enable_privacy(env: Env, account: Address, privacy_level: u32) -> bool {
+        account.require_auth();
         set_privacy_level(&env, &account, privacy_level);
         add_privacy_history(&env, &account, privacy_level);
         true
@@ -100,7 +156,7 @@ impl QuickexContract {
 
     /// Get the history of privacy level changes for an account.
     ///
-    /// Returns a vector of levels in chronological order (oldest first).
+    /// Returns a vector of levels, newest first, capped at the most recent 64 changes.
     ///
     /// # Arguments
     /// * `env` - The contract environment
@@ -126,344 +182,2731 @@ impl QuickexContract {
         privacy::set_privacy(&env, owner, enabled)
     }
 
-    /// Check the current privacy status of an account
+    /// Enable or disable stealth mode for an owner's escrows.
+    ///
+    /// In stealth mode [`get_escrow_details`](QuickexContract::get_escrow_details)
+    /// returns `None` to anyone but the owner and granted viewers, exactly as if the
+    /// escrow did not exist. This is stronger than privacy redaction, which still
+    /// reveals the token and status. Stealth is independent of the privacy flag.
+    /// Disabled by default.
     ///
     /// # Arguments
     /// * `env` - The contract environment
-    /// * `owner` - The account address to query
-    ///
-    /// # Returns
-    /// * `bool` - Current privacy status (true = enabled)
-    pub fn get_privacy(env: Env, owner: Address) -> bool {
-        privacy::get_privacy(&env, owner)
+    /// * `owner` - The account address to configure (must authorize)
+    /// * `enabled` - `true` to hide the owner's escrows from strangers
+    pub fn set_stealth(env: Env, owner: Address, enabled: bool) {
+        privacy::set_stealth(&env, owner, enabled);
     }
 
-    /// Deposit funds and create an escrow entry keyed by `SHA256(owner || amount || salt)`.
+    /// Check whether an account has stealth mode enabled.
+    pub fn is_stealth(env: Env, owner: Address) -> bool {
+        is_stealth(&env, &owner)
+    }
+
+    /// Enable or disable privacy, returning how the owner's active escrows now look to
+    /// a stranger.
     ///
-    /// Transfers `amount` from `owner` to the contract and stores an escrow entry.
+    /// Lets the owner confirm in the same call that redaction took effect. Each view
+    /// is what [`get_escrow_details`](QuickexContract::get_escrow_details) returns to
+    /// a caller who is neither the owner nor a granted viewer.
     ///
     /// # Arguments
     /// * `env` - The contract environment
-    /// * `token` - The token contract address
-    /// * `amount` - Amount to deposit; must be positive
-    /// * `owner` - Owner of the funds (must authorize)
-    /// * `salt` - Random salt (0–1024 bytes) for uniqueness
-    /// * `timeout_secs` - Seconds from now until the escrow expires (0 = no expiry)
+    /// * `owner` - The account address to configure (must authorize)
+    /// * `enabled` - `true` to enable privacy, `false` to disable
     ///
     /// # Errors
-    /// * `InvalidAmount` - Amount is zero or negative
-    /// * `InvalidSalt` - Salt length exceeds 1024 bytes
     /// * `ContractPaused` - Contract is currently paused
-    /// * `CommitmentAlreadyExists` - An escrow for this commitment already exists
-    pub fn deposit(
+    /// * `PrivacyAlreadySet` - Privacy state is already at the requested value
+    pub fn set_privacy_and_preview(
         env: Env,
-        token: Address,
-        amount: i128,
         owner: Address,
-        salt: Bytes,
-        timeout_secs: u64,
-    ) -> Result<BytesN<32>, QuickexError> {
+        enabled: bool,
+    ) -> Result<Vec<PrivacyAwareEscrowView>, QuickexError> {
         if is_paused(&env) {
             return Err(QuickexError::ContractPaused);
         }
-        escrow::deposit(&env, token, amount, owner, salt, timeout_secs)
+        privacy::set_privacy_and_preview(&env, owner, enabled)
     }
 
-    /// Create a deterministic commitment hash for an amount (off-chain / pre-deposit use).
-    ///
-    /// Computes `SHA256(owner || amount || salt)`. Not a zero-knowledge proof; same inputs
-    /// always yield the same hash. Use for API shape validation and audit trails.
+    /// Check the current privacy status of an account
     ///
     /// # Arguments
     /// * `env` - The contract environment
-    /// * `owner` - The owner address
-    /// * `amount` - Non-negative amount in token base units
-    /// * `salt` - Random bytes (0–1024 bytes) for uniqueness
+    /// * `owner` - The account address to query
     ///
-    /// # Errors
-    /// * `InvalidAmount` - Amount is negative
-    /// * `InvalidSalt` - Salt length exceeds 1024 bytes
-    pub fn create_amount_commitment(
-        env: Env,
-        owner: Address,
-        amount: i128,
-        salt: Bytes,
-    ) -> Result<BytesN<32>, QuickexError> {
-        commitment::create_amount_commitment(&env, owner, amount, salt)
+    /// # Returns
+    /// * `bool` - Current privacy status (true = enabled). Always `false` while
+    ///   privacy is disabled globally; see
+    ///   [`disable_privacy_globally`](QuickexContract::disable_privacy_globally).
+    pub fn get_privacy(env: Env, owner: Address) -> bool {
+        privacy::is_privacy_active(&env, &owner)
     }
 
-    /// Verify that a commitment hash matches the given `owner`, `amount`, and `salt`.
+    /// Copy an account's boolean privacy flag into the level-based system.
     ///
-    /// Recomputes the commitment and compares. Returns `false` if inputs are invalid or don't match.
+    /// Sets the privacy level to 2 (amount and owner hidden, matching what the flag
+    /// already hides) if boolean privacy is enabled and to 0 otherwise,
+    /// appends it to the privacy history, and returns it. Afterwards
+    /// [`get_privacy`](QuickexContract::get_privacy) and
+    /// [`privacy_status`](QuickexContract::privacy_status) agree.
+    ///
+    /// The boolean flag is not cleared: [`get_privacy`](QuickexContract::get_privacy)
+    /// and the choice of the private withdrawal fee rate still read it, so clearing it
+    /// would change both.
     ///
     /// # Arguments
     /// * `env` - The contract environment
-    /// * `commitment` - 32-byte commitment hash to verify
-    /// * `owner` - Claimed owner
-    /// * `amount` - Claimed amount (must be non-negative)
-    /// * `salt` - Salt used when creating the commitment
-    pub fn verify_amount_commitment(
-        env: Env,
-        commitment: BytesN<32>,
-        owner: Address,
-        amount: i128,
-        salt: Bytes,
-    ) -> bool {
-        commitment::verify_amount_commitment(&env, commitment, owner, amount, salt)
+    /// * `account` - The account to migrate (must authorize)
+    ///
+    /// # Errors
+    /// * `ContractPaused` - Contract is currently paused
+    pub fn migrate_privacy_to_level(env: Env, account: Address) -> Result<u32, QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
+        }
+        Ok(privacy::migrate_privacy_to_level(&env, account))
     }
 
-    /// Create an escrow record and increment the global escrow counter.
+    /// Get the boolean privacy flag of an account as a tri-state.
     ///
-    /// Returns the new counter value. Parameters `_from`, `_to`, `_amount` are reserved for
-    /// future use; the implementation only increments the counter.
+    /// Unlike [`get_privacy`](QuickexContract::get_privacy), returns `Unset` for an
+    /// account that has never set privacy, and `Disabled` only for one that turned it off.
     ///
     /// # Arguments
     /// * `env` - The contract environment
-    /// * `_from` - Reserved (depositor address for future use)
-    /// * `_to` - Reserved (recipient address for future use)
-    /// * `_amount` - Reserved (amount for future use)
-    pub fn create_escrow(env: Env, _from: Address, _to: Address, _amount: u64) -> u64 {
-        increment_escrow_counter(&env)
+    /// * `account` - The account address to query
+    pub fn privacy_state(env: Env, account: Address) -> PrivacyState {
+        privacy::get_privacy_state(&env, account)
     }
 
-    /// Health check for deployment and monitoring.
+    /// Get the number of accounts that currently have boolean privacy enabled.
     ///
-    /// Returns `true` if the contract is deployed and callable. No state or auth required.
-    pub fn health_check() -> bool {
-        true
+    /// Maintained incrementally by [`set_privacy`](QuickexContract::set_privacy), so it
+    /// can be read without scanning accounts.
+    pub fn privacy_adoption(env: Env) -> u64 {
+        get_privacy_enabled_count(&env)
     }
 
-    /// Deposit funds using a pre-generated 32-byte commitment hash.
+    /// Grant `viewer` access to the full details of `owner`'s private escrows.
     ///
-    /// Transfers `amount` from `from` to the contract and stores an escrow keyed by
-    /// `commitment`. The depositor must authorize. Use when the commitment was created
-    /// off-chain or via [`create_amount_commitment`](QuickexContract::create_amount_commitment).
+    /// Granting an existing viewer again is a no-op.
     ///
     /// # Arguments
     /// * `env` - The contract environment
-    /// * `from` - Depositor (must authorize the token transfer)
-    /// * `token` - Token contract address
-    /// * `amount` - Amount to deposit; must be positive
-    /// * `commitment` - 32-byte commitment hash (must be unique)
-    /// * `timeout_secs` - Seconds from now until the escrow expires (0 = no expiry)
+    /// * `owner` - The account granting access (must authorize)
+    /// * `viewer` - The account receiving access
     ///
     /// # Errors
-    /// * `InvalidAmount` - Amount is zero or negative
     /// * `ContractPaused` - Contract is currently paused
-    /// * `CommitmentAlreadyExists` - An escrow for this commitment already exists
-    pub fn deposit_with_commitment(
-        env: Env,
-        from: Address,
-        token: Address,
-        amount: i128,
-        commitment: BytesN<32>,
-        timeout_secs: u64,
-    ) -> Result<(), QuickexError> {
+    /// * `TooManyViewers` - The owner already has the maximum of 50 viewers
+    pub fn grant_viewer(env: Env, owner: Address, viewer: Address) -> Result<(), QuickexError> {
         if is_paused(&env) {
             return Err(QuickexError::ContractPaused);
         }
-        escrow::deposit_with_commitment(&env, from, token, amount, commitment, timeout_secs)
+        privacy::grant_viewer(&env, owner, viewer)
     }
 
-    /// Refund an expired escrow back to its original owner.
+    /// Grant several viewers at once, with a single authorization from `owner`.
     ///
-    /// Can only be called after `expires_at` is reached. The caller must be the
-    /// original depositor. The escrow must still be `Pending`.
+    /// Viewers already granted, or listed twice, are skipped. An owner may have at
+    /// most 50 viewers in total.
     ///
     /// # Arguments
     /// * `env` - The contract environment
-    /// * `commitment` - 32-byte commitment hash identifying the escrow
-    /// * `caller` - Must equal the original depositor (must authorize)
+    /// * `owner` - The account granting access (must authorize)
+    /// * `viewers` - Accounts receiving access (at most the max batch size, default 50)
     ///
     /// # Errors
-    /// * `CommitmentNotFound` - No escrow exists for the commitment
-    /// * `AlreadySpent` - Escrow is already in a terminal state
-    /// * `EscrowNotExpired` - Escrow has no expiry or has not yet expired
-    /// * `InvalidOwner` - Caller is not the original owner
-    pub fn refund(env: Env, commitment: BytesN<32>, caller: Address) -> Result<(), QuickexError> {
-        escrow::refund(&env, commitment, caller)
+    /// * `ContractPaused` - Contract is currently paused
+    /// * `BatchTooLarge` - More viewers supplied than the max batch size
+    /// * `TooManyViewers` - The grants would take the owner past 50 viewers
+    pub fn grant_viewers(
+        env: Env,
+        owner: Address,
+        viewers: Vec<Address>,
+    ) -> Result<(), QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
+        }
+        privacy::grant_viewers(&env, owner, viewers)
     }
 
-    /// Initialize the contract with an admin address (one-time only).
+    /// Revoke several viewers at once, with a single authorization from `owner`.
     ///
-    /// Sets the admin who can pause/unpause, transfer admin, and upgrade the contract.
+    /// Viewers without a grant are skipped.
     ///
     /// # Arguments
     /// * `env` - The contract environment
-    /// * `admin` - The admin address to set
+    /// * `owner` - The granting account (must authorize)
+    /// * `viewers` - Accounts losing access (at most the max batch size, default 50)
     ///
     /// # Errors
-    /// * `AlreadyInitialized` - Contract has already been initialized
-    pub fn initialize(env: Env, admin: Address) -> Result<(), QuickexError> {
-        if get_admin(&env).is_some() {
-            return Err(QuickexError::AlreadyInitialized);
+    /// * `ContractPaused` - Contract is currently paused
+    /// * `BatchTooLarge` - More viewers supplied than the max batch size
+    pub fn revoke_viewers(
+        env: Env,
+        owner: Address,
+        viewers: Vec<Address>,
+    ) -> Result<(), QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
         }
-        set_admin(&env, &admin);
-        Ok(())
+        privacy::revoke_viewers(&env, owner, viewers)
     }
 
-    /// Pause or unpause the contract (**Admin only**).
-    ///
-    /// When paused, certain operations may be blocked. Caller must equal the stored admin.
+    /// Revoke a viewer previously granted by `owner`. No-op if no grant exists.
     ///
     /// # Arguments
     /// * `env` - The contract environment
-    /// * `caller` - Caller address (must equal admin)
-    /// * `new_state` - `true` to pause, `false` to unpause
+    /// * `owner` - The account revoking access (must authorize)
+    /// * `viewer` - The account losing access
     ///
     /// # Errors
-    /// * `Unauthorized` - Caller is not the admin, or admin not set
-    pub fn set_paused(env: Env, caller: Address, new_state: bool) -> Result<(), QuickexError> {
-        let admin = get_admin(&env).ok_or(QuickexError::Unauthorized)?;
-        if caller != admin {
-            return Err(QuickexError::Unauthorized);
+    /// * `ContractPaused` - Contract is currently paused
+    pub fn revoke_viewer(env: Env, owner: Address, viewer: Address) -> Result<(), QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
         }
-        set_paused(&env, new_state);
+        privacy::revoke_viewer(&env, owner, viewer);
         Ok(())
     }
 
-    /// Transfer admin rights to a new address (**Admin only**).
-    ///
-    /// Caller must equal the current admin. The new admin can later transfer again.
+    /// List the viewers `owner` has granted access to, in grant order.
     ///
     /// # Arguments
     /// * `env` - The contract environment
-    /// * `caller` - Caller address (must equal current admin)
-    /// * `new_admin` - New admin address
-    ///
-    /// # Errors
-    /// * `Unauthorized` - Caller is not the admin, or admin not set
-    pub fn set_admin(env: Env, caller: Address, new_admin: Address) -> Result<(), QuickexError> {
-        let admin = get_admin(&env).ok_or(QuickexError::Unauthorized)?;
-        if caller != admin {
-            return Err(QuickexError::Unauthorized);
-        }
-        set_admin(&env, &new_admin);
-        Ok(())
+    /// * `owner` - The granting account (must authorize)
+    pub fn my_granted_viewers(env: Env, owner: Address) -> Vec<Address> {
+        owner.require_auth();
+        get_viewers(&env, &owner)
     }
 
-    /// Check if the contract is currently paused.
+    /// Export an account's privacy configuration and active escrow commitments.
     ///
-    /// Returns `true` if paused, `false` otherwise.
-    pub fn is_paused(env: Env) -> bool {
-        is_paused(&env)
-    }
-
-    /// Get the current admin address.
+    /// Intended for off-chain backup or for migrating an account to a new deployment.
     ///
-    /// Returns `None` if the contract has not been initialized.
-    pub fn get_admin(env: Env) -> Option<Address> {
-        get_admin(&env)
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The account to export (must authorize)
+    pub fn export_account_state(env: Env, owner: Address) -> AccountExport {
+        privacy::export_account_state(&env, owner)
     }
 
-    /// Get the status of an escrow by its commitment hash (read-only).
-    ///
-    /// Returns `Pending`, `Spent`, `Expired`, or `Refunded` if an escrow exists; `None` otherwise.
+    /// List the owners whose private escrows `viewer` has been granted access to.
     ///
     /// # Arguments
     /// * `env` - The contract environment
-    /// * `commitment` - 32-byte commitment hash used as the escrow key
-    pub fn get_commitment_state(env: Env, commitment: BytesN<32>) -> Option<EscrowStatus> {
-        let commitment_bytes: Bytes = commitment.into();
-        let entry: Option<EscrowEntry> = get_escrow(&env, &commitment_bytes);
-        entry.map(|e| e.status)
+    /// * `viewer` - The viewing account (must authorize)
+    pub fn escrows_i_can_view(env: Env, viewer: Address) -> Vec<Address> {
+        viewer.require_auth();
+        get_viewer_of(&env, &viewer)
     }
 
-    /// Verify withdrawal parameters without submitting a transaction (read-only).
+    /// Count the owners that have granted `viewer` access to their private escrows.
     ///
-    /// Recomputes the commitment from `amount`, `salt`, and `owner`, then checks that an
-    /// escrow exists with status `Pending`, matching amount, and not yet expired.
+    /// The length of [`escrows_i_can_view`](QuickexContract::escrows_i_can_view),
+    /// without returning the owners themselves.
     ///
     /// # Arguments
     /// * `env` - The contract environment
-    /// * `amount` - Amount to verify (non-negative)
-    /// * `salt` - Salt used when creating the deposit
-    /// * `owner` - Owner of the escrow
-    pub fn verify_proof_view(env: Env, amount: i128, salt: Bytes, owner: Address) -> bool {
-        let commitment_result =
-            commitment::create_amount_commitment(&env, owner.clone(), amount, salt);
-
-        let commitment = match commitment_result {
-            Ok(c) => c,
-            Err(_) => return false,
-        };
-
-        let commitment_bytes: Bytes = commitment.into();
-        let entry: Option<EscrowEntry> = get_escrow(&env, &commitment_bytes);
-
-        match entry {
-            Some(e) => {
-                if e.status != EscrowStatus::Pending {
-                    return false;
-                }
-                if e.expires_at > 0 && env.ledger().timestamp() >= e.expires_at {
-                    return false;
-                }
-                e.amount == amount
-            }
-            None => false,
-        }
+    /// * `viewer` - The viewing account (must authorize)
+    pub fn granted_count(env: Env, viewer: Address) -> u32 {
+        viewer.require_auth();
+        get_viewer_of(&env, &viewer).len()
     }
 
-    /// Get a privacy-aware view of escrow details for a commitment hash (read-only).
-    ///
-    /// Returns a [`PrivacyAwareEscrowView`] if an escrow exists for the commitment,
-    /// or `None` otherwise.
+    /// Deposit funds and create an escrow entry keyed by `SHA256(owner || amount || salt)`.
     ///
-    /// ## Privacy behaviour
-    /// - If the escrow owner **has privacy enabled** and `caller` is **not** the owner,
-    ///   the `amount` and `owner` fields are returned as `None`.
-    /// - If privacy is **disabled**, or `caller` equals the escrow owner,
-    ///   all fields are returned in full.
+    /// Transfers `amount` from `owner` to the contract and stores an escrow entry.
     ///
     /// # Arguments
     /// * `env` - The contract environment
-    /// * `commitment` - 32-byte commitment hash identifying the escrow
-    /// * `caller` - Address of the caller; used to determine whether full details
-    ///   are returned when privacy is enabled
-    pub fn get_escrow_details(
+    /// * `token` - The token contract address
+    /// * `amount` - Amount to deposit; must be positive
+    /// * `owner` - Owner of the funds (must authorize)
+    /// * `salt` - Random salt (0–1024 bytes) for uniqueness
+    /// * `timeout_secs` - Seconds from now until the escrow expires (0 = no expiry)
+    ///
+    /// # Errors
+    /// * `InvalidAmount` - Amount is zero or negative
+    /// * `InvalidSalt` - Salt length exceeds 1024 bytes
+    /// * `ContractPaused` - Contract is currently paused
+    /// * `Unauthorized` - Deposits are gated and `owner` is not the admin
+    /// * `CommitmentAlreadyExists` - An escrow for this commitment already exists
+    /// * `TokenNotAllowed` - The token allow-list is set and `token` is not on it
+    pub fn deposit(
         env: Env,
-        commitment: BytesN<32>,
-        caller: Address,
-    ) -> Option<PrivacyAwareEscrowView> {
-        let commitment_bytes: Bytes = commitment.into();
-        let entry = get_escrow(&env, &commitment_bytes)?;
-
-        let privacy_on = privacy::get_privacy(&env, entry.owner.clone());
-        let is_owner = caller == entry.owner;
-
-        if privacy_on && !is_owner {
-            Some(PrivacyAwareEscrowView {
-                token: entry.token,
-                amount: None,
-                owner: None,
-                status: entry.status,
-                created_at: entry.created_at,
-                expires_at: entry.expires_at,
-            })
-        } else {
-            Some(PrivacyAwareEscrowView {
-                token: entry.token,
-                amount: Some(entry.amount),
-                owner: Some(entry.owner),
-                status: entry.status,
-                created_at: entry.created_at,
-                expires_at: entry.expires_at,
-            })
+        token: Address,
+        amount: i128,
+        owner: Address,
+        salt: Bytes,
+        timeout_secs: u64,
+    ) -> Result<BytesN<32>, QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
         }
+        require_operation_allowed(&env, OP_DEPOSIT, &owner)?;
+        escrow::deposit(
+            &env,
+            token,
+            amount,
+            owner,
+            salt,
+            timeout_secs,
+            EscrowOptions::default(),
+        )
     }
-    /// Upgrade the contract to a new WASM implementation (**Admin only**).
+
+    /// Deposit funds like [`deposit`](QuickexContract::deposit), with per-escrow settings.
     ///
-    /// Caller must equal admin and authorize. The new WASM must be pre-uploaded to the network.
-    /// Emits an upgrade event for audit.
+    /// Setting `options.next_of` chains the escrow after another commitment for
+    /// sequential release: it cannot be withdrawn until that escrow is spent.
     ///
     /// # Arguments
     /// * `env` - The contract environment
-    /// * `caller` - Caller address (must equal admin; must authorize)
-    /// * `new_wasm_hash` - 32-byte hash of the new WASM code
-    ///
-    /// # Errors
-    /// * `Unauthorized` - Caller is not the admin, or admin not set
+    /// * `token` - The token contract address
+    /// * `amount` - Amount to deposit; must be positive
+    /// * `owner` - Owner of the funds (must authorize)
+    /// * `salt` - Random salt (0–1024 bytes) for uniqueness
+    /// * `timeout_secs` - Seconds from now until the escrow expires (0 = no expiry)
+    /// * `options` - Per-escrow settings; see [`EscrowOptions`]
+    ///
+    /// # Errors
+    /// * `InvalidAmount` - Amount is zero or negative
+    /// * `InvalidSalt` - Salt length exceeds 1024 bytes
+    /// * `ContractPaused` - Contract is currently paused
+    /// * `Unauthorized` - Deposits are gated and `owner` is not the admin
+    /// * `CommitmentAlreadyExists` - An escrow for this commitment already exists
+    /// * `InvalidSchedule` - `options.installments` is empty, longer than 50, or not ascending,
+    ///   or `options.cliff_secs` is not below a non-zero `timeout_secs`
+    pub fn deposit_with_options(
+        env: Env,
+        token: Address,
+        amount: i128,
+        owner: Address,
+        salt: Bytes,
+        timeout_secs: u64,
+        options: EscrowOptions,
+    ) -> Result<BytesN<32>, QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
+        }
+        require_operation_allowed(&env, OP_DEPOSIT, &owner)?;
+        escrow::deposit(&env, token, amount, owner, salt, timeout_secs, options)
+    }
+
+    /// Deposit funds on behalf of a separate beneficiary.
+    ///
+    /// Creates an escrow keyed by `SHA256(beneficiary || amount || salt)` and funded by
+    /// `owner`. The beneficiary withdraws it with
+    /// [`withdraw`](QuickexContract::withdraw) once given the salt; no one else can.
+    /// `owner` keeps refund rights and any refund goes only to them.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `token` - The token contract address
+    /// * `amount` - Amount to deposit; must be positive
+    /// * `owner` - Payer of the funds and refund holder (must authorize)
+    /// * `beneficiary` - Address with withdraw rights
+    /// * `salt` - Random salt (0–1024 bytes) for uniqueness
+    /// * `timeout_secs` - Seconds from now until the escrow expires (0 = no expiry)
+    ///
+    /// # Errors
+    /// * `InvalidAmount` - Amount is zero or negative
+    /// * `InvalidSalt` - Salt length exceeds 1024 bytes
+    /// * `ContractPaused` - Contract is currently paused
+    /// * `Unauthorized` - Deposits are gated and `owner` is not the admin
+    /// * `CommitmentAlreadyExists` - An escrow for this commitment already exists
+    pub fn deposit_to(
+        env: Env,
+        token: Address,
+        amount: i128,
+        owner: Address,
+        beneficiary: Address,
+        salt: Bytes,
+        timeout_secs: u64,
+    ) -> Result<BytesN<32>, QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
+        }
+        require_operation_allowed(&env, OP_DEPOSIT, &owner)?;
+        escrow::deposit_to(&env, token, amount, owner, beneficiary, salt, timeout_secs)
+    }
+
+    /// Create several escrows in one call, with a single token transfer.
+    ///
+    /// Each `(amount, salt, timeout_secs)` entry becomes an escrow exactly as if
+    /// passed to [`deposit`](QuickexContract::deposit), and the commitments are
+    /// returned in entry order. The summed amount is pulled from `owner` once. If any
+    /// entry is invalid the whole call reverts, so no escrow is created.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `token` - The token contract address
+    /// * `owner` - Owner of the funds (must authorize)
+    /// * `entries` - `(amount, salt, timeout_secs)` per escrow, at most the max batch
+    ///   size (default 50)
+    ///
+    /// # Errors
+    /// * `ContractPaused` - Contract is currently paused
+    /// * `Unauthorized` - Deposits are gated and `owner` is not the admin
+    /// * `BatchTooLarge` - More entries supplied than the max batch size
+    /// * `InvalidAmount` - An amount is zero or negative
+    /// * `InvalidSalt` - A salt exceeds 1024 bytes
+    /// * `CommitmentAlreadyExists` - An entry's commitment already exists, or two
+    ///   entries share an amount and salt
+    pub fn deposit_batch(
+        env: Env,
+        token: Address,
+        owner: Address,
+        entries: Vec<(i128, Bytes, u64)>,
+    ) -> Result<Vec<BytesN<32>>, QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
+        }
+        require_operation_allowed(&env, OP_DEPOSIT, &owner)?;
+        escrow::deposit_batch(&env, token, owner, entries)
+    }
+
+    /// Deposit funds like [`deposit`](QuickexContract::deposit), but only if the
+    /// resulting commitment equals a previously published one.
+    ///
+    /// The commitment is recomputed from `owner`, `amount` and `salt` and compared with
+    /// `expected_commitment` before any transfer, proving the depositor committed to
+    /// this exact amount beforehand.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `token` - The token contract address
+    /// * `amount` - Amount to deposit; must be positive
+    /// * `owner` - Owner of the funds (must authorize)
+    /// * `salt` - Random salt (0–1024 bytes) for uniqueness
+    /// * `expected_commitment` - Commitment the deposit must produce
+    /// * `timeout_secs` - Seconds from now until the escrow expires (0 = no expiry)
+    ///
+    /// # Errors
+    /// * `InvalidAmount` - Amount is zero or negative
+    /// * `InvalidSalt` - Salt length exceeds 1024 bytes
+    /// * `CommitmentMismatch` - The inputs do not produce `expected_commitment`
+    /// * `ContractPaused` - Contract is currently paused
+    /// * `Unauthorized` - Deposits are gated and `owner` is not the admin
+    /// * `CommitmentAlreadyExists` - An escrow for this commitment already exists
+    pub fn deposit_matching_commitment(
+        env: Env,
+        token: Address,
+        amount: i128,
+        owner: Address,
+        salt: Bytes,
+        expected_commitment: BytesN<32>,
+        timeout_secs: u64,
+    ) -> Result<BytesN<32>, QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
+        }
+        require_operation_allowed(&env, OP_DEPOSIT, &owner)?;
+        escrow::deposit_matching_commitment(
+            &env,
+            token,
+            amount,
+            owner,
+            salt,
+            expected_commitment,
+            timeout_secs,
+        )
+    }
+
+    /// Deposit funds like [`deposit`](QuickexContract::deposit), locked until a cliff.
+    ///
+    /// The escrow cannot be withdrawn until `cliff_secs` seconds after the deposit;
+    /// earlier attempts fail with `EscrowNotYetClaimable`. The cliff is separate from
+    /// expiry: the owner still cannot refund until the escrow expires. Equivalent to
+    /// [`deposit_with_options`](QuickexContract::deposit_with_options) with
+    /// `cliff_secs` set.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `token` - The token contract address
+    /// * `amount` - Amount to deposit; must be positive
+    /// * `owner` - Owner of the funds (must authorize)
+    /// * `salt` - Random salt (0–1024 bytes) for uniqueness
+    /// * `timeout_secs` - Seconds from now until the escrow expires (0 = no expiry)
+    /// * `cliff_secs` - Seconds from now before the escrow can be withdrawn
+    ///
+    /// # Errors
+    /// * `InvalidAmount` - Amount is zero or negative
+    /// * `InvalidSalt` - Salt length exceeds 1024 bytes
+    /// * `InvalidSchedule` - `cliff_secs` is not below a non-zero `timeout_secs`
+    /// * `ContractPaused` - Contract is currently paused
+    /// * `Unauthorized` - Deposits are gated and `owner` is not the admin
+    pub fn deposit_with_cliff(
+        env: Env,
+        token: Address,
+        amount: i128,
+        owner: Address,
+        salt: Bytes,
+        timeout_secs: u64,
+        cliff_secs: u64,
+    ) -> Result<BytesN<32>, QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
+        }
+        require_operation_allowed(&env, OP_DEPOSIT, &owner)?;
+        let options = EscrowOptions {
+            cliff_secs,
+            ..Default::default()
+        };
+        escrow::deposit(&env, token, amount, owner, salt, timeout_secs, options)
+    }
+
+    /// Deposit funds keyed by `SHA256(owner || amount || salt_hash)` without revealing the salt.
+    ///
+    /// The client supplies `salt_hash = SHA256(salt)` instead of the raw salt, so the
+    /// salt does not appear in the deposit transaction. It is revealed once, when the
+    /// escrow is withdrawn through
+    /// [`withdraw_with_salt_hash`](QuickexContract::withdraw_with_salt_hash); the plain
+    /// `withdraw` path rejects these escrows.
+    ///
+    /// **Tradeoff:** the salt hash is public once the deposit lands, and the owner and
+    /// amount are visible in the same transaction, so this narrows *when* the salt is
+    /// exposed rather than adding hiding on top of the standard commitment.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `token` - The token contract address
+    /// * `amount` - Amount to deposit; must be positive
+    /// * `owner` - Owner of the funds (must authorize)
+    /// * `salt_hash` - `SHA256(salt)` computed off-chain
+    /// * `timeout_secs` - Seconds from now until the escrow expires (0 = no expiry)
+    ///
+    /// # Errors
+    /// * `InvalidAmount` - Amount is zero or negative
+    /// * `ContractPaused` - Contract is currently paused
+    /// * `Unauthorized` - Deposits are gated and `owner` is not the admin
+    /// * `CommitmentAlreadyExists` - An escrow for this commitment already exists
+    pub fn deposit_with_salt_hash(
+        env: Env,
+        token: Address,
+        amount: i128,
+        owner: Address,
+        salt_hash: BytesN<32>,
+        timeout_secs: u64,
+    ) -> Result<BytesN<32>, QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
+        }
+        require_operation_allowed(&env, OP_DEPOSIT, &owner)?;
+        escrow::deposit_with_salt_hash(&env, token, amount, owner, salt_hash, timeout_secs)
+    }
+
+    /// Withdraw part of an escrow, leaving the remainder claimable.
+    ///
+    /// The escrow is located exactly as in [`withdraw`](QuickexContract::withdraw),
+    /// from `to`, the originally deposited `amount`, and `salt`. `requested` is paid
+    /// to `to` (less the withdrawal fee) and deducted from the escrow, which stays
+    /// pending until its balance reaches zero and then becomes spent. After a partial
+    /// withdrawal, [`withdraw`](QuickexContract::withdraw) no longer matches the stored
+    /// amount, so the rest must be claimed with this method. Emits `PartialWithdraw`
+    /// with the remaining balance.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `amount` - Amount originally deposited; must be positive
+    /// * `to` - Recipient address (must authorize the call)
+    /// * `salt` - Salt used when creating the original deposit commitment
+    /// * `requested` - Amount to withdraw now; must be positive
+    ///
+    /// # Errors
+    /// * `ContractPaused` - Contract is currently paused
+    /// * `PartialWithdrawExceedsBalance` - `requested` exceeds the remaining balance
+    /// * Otherwise as [`withdraw`](QuickexContract::withdraw), except `TokenMismatch`
+    ///   and the amount check
+    pub fn withdraw_partial(
+        env: Env,
+        amount: i128,
+        to: Address,
+        salt: Bytes,
+        requested: i128,
+    ) -> Result<bool, QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
+        }
+        escrow::withdraw_partial(&env, amount, to, salt, requested)
+    }
+
+    /// Withdraw an escrow and swap the proceeds into another token through a DEX.
+    ///
+    /// Settles the escrow keyed by (`to`, `amount`, `salt`) exactly like
+    /// [`withdraw`](QuickexContract::withdraw), takes the withdrawal fee in the escrowed
+    /// token, then calls `swap` on `dex` to convert the rest into `out_token` for `to`.
+    /// If `to` receives less than `min_out`, or the DEX does not take exactly the net
+    /// amount, the whole call reverts and the escrow stays pending. Both are measured
+    /// from token balances, not from what the DEX reports. Returns the amount of
+    /// `out_token` received.
+    ///
+    /// The DEX must implement [`dex::Dex`]; it is authorized to pull exactly the net
+    /// amount of the escrowed token from this contract.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `amount` - Amount to withdraw; must be positive and match the escrow amount
+    /// * `salt` - Salt used when creating the original deposit commitment
+    /// * `to` - Recipient address (must authorize the call)
+    /// * `dex` - DEX contract to swap through
+    /// * `min_out` - Minimum amount of `out_token` `to` must receive
+    /// * `out_token` - Token to receive
+    ///
+    /// # Errors
+    /// * `ContractPaused` - Contract is currently paused
+    /// * `SwapInputMismatch` - The DEX did not take exactly the net amount
+    /// * `SlippageExceeded` - `to` received less than `min_out`
+    /// * Otherwise as [`withdraw`](QuickexContract::withdraw)
+    pub fn withdraw_and_swap(
+        env: Env,
+        amount: i128,
+        salt: Bytes,
+        to: Address,
+        dex: Address,
+        min_out: i128,
+        out_token: Address,
+    ) -> Result<i128, QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
+        }
+        escrow::withdraw_and_swap(&env, amount, salt, to, dex, min_out, out_token)
+    }
+
+    /// Open a pooled escrow that many contributors can fund.
+    ///
+    /// The pool is keyed by `commitment` and holds a single `token`. Contributions are
+    /// accepted through [`contribute`](QuickexContract::contribute) until `release_at`;
+    /// from then on each contributor withdraws their own share with
+    /// [`withdraw_share`](QuickexContract::withdraw_share). Pools live alongside
+    /// regular escrows and do not conflict with an escrow under the same commitment.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - 32-byte key identifying the pool
+    /// * `creator` - Account opening the pool (must authorize)
+    /// * `token` - Token the pool holds
+    /// * `release_at` - Ledger timestamp at which shares become withdrawable
+    ///
+    /// # Errors
+    /// * `ContractPaused` - Contract is currently paused
+    /// * `CommitmentAlreadyExists` - A pool already exists for the commitment
+    /// * `InvalidSchedule` - `release_at` is not in the future
+    pub fn open_pool(
+        env: Env,
+        commitment: BytesN<32>,
+        creator: Address,
+        token: Address,
+        release_at: u64,
+    ) -> Result<(), QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
+        }
+        pool::open_pool(&env, commitment, creator, token, release_at)
+    }
+
+    /// Add funds to a pooled escrow. Returns the contributor's total share.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - Pool key passed to [`open_pool`](QuickexContract::open_pool)
+    /// * `from` - Contributor (must authorize the token transfer)
+    /// * `token` - Token to contribute; must be the pool's token
+    /// * `amount` - Amount to contribute; must be positive
+    ///
+    /// # Errors
+    /// * `InvalidAmount` - Amount is zero or negative
+    /// * `ContractPaused` - Contract is currently paused
+    /// * `Unauthorized` - Deposits are gated and `from` is not the admin
+    /// * `CommitmentNotFound` - No pool exists for the commitment
+    /// * `TokenMismatch` - `token` is not the pool's token
+    /// * `EscrowExpired` - The pool's release time has passed
+    pub fn contribute(
+        env: Env,
+        commitment: BytesN<32>,
+        from: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<i128, QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
+        }
+        require_operation_allowed(&env, OP_DEPOSIT, &from)?;
+        pool::contribute(&env, commitment, from, token, amount)
+    }
+
+    /// Withdraw a contributor's whole share of a released pool.
+    ///
+    /// Pays the share, less the withdrawal fee, to `contributor` and returns the net
+    /// amount. Returns 0 without paying if the auto-pause circuit breaker trips.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - Pool key
+    /// * `contributor` - Contributor withdrawing (must authorize)
+    ///
+    /// # Errors
+    /// * `ContractPaused` - Contract is currently paused
+    /// * `CommitmentNotFound` - No pool exists for the commitment
+    /// * `EscrowNotYetClaimable` - The pool's release time has not passed
+    /// * `NoPoolShare` - `contributor` holds nothing in the pool
+    pub fn withdraw_share(
+        env: Env,
+        commitment: BytesN<32>,
+        contributor: Address,
+    ) -> Result<i128, QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
+        }
+        pool::withdraw_share(&env, commitment, contributor)
+    }
+
+    /// Get a pooled escrow (read-only). Returns `None` if no pool exists.
+    pub fn get_pool(env: Env, commitment: BytesN<32>) -> Option<Pool> {
+        get_pool(&env, &commitment.into())
+    }
+
+    /// Get a contributor's outstanding share of a pool (read-only). Returns 0 if none.
+    pub fn pool_share(env: Env, commitment: BytesN<32>, contributor: Address) -> i128 {
+        get_pool_share(&env, &commitment.into(), &contributor)
+    }
+
+    /// Withdraw like [`withdraw`](QuickexContract::withdraw), submitted by a relayer on
+    /// the recipient's behalf.
+    ///
+    /// `to` signs this invocation, including `nonce`, off-chain; anyone can then submit
+    /// it and pay the fees. `nonce` must equal [`withdraw_nonce`](QuickexContract::withdraw_nonce)
+    /// for `to` and is advanced on success, so a signed call cannot be replayed.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `token` - Token the recipient expects to receive; must match the escrow's token
+    /// * `amount` - Amount to withdraw; must be positive and match the escrow amount
+    /// * `to` - Recipient address (must have signed the call)
+    /// * `salt` - Salt used when creating the original deposit commitment
+    /// * `nonce` - The recipient's current withdrawal nonce
+    ///
+    /// # Errors
+    /// * `StaleNonce` - `nonce` is not the recipient's current withdrawal nonce
+    /// * `ContractPaused` - Contract is currently paused
+    /// * Otherwise as [`withdraw`](QuickexContract::withdraw)
+    pub fn withdraw_signed(
+        env: Env,
+        token: Address,
+        amount: i128,
+        to: Address,
+        salt: Bytes,
+        nonce: u64,
+    ) -> Result<bool, QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
+        }
+        escrow::withdraw_signed(&env, &token, amount, to, salt, nonce)
+    }
+
+    /// Get the nonce `who`'s next [`withdraw_signed`](QuickexContract::withdraw_signed)
+    /// must carry.
+    pub fn withdraw_nonce(env: Env, who: Address) -> u64 {
+        get_withdraw_nonce(&env, &who)
+    }
+
+    /// Withdraw an escrow created by
+    /// [`deposit_with_salt_hash`](QuickexContract::deposit_with_salt_hash).
+    ///
+    /// The caller (`to`) must authorize. The contract hashes `salt`, recomputes the
+    /// commitment from `to`, `amount`, and `SHA256(salt)`, and requires the escrow's
+    /// stored salt hash to match.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `amount` - Amount to withdraw; must match the escrow amount
+    /// * `to` - Recipient address (must authorize the call)
+    /// * `salt` - The salt whose hash was supplied at deposit
+    ///
+    /// # Errors
+    /// * `ContractPaused` - Contract is currently paused
+    /// * `CommitmentMismatch` - Stored salt hash does not equal `SHA256(salt)`
+    /// * Otherwise as [`withdraw`](QuickexContract::withdraw)
+    pub fn withdraw_with_salt_hash(
+        env: Env,
+        amount: i128,
+        to: Address,
+        salt: Bytes,
+    ) -> Result<bool, QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
+        }
+        escrow::withdraw_with_salt_hash(&env, amount, to, salt)
+    }
+
+    /// Deposit funds under a commitment that also binds the owner's next deposit nonce.
+    ///
+    /// Computes `SHA256(owner || amount || salt || nonce)` with the owner's current
+    /// nonce (see [`owner_nonce`](QuickexContract::owner_nonce)), then advances it.
+    /// Repeating the same `amount` and `salt` therefore never collides with an
+    /// earlier deposit. Withdraw with [`withdraw_nonced`](QuickexContract::withdraw_nonced).
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `token` - The token contract address
+    /// * `amount` - Amount to deposit; must be positive
+    /// * `owner` - Owner of the funds (must authorize)
+    /// * `salt` - Random bytes (0–1024 bytes)
+    /// * `timeout_secs` - Seconds from now until the escrow expires (0 = no expiry)
+    ///
+    /// # Errors
+    /// * `InvalidAmount` - Amount is zero or negative
+    /// * `InvalidSalt` - Salt exceeds 1024 bytes
+    /// * `ContractPaused` - Contract is currently paused
+    /// * `Unauthorized` - Deposits are gated and `owner` is not the admin
+    /// * `CommitmentAlreadyExists` - An escrow for this commitment already exists
+    pub fn deposit_nonced(
+        env: Env,
+        token: Address,
+        amount: i128,
+        owner: Address,
+        salt: Bytes,
+        timeout_secs: u64,
+    ) -> Result<BytesN<32>, QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
+        }
+        require_operation_allowed(&env, OP_DEPOSIT, &owner)?;
+        escrow::deposit_nonced(&env, token, amount, owner, salt, timeout_secs)
+    }
+
+    /// Withdraw an escrow created by [`deposit_nonced`](QuickexContract::deposit_nonced).
+    ///
+    /// The caller (`to`) must authorize. The nonce is read from the escrow at
+    /// `commitment` and the commitment is recomputed from `to`, `amount`, `salt`, and
+    /// that nonce.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - Commitment returned by the nonced deposit
+    /// * `amount` - Amount to withdraw; must match the escrow amount
+    /// * `to` - Recipient address (must authorize the call)
+    /// * `salt` - Salt used at deposit
+    ///
+    /// # Errors
+    /// * `ContractPaused` - Contract is currently paused
+    /// * `CommitmentMismatch` - Escrow is not nonced, or the inputs do not open `commitment`
+    /// * Otherwise as [`withdraw`](QuickexContract::withdraw)
+    pub fn withdraw_nonced(
+        env: Env,
+        commitment: BytesN<32>,
+        amount: i128,
+        to: Address,
+        salt: Bytes,
+    ) -> Result<bool, QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
+        }
+        escrow::withdraw_nonced(&env, commitment, amount, to, salt)
+    }
+
+    /// Get the nonce `owner`'s next [`deposit_nonced`](QuickexContract::deposit_nonced) will use.
+    ///
+    /// Lets clients precompute the commitment off-chain. Starts at 0.
+    pub fn owner_nonce(env: Env, owner: Address) -> u64 {
+        get_owner_nonce(&env, &owner)
+    }
+
+    /// Opt in to or out of salt fingerprint tracking for `owner`'s deposits.
+    ///
+    /// While enabled, every [`deposit`](QuickexContract::deposit) or
+    /// [`deposit_with_options`](QuickexContract::deposit_with_options) by `owner` stores
+    /// `SHA256(salt)`, one storage entry per distinct salt, so
+    /// [`salt_used`](QuickexContract::salt_used) can warn before a salt is reused.
+    /// Disabled by default; fingerprints already stored are kept when disabling.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - Account to configure (must authorize)
+    /// * `enabled` - `true` to start tracking
+    pub fn set_salt_tracking(env: Env, owner: Address, enabled: bool) {
+        owner.require_auth();
+        set_salt_tracking(&env, &owner, enabled);
+    }
+
+    /// Check whether `owner` has already deposited with `salt`.
+    ///
+    /// Only deposits made while [`set_salt_tracking`](QuickexContract::set_salt_tracking)
+    /// was enabled are known; others report `false`. Only the fingerprint is stored,
+    /// so the salt itself never appears in contract storage.
+    pub fn salt_used(env: Env, owner: Address, salt: Bytes) -> bool {
+        escrow::salt_used(&env, &owner, &salt)
+    }
+
+    /// Release every installment of an installment escrow whose unlock time has passed.
+    ///
+    /// The escrow must have been created with an `installments` schedule via
+    /// [`deposit_with_options`](QuickexContract::deposit_with_options). Funds go to the
+    /// escrow's `allowed_recipient`, or to the owner if none was set. Returns the
+    /// amount released.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - 32-byte commitment hash identifying the escrow
+    /// * `to` - Beneficiary (must authorize)
+    ///
+    /// # Errors
+    /// * `ContractPaused` - Contract is currently paused
+    /// * `CommitmentNotFound` - No escrow exists for the commitment
+    /// * `CommitmentMismatch` - Escrow has no installment schedule
+    /// * `AlreadySpent` - Escrow is fully released or refunded
+    /// * `EscrowExpired` - Escrow has passed its expiry timestamp
+    /// * `InsufficientConfirmations` - The escrow's `min_confirmations` ledgers have not yet closed
+    /// * `EscrowNotYetClaimable` - The escrow's cliff has not passed
+    /// * `PrerequisiteNotMet` - The escrow is chained after one that is not yet spent
+    /// * `RecipientNotAllowed` - `to` is not the beneficiary
+    /// * `NoMaturedInstallments` - No unclaimed installment has unlocked yet
+    pub fn withdraw_matured(
+        env: Env,
+        commitment: BytesN<32>,
+        to: Address,
+    ) -> Result<i128, QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
+        }
+        escrow::withdraw_matured(&env, commitment, to)
+    }
+
+    /// Create a deterministic commitment hash for an amount (off-chain / pre-deposit use).
+    ///
+    /// Computes `SHA256(owner || amount || salt)`. Not a zero-knowledge proof; same inputs
+    /// always yield the same hash. Use for API shape validation and audit trails.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The owner address
+    /// * `amount` - Non-negative amount in token base units
+    /// * `salt` - Random bytes (0–1024 bytes) for uniqueness
+    ///
+    /// # Errors
+    /// * `InvalidAmount` - Amount is negative
+    /// * `InvalidSalt` - Salt length exceeds 1024 bytes
+    pub fn create_amount_commitment(
+        env: Env,
+        owner: Address,
+        amount: i128,
+        salt: Bytes,
+    ) -> Result<BytesN<32>, QuickexError> {
+        commitment::create_amount_commitment(&env, owner, amount, salt)
+    }
+
+    /// Recover the escrow key of one's own deposit from the remembered inputs (read-only).
+    ///
+    /// The salt is never stored on-chain, so an owner who has lost track of a
+    /// commitment can recompute it from `owner`, `amount`, and `salt` and then look the
+    /// escrow up with [`get_escrow_details`](QuickexContract::get_escrow_details). Only
+    /// escrows keyed by `deposit`/`deposit_with_options` can be recovered this way.
+    ///
+    /// Same result as [`create_amount_commitment`](QuickexContract::create_amount_commitment).
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The owner (or withdrawal recipient) the commitment was built for
+    /// * `amount` - Deposited amount in token base units
+    /// * `salt` - Salt used at deposit time
+    ///
+    /// # Errors
+    /// * `InvalidAmount` - Amount is negative
+    /// * `InvalidSalt` - Salt length exceeds 1024 bytes
+    pub fn recompute_commitment(
+        env: Env,
+        owner: Address,
+        amount: i128,
+        salt: Bytes,
+    ) -> Result<BytesN<32>, QuickexError> {
+        commitment::create_amount_commitment(&env, owner, amount, salt)
+    }
+
+    /// Package a verified opening of an escrow commitment for an external verifier.
+    ///
+    /// Recomputes `SHA256(owner || amount || salt)` using the stored escrow's amount
+    /// and returns `{owner, amount, salt, commitment}` if it equals `commitment`.
+    /// Returns `None` if no escrow exists or the inputs do not open it. Only escrows
+    /// keyed by a plain amount commitment can be opened this way.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - 32-byte commitment hash identifying the escrow
+    /// * `owner` - Address bound into the commitment (must authorize)
+    /// * `salt` - Salt used when creating the commitment
+    pub fn get_commitment_opening(
+        env: Env,
+        commitment: BytesN<32>,
+        owner: Address,
+        salt: Bytes,
+    ) -> Option<CommitmentOpening> {
+        owner.require_auth();
+        escrow::commitment_opening(&env, commitment, owner, salt)
+    }
+
+    /// Verify that a commitment hash matches the given `owner`, `amount`, and `salt`.
+    ///
+    /// Recomputes the commitment and compares. Returns `false` if inputs are invalid or don't match.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - 32-byte commitment hash to verify
+    /// * `owner` - Claimed owner
+    /// * `amount` - Claimed amount (must be non-negative)
+    /// * `salt` - Salt used when creating the commitment
+    pub fn verify_amount_commitment(
+        env: Env,
+        commitment: BytesN<32>,
+        owner: Address,
+        amount: i128,
+        salt: Bytes,
+    ) -> bool {
+        commitment::verify_amount_commitment(&env, commitment, owner, amount, salt)
+    }
+
+    /// Verify a commitment like [`verify_amount_commitment`](QuickexContract::verify_amount_commitment),
+    /// but distinguish invalid inputs from a mismatch.
+    ///
+    /// Returns `Ok(false)` only when the inputs are valid and do not match. (Named
+    /// without `amount_` to fit Soroban's 32-character function name limit.)
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - 32-byte commitment hash to verify
+    /// * `owner` - Claimed owner
+    /// * `amount` - Claimed amount (must be non-negative)
+    /// * `salt` - Salt used when creating the commitment
+    ///
+    /// # Errors
+    /// * `InvalidAmount` - Amount is negative
+    /// * `InvalidSalt` - Salt length exceeds 1024 bytes
+    pub fn verify_commitment_detailed(
+        env: Env,
+        commitment: BytesN<32>,
+        owner: Address,
+        amount: i128,
+        salt: Bytes,
+    ) -> Result<bool, QuickexError> {
+        commitment::verify_amount_commitment_detailed(&env, commitment, owner, amount, salt)
+    }
+
+    /// Create a commitment to an amount range rather than an exact amount (read-only).
+    ///
+    /// Commits `owner` to the inclusive range `[lower, upper]`. This is a simple
+    /// hash-based bucket commitment, **not** a zero-knowledge range proof: opening it
+    /// with [`verify_range_commitment`](QuickexContract::verify_range_commitment)
+    /// reveals the bounds and the amount.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - The owner address
+    /// * `lower` - Inclusive lower bound; must be non-negative
+    /// * `upper` - Inclusive upper bound; must be at least `lower`
+    /// * `salt` - Random bytes (0–1024 bytes) for hiding
+    ///
+    /// # Errors
+    /// * `InvalidAmount` - `lower` is negative or `upper` is below `lower`
+    /// * `InvalidSalt` - Salt length exceeds 1024 bytes
+    pub fn create_range_commitment(
+        env: Env,
+        owner: Address,
+        lower: i128,
+        upper: i128,
+        salt: Bytes,
+    ) -> Result<BytesN<32>, QuickexError> {
+        commitment::create_range_commitment(&env, owner, lower, upper, salt)
+    }
+
+    /// Verify that a range commitment opens to `[lower, upper]` and that `amount` lies
+    /// within it.
+    ///
+    /// Returns `false` if the inputs are invalid, the opening does not match, or
+    /// `amount` is outside the range.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - Range commitment to verify
+    /// * `owner` - Claimed owner
+    /// * `lower` - Claimed inclusive lower bound
+    /// * `upper` - Claimed inclusive upper bound
+    /// * `salt` - Salt used when creating the commitment
+    /// * `amount` - Revealed amount to check against the range
+    pub fn verify_range_commitment(
+        env: Env,
+        commitment: BytesN<32>,
+        owner: Address,
+        lower: i128,
+        upper: i128,
+        salt: Bytes,
+        amount: i128,
+    ) -> bool {
+        commitment::verify_range_commitment(&env, commitment, owner, lower, upper, salt, amount)
+    }
+
+    /// Deposit funds committed to an amount range rather than an exact amount.
+    ///
+    /// The escrow is keyed by
+    /// [`create_range_commitment`](QuickexContract::create_range_commitment) for
+    /// `owner`, `lower`, `upper` and `salt`, and is withdrawn with
+    /// [`withdraw_in_range`](QuickexContract::withdraw_in_range), so the recipient only
+    /// needs to remember the bounds. `amount` must lie within `[lower, upper]`.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `token` - The token contract address
+    /// * `amount` - Amount to deposit; must be positive and within the range
+    /// * `owner` - Owner of the funds (must authorize)
+    /// * `lower` - Inclusive lower bound of the committed range
+    /// * `upper` - Inclusive upper bound of the committed range
+    /// * `salt` - Random salt (0–1024 bytes) for uniqueness
+    /// * `timeout_secs` - Seconds from now until the escrow expires (0 = no expiry)
+    ///
+    /// # Errors
+    /// * `InvalidAmount` - Amount is not positive, the range is invalid, or the amount
+    ///   is outside it
+    /// * `InvalidSalt` - Salt length exceeds 1024 bytes
+    /// * `ContractPaused` - Contract is currently paused
+    /// * `Unauthorized` - Deposits are gated and `owner` is not the admin
+    /// * `CommitmentAlreadyExists` - An escrow for this commitment already exists
+    #[allow(clippy::too_many_arguments)]
+    pub fn deposit_in_range(
+        env: Env,
+        token: Address,
+        amount: i128,
+        owner: Address,
+        lower: i128,
+        upper: i128,
+        salt: Bytes,
+        timeout_secs: u64,
+    ) -> Result<BytesN<32>, QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
+        }
+        require_operation_allowed(&env, OP_DEPOSIT, &owner)?;
+        escrow::deposit_in_range(&env, token, amount, owner, lower, upper, salt, timeout_secs)
+    }
+
+    /// Withdraw a range-commitment escrow without knowing its exact amount.
+    ///
+    /// Recomputes the range commitment from `to`, `lower`, `upper` and `salt` and
+    /// pays `to` the escrow's stored amount (less the withdrawal fee). Bounds that
+    /// differ from the committed range produce a different commitment and fail with
+    /// `CommitmentNotFound`.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `lower` - Inclusive lower bound of the committed range
+    /// * `upper` - Inclusive upper bound of the committed range
+    /// * `salt` - Salt used when creating the commitment
+    /// * `to` - Recipient address (must authorize)
+    ///
+    /// # Errors
+    /// * `ContractPaused` - Contract is currently paused
+    /// * `InvalidAmount` - `lower` is negative or `upper` is below `lower`
+    /// * `CommitmentNotFound` - No escrow matches the bounds and salt
+    /// * `InvalidCommitment` - The stored amount is outside the committed range
+    /// * Otherwise as [`withdraw`](QuickexContract::withdraw), except `TokenMismatch`
+    pub fn withdraw_in_range(
+        env: Env,
+        lower: i128,
+        upper: i128,
+        salt: Bytes,
+        to: Address,
+    ) -> Result<bool, QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
+        }
+        escrow::withdraw_in_range(&env, lower, upper, salt, to)
+    }
+
+    /// Create an escrow record and increment the global escrow counter.
+    ///
+    /// Returns the new counter value. Parameters `_from`, `_to`, `_amount` are reserved for
+    /// future use; the implementation only increments the counter. The returned ID is not
+    /// backed by an escrow, so [`get_escrow_by_id`](QuickexContract::get_escrow_by_id)
+    /// resolves only IDs assigned by deposits.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `_from` - Reserved (depositor address for future use)
+    /// * `_to` - Reserved (recipient address for future use)
+    /// * `_amount` - Reserved (amount for future use)
+    pub fn create_escrow(env: Env, _from: Address, _to: Address, _amount: u64) -> u64 {
+        increment_placeholder_id_count(&env);
+        increment_escrow_counter(&env)
+    }
+
+    /// List every contract error code with its symbolic name, in code order.
+    ///
+    /// Lets clients render readable errors without hard-coding the mapping.
+    pub fn error_catalog(env: Env) -> Vec<(u32, Symbol)> {
+        errors::error_catalog(&env)
+    }
+
+    /// Check that `leaf` is included in the Merkle tree with root `root` (read-only).
+    ///
+    /// Inner nodes are `SHA256(min(a, b) || max(a, b))`; `proof` lists sibling hashes
+    /// from the leaf upward. Lets auditors confirm a commitment belongs to an anchored
+    /// batch without touching any escrow.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `root` - Expected Merkle root
+    /// * `leaf` - Leaf to check, typically an escrow commitment
+    /// * `proof` - Sibling hashes from the leaf to the root
+    pub fn verify_merkle_inclusion(
+        env: Env,
+        root: BytesN<32>,
+        leaf: BytesN<32>,
+        proof: Vec<BytesN<32>>,
+    ) -> bool {
+        merkle::verify_inclusion(&env, &root, &leaf, &proof)
+    }
+
+    /// Health check for deployment and monitoring.
+    ///
+    /// Returns `true` if the contract is deployed and callable. No state or auth required.
+    pub fn health_check() -> bool {
+        true
+    }
+
+    /// Create an escrow on behalf of a user from another contract (cross-contract call).
+    ///
+    /// Funds move from `payer` into escrow under the commitment
+    /// `SHA256(beneficiary || amount || salt)`, which is returned. The beneficiary
+    /// withdraws with [`withdraw`](QuickexContract::withdraw) using the same `salt`;
+    /// `payer` owns the escrow and may refund it after expiry.
+    ///
+    /// ## Auth requirements for the calling contract
+    /// `payer.require_auth()` is checked here, and the token transfer checks it again.
+    /// - If the calling contract pays from its own balance, pass its own address as
+    ///   `payer`. The contract is implicitly authorized for this direct call, but the
+    ///   token `transfer(payer, quickex, amount)` is invoked by QuickEx, so the caller
+    ///   must pre-authorize it with `env.authorize_as_current_contract` first.
+    /// - If a user pays, the user must sign an authorization tree rooted at the
+    ///   calling contract's entry point, with this `create_escrow_cpi` call as a
+    ///   sub-invocation and the token `transfer` (from `payer` to this contract)
+    ///   beneath it. Wallet simulation produces this tree automatically.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `payer` - Account or contract funding the escrow (must authorize)
+    /// * `token` - Token contract address
+    /// * `amount` - Amount to escrow; must be positive
+    /// * `beneficiary` - Address able to withdraw with `salt`
+    /// * `salt` - Random bytes (0–1024 bytes) shared with the beneficiary
+    /// * `timeout_secs` - Seconds from now until the escrow expires (0 = no expiry)
+    ///
+    /// # Errors
+    /// * `InvalidAmount` - Amount is zero or negative
+    /// * `InvalidSalt` - Salt exceeds 1024 bytes
+    /// * `ContractPaused` - Contract is currently paused
+    /// * `Unauthorized` - Deposits are gated and `payer` is not the admin
+    /// * `CommitmentAlreadyExists` - An escrow for this commitment already exists
+    pub fn create_escrow_cpi(
+        env: Env,
+        payer: Address,
+        token: Address,
+        amount: i128,
+        beneficiary: Address,
+        salt: Bytes,
+        timeout_secs: u64,
+    ) -> Result<BytesN<32>, QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
+        }
+        require_operation_allowed(&env, OP_DEPOSIT, &payer)?;
+        escrow::create_escrow_cpi(&env, payer, token, amount, beneficiary, salt, timeout_secs)
+    }
+
+    /// Deposit funds using a pre-generated 32-byte commitment hash.
+    ///
+    /// Transfers `amount` from `from` to the contract and stores an escrow keyed by
+    /// `commitment`. The depositor must authorize. Use when the commitment was created
+    /// off-chain or via [`create_amount_commitment`](QuickexContract::create_amount_commitment).
+    ///
+    /// Whoever knows the preimage of `commitment` can withdraw; `from` is recorded as
+    /// the owner and is the one refunded after expiry. If the commitment was built
+    /// from someone else's address, `from` cannot withdraw at all — see
+    /// [`gift_deposit`](QuickexContract::gift_deposit).
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `from` - Depositor (must authorize the token transfer)
+    /// * `token` - Token contract address
+    /// * `amount` - Amount to deposit; must be positive
+    /// * `commitment` - 32-byte commitment hash (must be unique)
+    /// * `timeout_secs` - Seconds from now until the escrow expires (0 = no expiry)
+    ///
+    /// # Errors
+    /// * `InvalidAmount` - Amount is zero or negative
+    /// * `ContractPaused` - Contract is currently paused
+    /// * `Unauthorized` - Deposits are gated and `from` is not the admin
+    /// * `CommitmentAlreadyExists` - An escrow for this commitment already exists and is not
+    ///   reusable under [`set_terminal_redeposit`](QuickexContract::set_terminal_redeposit)
+    /// * `TokenNotAllowed` - The token allow-list is set and `token` is not on it
+    pub fn deposit_with_commitment(
+        env: Env,
+        from: Address,
+        token: Address,
+        amount: i128,
+        commitment: BytesN<32>,
+        timeout_secs: u64,
+    ) -> Result<(), QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
+        }
+        require_operation_allowed(&env, OP_DEPOSIT, &from)?;
+        escrow::deposit_with_commitment(&env, from, token, amount, commitment, timeout_secs)
+    }
+
+    /// Fund a gift or airdrop for a recipient who holds the secret salt.
+    ///
+    /// `commitment` is the recipient's `SHA256(recipient || amount || salt)`; only the
+    /// recipient can withdraw it, using `withdraw` with their salt. The giver waives
+    /// withdrawal rights but stays the escrow owner: the gift expires after 30 days,
+    /// after which the giver can `refund` it if it was never claimed.
+    ///
+    /// While the strict owner check is enabled, gifts cannot be withdrawn because the
+    /// recipient is not the owner.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `from` - Giver (must authorize the token transfer)
+    /// * `token` - Token contract address
+    /// * `amount` - Amount to gift; must be positive
+    /// * `commitment` - Recipient's 32-byte commitment hash (must be unique)
+    ///
+    /// # Errors
+    /// * `InvalidAmount` - Amount is zero or negative
+    /// * `ContractPaused` - Contract is currently paused
+    /// * `Unauthorized` - Deposits are gated and `from` is not the admin
+    /// * `CommitmentAlreadyExists` - An escrow for this commitment already exists
+    pub fn gift_deposit(
+        env: Env,
+        from: Address,
+        token: Address,
+        amount: i128,
+        commitment: BytesN<32>,
+    ) -> Result<(), QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
+        }
+        require_operation_allowed(&env, OP_DEPOSIT, &from)?;
+        escrow::gift_deposit(&env, from, token, amount, commitment)
+    }
+
+    /// Refund an expired escrow back to its original owner.
+    ///
+    /// Can only be called after `expires_at` is reached. The caller must be the
+    /// original depositor. The escrow must be `Pending` or `Expired`.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - 32-byte commitment hash identifying the escrow
+    /// * `caller` - Must equal the original depositor (must authorize)
+    ///
+    /// # Errors
+    /// * `CommitmentNotFound` - No escrow exists for the commitment
+    /// * `AlreadySpent` - Escrow is already in a terminal state
+    /// * `EscrowNotExpired` - Escrow has no expiry or has not yet expired
+    /// * `InvalidOwner` - Caller is not the original owner
+    /// * `RefundDisabled` - Escrow forwards to an on-expiry recipient instead
+    /// * `RecipientNotAllowed` - The refund allow-list is set and the owner is not on it
+    pub fn refund(env: Env, commitment: BytesN<32>, caller: Address) -> Result<(), QuickexError> {
+        escrow::refund(&env, commitment, caller)
+    }
+
+    /// Refund an expired escrow, redirecting to `fallback` if the owner cannot receive.
+    ///
+    /// Behaves like [`refund`](QuickexContract::refund), but if the token transfer to
+    /// the owner fails (for example because their trustline was removed), the funds are
+    /// sent to `fallback` instead of leaving the escrow stuck. The funds also go to
+    /// `fallback` if the refund allow-list is set and the owner is not on it.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - 32-byte commitment hash identifying the escrow
+    /// * `caller` - Must equal the original depositor (must authorize)
+    /// * `fallback` - Alternate recipient used only if the owner cannot receive or is
+    ///   not allow-listed
+    ///
+    /// # Errors
+    /// * `CommitmentNotFound` - No escrow exists for the commitment
+    /// * `AlreadySpent` - Escrow is already in a terminal state
+    /// * `EscrowNotExpired` - Escrow has no expiry or has not yet expired
+    /// * `InvalidOwner` - Caller is not the original owner
+    /// * `RefundDisabled` - Escrow forwards to an on-expiry recipient instead
+    /// * `RecipientNotAllowed` - The refund allow-list is set and the funds would go to
+    ///   `fallback`, which is not on it
+    pub fn refund_with_fallback(
+        env: Env,
+        commitment: BytesN<32>,
+        caller: Address,
+        fallback: Address,
+    ) -> Result<(), QuickexError> {
+        escrow::refund_with_fallback(&env, commitment, caller, fallback)
+    }
+
+    /// Refund an expired escrow and re-escrow the funds for a new beneficiary.
+    ///
+    /// Equivalent to [`refund`](QuickexContract::refund) followed by a deposit of the
+    /// same token and amount for `SHA256(new_beneficiary || amount || new_salt)`, in
+    /// one transaction and without moving the funds out of the contract. The old
+    /// escrow becomes `Refunded`; the new one is `Pending` and owned by `owner`.
+    /// Returns the new commitment, or `None` if the auto-pause circuit breaker
+    /// tripped and nothing changed.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - 32-byte commitment hash of the expired escrow
+    /// * `owner` - Must equal the original depositor (must authorize)
+    /// * `new_beneficiary` - Address that can withdraw the new escrow
+    /// * `new_salt` - Salt for the new commitment (0–1024 bytes)
+    /// * `new_timeout` - Seconds from now until the new escrow expires (0 = no expiry)
+    ///
+    /// # Errors
+    /// * `ContractPaused` - Contract is currently paused
+    /// * `Unauthorized` - Deposits are gated and `owner` is not the admin
+    /// * `CommitmentNotFound` - No escrow exists for the commitment
+    /// * `AlreadySpent` - Escrow is already in a terminal state
+    /// * `EscrowNotExpired` - Escrow has no expiry or has not yet expired
+    /// * `InvalidOwner` - `owner` is not the original owner
+    /// * `RefundDisabled` - Escrow forwards to an on-expiry recipient instead
+    /// * `InvalidSalt` - `new_salt` exceeds 1024 bytes
+    /// * `CommitmentAlreadyExists` - An escrow for the new commitment already exists
+    pub fn refund_and_reassign(
+        env: Env,
+        commitment: BytesN<32>,
+        owner: Address,
+        new_beneficiary: Address,
+        new_salt: Bytes,
+        new_timeout: u64,
+    ) -> Result<Option<BytesN<32>>, QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
+        }
+        require_operation_allowed(&env, OP_DEPOSIT, &owner)?;
+        escrow::refund_and_reassign(
+            &env,
+            commitment,
+            owner,
+            new_beneficiary,
+            new_salt,
+            new_timeout,
+        )
+    }
+
+    /// Forward an expired escrow to the `on_expiry_recipient` set at deposit.
+    ///
+    /// Permissionless: once the escrow has expired anyone may trigger the forward.
+    /// The escrow is marked `Refunded` and an `ExpiryForwarded` event is emitted.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - 32-byte commitment hash identifying the escrow
+    ///
+    /// # Errors
+    /// * `ContractPaused` - Contract is currently paused
+    /// * `CommitmentNotFound` - No escrow exists for the commitment
+    /// * `AlreadySpent` - Escrow is already in a terminal state
+    /// * `EscrowNotExpired` - Escrow has no expiry or has not yet expired
+    /// * `NoExpiryRecipient` - Escrow has no forward recipient; use `refund`
+    pub fn sweep_expired_forward(env: Env, commitment: BytesN<32>) -> Result<(), QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
+        }
+        escrow::sweep_expired_forward(&env, commitment)
+    }
+
+    /// Mark a pending escrow that has passed its expiry as `Expired`.
+    ///
+    /// Permissionless. Gives indexers an on-chain signal of expiry via an
+    /// `EscrowExpired` event carrying the owner and expiry time, instead of polling
+    /// timestamps. The escrow can still be refunded (or forwarded to its on-expiry
+    /// recipient) afterwards. Calling this on an escrow already marked `Expired` does
+    /// nothing.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - 32-byte commitment hash identifying the escrow
+    ///
+    /// # Errors
+    /// * `ContractPaused` - Contract is currently paused
+    /// * `CommitmentNotFound` - No escrow exists for the commitment
+    /// * `AlreadySpent` - Escrow is already in a terminal state
+    /// * `EscrowNotExpired` - Escrow has no expiry or has not yet expired
+    pub fn mark_expired(env: Env, commitment: BytesN<32>) -> Result<(), QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
+        }
+        escrow::mark_expired(&env, commitment)
+    }
+
+    /// Preview the net amount the owner would receive from a refund right now (read-only).
+    ///
+    /// Applies the same eligibility rules as [`refund`](QuickexContract::refund). No
+    /// refund penalty or fee exists at present, so the net amount is the escrowed amount.
+    ///
+    /// Returns `None` if the escrow does not exist or is not currently refundable, if
+    /// the owner's privacy hides the amount from `caller` (level 1 or above), or if
+    /// the owner is in stealth mode and `caller` is neither the owner nor a granted
+    /// viewer.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - 32-byte commitment hash identifying the escrow
+    /// * `caller` - Address of the caller; used for privacy enforcement
+    pub fn preview_refund(env: Env, commitment: BytesN<32>, caller: Address) -> Option<i128> {
+        escrow::preview_refund(&env, commitment, &caller)
+    }
+
+    /// Attest whether an escrow exists for `commitment` at the current ledger.
+    ///
+    /// Publishes a `NonExistenceChecked` event so a counterparty has a timestamped
+    /// on-chain record, e.g. that a claim was never funded. The returned struct is not
+    /// signed; the event is the attestation. Escrows in any status count as existing.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - 32-byte commitment hash to check
+    pub fn attest_no_escrow(env: Env, commitment: BytesN<32>) -> NonExistenceAttestation {
+        let attestation = NonExistenceAttestation {
+            commitment: commitment.clone(),
+            ledger: env.ledger().sequence(),
+            exists: has_escrow(&env, &commitment.clone().into()),
+        };
+        events::publish_non_existence_checked(
+            &env,
+            commitment,
+            attestation.ledger,
+            attestation.exists,
+        );
+        attestation
+    }
+
+    /// Largest amount a withdrawal of this escrow could pay out right now (read-only).
+    ///
+    /// Equals the escrowed amount unless the contract holds less of the token than
+    /// that, in which case the contract balance is returned. Lets clients detect
+    /// under-collateralization before attempting a withdrawal.
+    ///
+    /// Returns 0 if the escrow does not exist, is terminal, or has expired, and also
+    /// if the owner's privacy hides the amount from `caller` (level 1 or above) or the
+    /// owner is in stealth mode and `caller` is neither the owner nor a granted viewer.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - 32-byte commitment hash identifying the escrow
+    /// * `caller` - Address of the caller; used for privacy enforcement
+    pub fn max_withdrawable(env: Env, commitment: BytesN<32>, caller: Address) -> i128 {
+        escrow::max_withdrawable(&env, commitment, &caller)
+    }
+
+    /// Initialize the contract with an admin address (one-time only).
+    ///
+    /// Sets the admin who can pause/unpause, transfer admin, and upgrade the contract,
+    /// and records the current ledger timestamp as the initialization time.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `admin` - The admin address to set
+    ///
+    /// # Errors
+    /// * `AlreadyInitialized` - Contract has already been initialized
+    pub fn initialize(env: Env, admin: Address) -> Result<(), QuickexError> {
+        if get_admin(&env).is_some() {
+            return Err(QuickexError::AlreadyInitialized);
+        }
+        set_admin(&env, &admin);
+        set_initialized_at(&env, env.ledger().timestamp());
+        Ok(())
+    }
+
+    /// Pause or unpause the contract (**Admin only**).
+    ///
+    /// When paused, certain operations may be blocked. Caller must equal the stored admin.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - Caller address (must equal admin; must authorize)
+    /// * `new_state` - `true` to pause, `false` to unpause
+    ///
+    /// # Errors
+    /// * `Unauthorized` - Caller is not the admin, or admin not set
+    pub fn set_paused(env: Env, caller: Address, new_state: bool) -> Result<(), QuickexError> {
+        require_admin(&env, &caller)?;
+        set_paused(&env, new_state);
+        Ok(())
+    }
+
+    /// Nominate the next admin (**Admin only**).
+    ///
+    /// Admin rights are transferred in two steps so a mistyped address cannot lock
+    /// the admin out. First step: `nominee` becomes admin only once it calls
+    /// [`accept_admin`](QuickexContract::accept_admin), so a wrong address can simply
+    /// be replaced by nominating again. A new nomination overwrites the previous one.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - Caller address (must equal admin; must authorize)
+    /// * `nominee` - Address proposed as the next admin
+    ///
+    /// # Errors
+    /// * `Unauthorized` - Caller is not the admin, or admin not set
+    pub fn nominate_admin(env: Env, caller: Address, nominee: Address) -> Result<(), QuickexError> {
+        require_admin(&env, &caller)?;
+        set_pending_admin(&env, &nominee);
+        Ok(())
+    }
+
+    /// Accept a pending admin nomination.
+    ///
+    /// Second step of the two-step transfer started by
+    /// [`nominate_admin`](QuickexContract::nominate_admin). Promotes `caller` to
+    /// admin, clears the nomination, and emits `AdminChanged`.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - The nominated address (must authorize)
+    ///
+    /// # Errors
+    /// * `Unauthorized` - No nomination is pending, or `caller` is not the nominee
+    pub fn accept_admin(env: Env, caller: Address) -> Result<(), QuickexError> {
+        caller.require_auth();
+        if get_pending_admin(&env) != Some(caller.clone()) {
+            return Err(QuickexError::Unauthorized);
+        }
+        let old_admin = get_admin(&env).ok_or(QuickexError::Unauthorized)?;
+        set_admin(&env, &caller);
+        clear_pending_admin(&env);
+        events::publish_admin_changed(&env, old_admin, caller, env.ledger().timestamp());
+        Ok(())
+    }
+
+    /// Get the address nominated to become admin, if any.
+    pub fn get_pending_admin(env: Env) -> Option<Address> {
+        get_pending_admin(&env)
+    }
+
+    /// Enable or disable the strict owner check on withdrawal (**Admin only**).
+    ///
+    /// When enabled, `withdraw` additionally requires the stored `entry.owner` to equal
+    /// the recipient, on top of the commitment match. Escrows funded on behalf of a
+    /// different recipient (e.g. via `deposit_with_commitment`) cannot be withdrawn
+    /// while the check is on.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - Caller address (must equal admin; must authorize)
+    /// * `enabled` - `true` to enforce the check, `false` to disable it
+    ///
+    /// # Errors
+    /// * `Unauthorized` - Caller is not the admin, or admin not set
+    pub fn set_strict_owner_check(
+        env: Env,
+        caller: Address,
+        enabled: bool,
+    ) -> Result<(), QuickexError> {
+        require_admin(&env, &caller)?;
+        set_strict_owner_check(&env, enabled);
+        Ok(())
+    }
+
+    /// Enable or disable privacy history dedup (**Admin only**).
+    ///
+    /// When enabled, [`enable_privacy`](QuickexContract::enable_privacy) does not append
+    /// a level to an account's history if it equals the most recent entry. Existing
+    /// history is left untouched. Disabled by default.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - Caller address (must equal admin; must authorize)
+    /// * `enabled` - `true` to skip consecutive duplicates
+    ///
+    /// # Errors
+    /// * `Unauthorized` - Caller is not the admin, or admin not set
+    pub fn set_privacy_history_dedup(
+        env: Env,
+        caller: Address,
+        enabled: bool,
+    ) -> Result<(), QuickexError> {
+        require_admin(&env, &caller)?;
+        set_privacy_history_dedup(&env, enabled);
+        Ok(())
+    }
+
+    /// Check whether privacy history dedup is enabled.
+    pub fn is_privacy_history_dedup(env: Env) -> bool {
+        is_privacy_history_dedup(&env)
+    }
+
+    /// Give the counterparty more time by pushing back an escrow's expiry.
+    ///
+    /// Adds `additional_secs` to `expires_at`. Only the owner can extend, and only
+    /// while the escrow is pending and has not yet expired. Escrows created with
+    /// `max_extensions` set can be extended at most that many times.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - 32-byte commitment hash identifying the escrow
+    /// * `caller` - Escrow owner (must authorize)
+    /// * `additional_secs` - Seconds to add to the current expiry
+    ///
+    /// # Errors
+    /// * `ContractPaused` - Contract is currently paused
+    /// * `CommitmentNotFound` - No escrow exists for this commitment
+    /// * `InvalidOwner` - Caller is not the escrow owner
+    /// * `AlreadySpent` - Escrow has already been withdrawn or refunded
+    /// * `EscrowNotExpirable` - Escrow has no expiry
+    /// * `EscrowExpired` - Escrow has already expired
+    /// * `ExtensionLimitReached` - Escrow has used all of its permitted extensions
+    pub fn extend_timeout(
+        env: Env,
+        commitment: BytesN<32>,
+        caller: Address,
+        additional_secs: u64,
+    ) -> Result<(), QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
+        }
+        escrow::extend_timeout(&env, commitment, caller, additional_secs)
+    }
+
+    /// Make a pending escrow refundable immediately (**Admin only**).
+    ///
+    /// Sets the escrow's `expires_at` to the current ledger time, for testing and
+    /// dispute resolution. This only lifts the time gate: `refund` still requires the
+    /// escrow owner, and withdrawals are blocked from then on as for any expired escrow.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - Caller address (must equal admin; must authorize)
+    /// * `commitment` - 32-byte commitment hash identifying the escrow
+    ///
+    /// # Errors
+    /// * `Unauthorized` - Caller is not the admin, or admin not set
+    /// * `CommitmentNotFound` - No escrow exists for this commitment
+    /// * `AlreadySpent` - Escrow is not pending
+    pub fn force_expire(
+        env: Env,
+        caller: Address,
+        commitment: BytesN<32>,
+    ) -> Result<(), QuickexError> {
+        require_admin(&env, &caller)?;
+        escrow::force_expire(&env, caller, commitment)
+    }
+
+    /// Reclaim the funds of a long-abandoned expired escrow (**Admin only**).
+    ///
+    /// Sends the escrowed amount to `destination` and marks the escrow `Refunded`.
+    /// Only allowed once the escrow has been expired for the sweep grace period
+    /// (90 days unless changed with
+    /// [`set_sweep_grace_period`](QuickexContract::set_sweep_grace_period)), so the
+    /// owner always has that long to refund first. Emits a `Swept` event.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - Caller address (must equal admin; must authorize)
+    /// * `commitment` - 32-byte commitment hash identifying the escrow
+    /// * `destination` - Address receiving the funds
+    ///
+    /// # Errors
+    /// * `Unauthorized` - Caller is not the admin, or admin not set
+    /// * `CommitmentNotFound` - No escrow exists for this commitment
+    /// * `AlreadySpent` - Escrow is already in a terminal state
+    /// * `EscrowNotExpired` - Escrow has no expiry, or the grace period has not passed
+    /// * `RefundDisabled` - Escrow forwards to an on-expiry recipient instead
+    pub fn sweep_expired(
+        env: Env,
+        caller: Address,
+        commitment: BytesN<32>,
+        destination: Address,
+    ) -> Result<(), QuickexError> {
+        require_admin(&env, &caller)?;
+        escrow::sweep_expired(&env, caller, commitment, destination)
+    }
+
+    /// Set how long after expiry an escrow becomes sweepable (**Admin only**).
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - Caller address (must equal admin; must authorize)
+    /// * `secs` - Grace period in seconds
+    ///
+    /// # Errors
+    /// * `Unauthorized` - Caller is not the admin, or admin not set
+    pub fn set_sweep_grace_period(
+        env: Env,
+        caller: Address,
+        secs: u64,
+    ) -> Result<(), QuickexError> {
+        require_admin(&env, &caller)?;
+        set_sweep_grace_period(&env, secs);
+        Ok(())
+    }
+
+    /// Get how long after expiry an escrow becomes sweepable, in seconds.
+    pub fn sweep_grace_period(env: Env) -> u64 {
+        get_sweep_grace_period(&env)
+    }
+
+    /// Check whether an escrow looks permanently stuck (read-only).
+    ///
+    /// Returns `true` for `Pending` escrows that have no expiry, and so can never
+    /// be refunded, and were created at least the stuck age ago (365 days unless
+    /// set with [`set_stuck_age`](QuickexContract::set_stuck_age)). If the salt for
+    /// such an escrow has been lost, only
+    /// [`rescue_stuck`](QuickexContract::rescue_stuck) can recover it. Returns
+    /// `false` for missing escrows.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - 32-byte commitment hash identifying the escrow
+    pub fn is_stuck(env: Env, commitment: BytesN<32>) -> bool {
+        escrow::is_stuck(&env, commitment)
+    }
+
+    /// Recover the funds of a stuck escrow to `to` (**Admin only**).
+    ///
+    /// Only escrows reported by [`is_stuck`](QuickexContract::is_stuck) qualify:
+    /// `Pending`, with no expiry, and at least the stuck age old. The contract must
+    /// be paused, so a rescue is always a deliberate emergency action. The escrow
+    /// becomes `Refunded` and a `Rescued` event records the admin, recipient and
+    /// amount.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - Caller address (must equal admin; must authorize)
+    /// * `commitment` - 32-byte commitment hash identifying the escrow
+    /// * `to` - Recovery address that receives the funds
+    ///
+    /// # Errors
+    /// * `Unauthorized` - Caller is not the admin, or admin not set
+    /// * `ContractNotPaused` - The contract is not paused
+    /// * `CommitmentNotFound` - No escrow exists for the commitment
+    /// * `EscrowNotStuck` - The escrow is settled, has an expiry, or is too young
+    pub fn rescue_stuck(
+        env: Env,
+        caller: Address,
+        commitment: BytesN<32>,
+        to: Address,
+    ) -> Result<(), QuickexError> {
+        require_admin(&env, &caller)?;
+        if !is_paused(&env) {
+            return Err(QuickexError::ContractNotPaused);
+        }
+        escrow::rescue_stuck(&env, caller, commitment, to)
+    }
+
+    /// Set the age at which non-expiring escrows are reported as stuck (**Admin only**).
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - Caller address (must equal admin; must authorize)
+    /// * `secs` - Age in seconds
+    ///
+    /// # Errors
+    /// * `Unauthorized` - Caller is not the admin, or admin not set
+    pub fn set_stuck_age(env: Env, caller: Address, secs: u64) -> Result<(), QuickexError> {
+        require_admin(&env, &caller)?;
+        set_stuck_age(&env, secs);
+        Ok(())
+    }
+
+    /// Get the age at which non-expiring escrows are reported as stuck, in seconds.
+    pub fn stuck_age(env: Env) -> u64 {
+        get_stuck_age(&env)
+    }
+
+    /// Turn the global privacy kill-switch on or off (**Admin only**).
+    ///
+    /// While on, every account is treated as having privacy and stealth mode off:
+    /// [`get_escrow_details`](QuickexContract::get_escrow_details) returns full details
+    /// to anyone and [`get_privacy`](QuickexContract::get_privacy) returns `false`.
+    /// Stored per-account settings are left untouched, so turning the switch off
+    /// restores them. The privacy fee discount is unaffected. Emits a
+    /// `GlobalPrivacyDisabled` event.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - Caller address (must equal admin; must authorize)
+    /// * `disabled` - `true` to override every account's privacy
+    ///
+    /// # Errors
+    /// * `Unauthorized` - Caller is not the admin, or admin not set
+    pub fn disable_privacy_globally(
+        env: Env,
+        caller: Address,
+        disabled: bool,
+    ) -> Result<(), QuickexError> {
+        require_admin(&env, &caller)?;
+        set_privacy_disabled_globally(&env, disabled);
+        events::publish_global_privacy_disabled(&env, caller, disabled);
+        Ok(())
+    }
+
+    /// Check whether the global privacy kill-switch is on.
+    pub fn is_privacy_disabled_globally(env: Env) -> bool {
+        is_privacy_disabled_globally(&env)
+    }
+
+    /// Delete a spent or refunded escrow from storage (**Admin only**).
+    ///
+    /// Reclaims storage for escrows that can no longer change. Counters are not
+    /// affected: terminal escrows already left the active counts when they were
+    /// withdrawn or refunded, and `status_counts` keeps counting them. Afterwards the
+    /// commitment and its escrow ID resolve to nothing.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - Caller address (must equal admin; must authorize)
+    /// * `commitment` - 32-byte commitment hash identifying the escrow
+    ///
+    /// # Errors
+    /// * `Unauthorized` - Caller is not the admin, or admin not set
+    /// * `CommitmentNotFound` - No escrow exists for this commitment
+    /// * `EscrowNotTerminal` - Escrow is still pending
+    pub fn purge_escrow(
+        env: Env,
+        caller: Address,
+        commitment: BytesN<32>,
+    ) -> Result<(), QuickexError> {
+        require_admin(&env, &caller)?;
+        escrow::purge_escrow(&env, commitment)
+    }
+
+    /// Restrict an operation to the admin, or reopen it (**Admin only**).
+    ///
+    /// While an operation is gated, its entry points fail with `Unauthorized` unless
+    /// the acting account is the admin. Nothing is gated by default.
+    ///
+    /// Gateable operations:
+    /// * `deposit` - Every entry point that creates an escrow or funds a pool,
+    ///   checked against the depositing account
+    ///
+    /// Other symbols are stored but have no effect.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - Caller address (must equal admin; must authorize)
+    /// * `operation` - Operation symbol, e.g. `deposit`
+    /// * `gated` - `true` to restrict the operation to the admin
+    ///
+    /// # Errors
+    /// * `Unauthorized` - Caller is not the admin, or admin not set
+    pub fn set_operation_gated(
+        env: Env,
+        caller: Address,
+        operation: Symbol,
+        gated: bool,
+    ) -> Result<(), QuickexError> {
+        require_admin(&env, &caller)?;
+        set_operation_gated(&env, &operation, gated);
+        Ok(())
+    }
+
+    /// List the operations currently restricted to the admin.
+    pub fn gated_operations(env: Env) -> Vec<Symbol> {
+        get_gated_operations(&env)
+    }
+
+    /// Add or remove an address from the refund allow-list (**Admin only**).
+    ///
+    /// While the list is non-empty, `refund` and `refund_with_fallback` fail with
+    /// `RecipientNotAllowed` unless the address being paid is on it. The list is
+    /// empty by default, which leaves refunds unrestricted. Refunds that keep the
+    /// funds in the contract (`refund_and_reassign`) are not affected.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - Caller address (must equal admin; must authorize)
+    /// * `recipient` - Address to add or remove
+    /// * `allowed` - `true` to add the address, `false` to remove it
+    ///
+    /// # Errors
+    /// * `Unauthorized` - Caller is not the admin, or admin not set
+    pub fn set_refund_recipient_allowed(
+        env: Env,
+        caller: Address,
+        recipient: Address,
+        allowed: bool,
+    ) -> Result<(), QuickexError> {
+        require_admin(&env, &caller)?;
+        set_refund_recipient_allowed(&env, &recipient, allowed);
+        Ok(())
+    }
+
+    /// Add a token to the deposit allow-list (**Admin only**).
+    ///
+    /// While the allow-list is non-empty, every entry point that creates an escrow
+    /// or opens a pool fails with `TokenNotAllowed` for tokens not on it. The list
+    /// is empty by default, which accepts any token. Existing escrows are not
+    /// affected. Adding a listed token is a no-op.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - Caller address (must equal admin; must authorize)
+    /// * `token` - Token contract address to accept
+    ///
+    /// # Errors
+    /// * `Unauthorized` - Caller is not the admin, or admin not set
+    pub fn add_allowed_token(
+        env: Env,
+        caller: Address,
+        token: Address,
+    ) -> Result<(), QuickexError> {
+        require_admin(&env, &caller)?;
+        set_token_allowed(&env, &token, true);
+        Ok(())
+    }
+
+    /// Remove a token from the deposit allow-list (**Admin only**).
+    ///
+    /// Removing the last token empties the list, so every token is accepted again.
+    /// Removing an unlisted token is a no-op.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - Caller address (must equal admin; must authorize)
+    /// * `token` - Token contract address to stop accepting
+    ///
+    /// # Errors
+    /// * `Unauthorized` - Caller is not the admin, or admin not set
+    pub fn remove_allowed_token(
+        env: Env,
+        caller: Address,
+        token: Address,
+    ) -> Result<(), QuickexError> {
+        require_admin(&env, &caller)?;
+        set_token_allowed(&env, &token, false);
+        Ok(())
+    }
+
+    /// Check whether new escrows may be created in `token`.
+    pub fn is_token_allowed(env: Env, token: Address) -> bool {
+        is_token_allowed(&env, &token)
+    }
+
+    /// List the addresses refunds may be paid to. Empty means unrestricted.
+    pub fn refund_allow_list(env: Env) -> Vec<Address> {
+        get_refund_allow_list(&env)
+    }
+
+    /// Enable or disable the auto-pause circuit breaker (**Admin only**).
+    ///
+    /// When enabled, every withdrawal, refund, and expiry forward first compares the
+    /// contract's balance of the escrow token with its tracked TVL. If the balance is
+    /// short, the contract pauses itself, emits `AutoPaused`, and the call returns
+    /// without moving funds (`withdraw` returns `false`). Disabled by default.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - Caller address (must equal admin; must authorize)
+    /// * `enabled` - `true` to arm the circuit breaker
+    ///
+    /// # Errors
+    /// * `Unauthorized` - Caller is not the admin, or admin not set
+    pub fn set_auto_pause(env: Env, caller: Address, enabled: bool) -> Result<(), QuickexError> {
+        require_admin(&env, &caller)?;
+        set_auto_pause(&env, enabled);
+        Ok(())
+    }
+
+    /// Allow or forbid re-depositing onto spent or refunded commitments (**Admin only**).
+    ///
+    /// When enabled, [`deposit_with_commitment`](QuickexContract::deposit_with_commitment)
+    /// (and the gift and CPI deposits built on it) may reuse a commitment whose escrow
+    /// is `Spent` or `Refunded`, overwriting the old entry with a fresh `Pending` one.
+    /// Pending and expired escrows are never overwritten. Disabled by default.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - Caller address (must equal admin; must authorize)
+    /// * `enabled` - `true` to allow re-deposits on terminal commitments
+    ///
+    /// # Errors
+    /// * `Unauthorized` - Caller is not the admin, or admin not set
+    pub fn set_terminal_redeposit(
+        env: Env,
+        caller: Address,
+        enabled: bool,
+    ) -> Result<(), QuickexError> {
+        require_admin(&env, &caller)?;
+        set_terminal_redeposit(&env, enabled);
+        Ok(())
+    }
+
+    /// Set the maximum number of items accepted by batch operations (**Admin only**).
+    ///
+    /// Applies to every batch input (`deposit_batch`, `grant_viewers`,
+    /// `revoke_viewers`, `get_states`) and caps the page size of paged queries such
+    /// as `list_tokens` and `reconcile_all`. Defaults to 50; lower it if batches
+    /// approach the instruction limit. Installment schedules keep their fixed cap
+    /// of 50.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - Caller address (must equal admin; must authorize)
+    /// * `size` - New maximum; must be positive
+    ///
+    /// # Errors
+    /// * `Unauthorized` - Caller is not the admin, or admin not set
+    /// * `InvalidAmount` - `size` is zero
+    pub fn set_max_batch_size(env: Env, caller: Address, size: u32) -> Result<(), QuickexError> {
+        require_admin(&env, &caller)?;
+        if size == 0 {
+            return Err(QuickexError::InvalidAmount);
+        }
+        set_max_batch_size(&env, size);
+        Ok(())
+    }
+
+    /// Get the maximum number of items accepted by batch operations.
+    pub fn max_batch_size(env: Env) -> u32 {
+        escrow::max_batch_size(&env)
+    }
+
+    /// Check whether re-deposits onto spent or refunded commitments are allowed.
+    pub fn is_terminal_redeposit(env: Env) -> bool {
+        is_terminal_redeposit(&env)
+    }
+
+    /// Check whether the auto-pause circuit breaker is enabled.
+    pub fn is_auto_pause(env: Env) -> bool {
+        is_auto_pause(&env)
+    }
+
+    /// Contract balance of `token` minus the total held in non-terminal escrows.
+    ///
+    /// Negative means the contract is insolvent for that token. Escrows created
+    /// before TVL tracking are not counted.
+    pub fn solvency_check(env: Env, token: Address) -> i128 {
+        escrow::solvency_check(&env, &token)
+    }
+
+    /// Report [`solvency_check`](QuickexContract::solvency_check) for every token
+    /// ever escrowed, for a full audit.
+    ///
+    /// Tokens are listed in the order they were first escrowed, at most the max batch
+    /// size (default 50) per call.
+    /// Pass `start = 0` for the first page and advance `start` by the number of
+    /// entries returned; an empty result means the end was reached.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `start` - Index of the first token to report
+    pub fn reconcile_all(env: Env, start: u32) -> Vec<(Address, i128)> {
+        escrow::reconcile_all(&env, start)
+    }
+
+    /// Check the contract's storage bookkeeping for inconsistencies (read-only).
+    ///
+    /// Meant to be run by operators after an upgrade. Returns each invariant's name
+    /// with `true` if it holds:
+    /// * `counter_covers_active` - The escrow counter is at least the pending count
+    /// * `tvl_non_negative` - No known token has a negative TVL
+    /// * `status_counts_sum` - The status counts sum to exactly the number of escrows
+    ///   ever created: the escrow counter minus the IDs handed out by `create_escrow`,
+    ///   which stores no escrow
+    pub fn verify_storage_invariants(env: Env) -> Vec<(Symbol, bool)> {
+        escrow::verify_storage_invariants(&env)
+    }
+
+    /// Number of distinct tokens ever escrowed (read-only).
+    pub fn token_count(env: Env) -> u32 {
+        get_known_tokens(&env).len()
+    }
+
+    /// List tokens ever escrowed, in the order they were first used (read-only).
+    ///
+    /// Returns at most `limit` tokens, capped at the max batch size, starting at index
+    /// `start`. An empty result means `start` is past the end; see
+    /// [`token_count`](QuickexContract::token_count) for the total.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `start` - Index of the first token to return
+    /// * `limit` - Maximum number of tokens to return
+    pub fn list_tokens(env: Env, start: u32, limit: u32) -> Vec<Address> {
+        escrow::list_tokens(&env, start, limit)
+    }
+
+    /// List an owner's active escrows, one page at a time (read-only).
+    ///
+    /// Returns at most `limit` commitments, capped at the max batch size, starting at
+    /// index `start` of the owner's index of non-terminal escrows, in creation order.
+    /// Spent and refunded escrows are pruned from the index, so they never appear. If
+    /// `caller` is neither the owner nor a granted viewer and the owner's privacy level
+    /// hides escrow owners (level 2 or above) or stealth mode is on, the result is
+    /// empty, so strangers cannot enumerate a private owner's escrows.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - Owner whose escrows are listed
+    /// * `start` - Index of the first commitment to return
+    /// * `limit` - Maximum number of commitments to return
+    /// * `caller` - Address of the caller; used for privacy enforcement
+    pub fn get_escrows_by_owner(
+        env: Env,
+        owner: Address,
+        start: u32,
+        limit: u32,
+        caller: Address,
+    ) -> Vec<BytesN<32>> {
+        escrow::escrows_by_owner(&env, &owner, start, limit, &caller)
+    }
+
+    /// Get an owner's escrow statistics in one call (read-only).
+    ///
+    /// Returns the number of active escrows, the number ever created, and the total
+    /// amount locked in the active ones. The total adds up amounts across tokens.
+    /// Returns `None` if the owner's privacy hides amounts from `caller` (level 1 or
+    /// above, as in [`get_escrow_details`](QuickexContract::get_escrow_details)), or
+    /// if the owner is in stealth mode and `caller` is neither the owner nor a granted
+    /// viewer.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - Owner whose statistics are returned
+    /// * `caller` - Address of the caller; used for privacy enforcement
+    ///
+    /// # Errors
+    /// * `InternalError` - The value sum overflows
+    pub fn owner_stats(
+        env: Env,
+        owner: Address,
+        caller: Address,
+    ) -> Result<Option<OwnerStats>, QuickexError> {
+        escrow::owner_stats(&env, &owner, &caller)
+    }
+
+    /// Configure withdrawal fees (**Admin only**).
+    ///
+    /// Fees are deducted from each withdrawal payout and sent to the admin. Accounts
+    /// with privacy enabled pay `fee_bps_private` instead of `fee_bps`, as an
+    /// incentive to use privacy; pass `None` to charge them the normal rate.
+    /// Refunds are never charged.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - Caller address (must equal admin; must authorize)
+    /// * `fee_bps` - Fee in basis points for public recipients
+    /// * `fee_bps_private` - Fee in basis points for privacy-enabled recipients
+    ///
+    /// # Errors
+    /// * `Unauthorized` - Caller is not the admin, or admin not set
+    /// * `InvalidFee` - A rate exceeds 10 000 basis points
+    pub fn set_withdrawal_fees(
+        env: Env,
+        caller: Address,
+        fee_bps: u32,
+        fee_bps_private: Option<u32>,
+    ) -> Result<(), QuickexError> {
+        require_admin(&env, &caller)?;
+        let config = FeeConfig {
+            fee_bps,
+            fee_bps_private,
+        };
+        fees::check_fee_config(&config)?;
+        set_fee_config(&env, &config);
+        Ok(())
+    }
+
+    /// Get the current withdrawal fee configuration (read-only).
+    pub fn get_fee_config(env: Env) -> FeeConfig {
+        get_fee_config(&env)
+    }
+
+    /// Get the cumulative withdrawal fees collected for a token (read-only).
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `token` - Token contract address
+    pub fn total_fees(env: Env, token: Address) -> i128 {
+        get_fees_collected(&env, &token)
+    }
+
+    /// Check whether the strict owner check on withdrawal is enabled.
+    pub fn is_strict_owner_check(env: Env) -> bool {
+        is_strict_owner_check(&env)
+    }
+
+    /// Check if the contract is currently paused.
+    ///
+    /// Returns `true` if paused, `false` otherwise.
+    pub fn is_paused(env: Env) -> bool {
+        is_paused(&env)
+    }
+
+    /// Get the current admin address.
+    ///
+    /// Returns `None` if the contract has not been initialized.
+    pub fn get_admin(env: Env) -> Option<Address> {
+        get_admin(&env)
+    }
+
+    /// Get the ledger timestamp at which the contract was initialized.
+    ///
+    /// Returns `None` if the contract has not been initialized.
+    pub fn initialized_at(env: Env) -> Option<u64> {
+        get_initialized_at(&env)
+    }
+
+    /// Get the status of an escrow by its commitment hash (read-only).
+    ///
+    /// Returns `Pending`, `Spent`, `Expired`, or `Refunded` if an escrow exists; `None` otherwise.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - 32-byte commitment hash used as the escrow key
+    pub fn get_commitment_state(env: Env, commitment: BytesN<32>) -> Option<EscrowStatus> {
+        let commitment_bytes: Bytes = commitment.into();
+        let entry: Option<EscrowEntry> = get_escrow(&env, &commitment_bytes);
+        entry.map(|e| e.status)
+    }
+
+    /// Get the statuses of several escrows in one call (read-only).
+    ///
+    /// Results are positional: entry `i` is the status of `commitments[i]`, or `None`
+    /// if no escrow exists. Unlike
+    /// [`get_commitment_state`](QuickexContract::get_commitment_state), a `Pending`
+    /// escrow past its expiry is reported as `Expired`.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitments` - Up to the max batch size (default 50) of commitment hashes
+    ///
+    /// # Errors
+    /// * `BatchTooLarge` - More commitments supplied than the max batch size
+    pub fn get_states(
+        env: Env,
+        commitments: Vec<BytesN<32>>,
+    ) -> Result<Vec<Option<EscrowStatus>>, QuickexError> {
+        escrow::get_states(&env, commitments)
+    }
+
+    /// Record a snapshot of contract-wide escrow activity for on-chain analytics.
+    ///
+    /// Permissionless, but at most once per snapshot interval (one day unless the
+    /// admin sets another with
+    /// [`set_snapshot_interval`](QuickexContract::set_snapshot_interval)). The last
+    /// 30 snapshots are kept; older ones are dropped. `tvl_sum` adds up the TVL of
+    /// every token, so it is only meaningful as a trend when several tokens are in
+    /// use. Returns the recorded snapshot.
+    ///
+    /// # Errors
+    /// * `ContractPaused` - Contract is currently paused
+    /// * `SnapshotTooSoon` - The previous snapshot is younger than the interval
+    pub fn snapshot_activity(env: Env) -> Result<ActivitySnapshot, QuickexError> {
+        if is_paused(&env) {
+            return Err(QuickexError::ContractPaused);
+        }
+        escrow::snapshot_activity(&env)
+    }
+
+    /// Get up to `count` of the most recent activity snapshots, newest first (read-only).
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `count` - Maximum number of snapshots to return
+    pub fn get_snapshots(env: Env, count: u32) -> Vec<ActivitySnapshot> {
+        let snapshots = get_activity_snapshots(&env);
+        snapshots.slice(0..count.min(snapshots.len()))
+    }
+
+    /// Set the minimum number of seconds between activity snapshots (**Admin only**).
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - Caller address (must equal admin; must authorize)
+    /// * `secs` - New interval; `0` removes the rate limit
+    ///
+    /// # Errors
+    /// * `Unauthorized` - Caller is not the admin, or admin not set
+    pub fn set_snapshot_interval(env: Env, caller: Address, secs: u64) -> Result<(), QuickexError> {
+        require_admin(&env, &caller)?;
+        set_snapshot_interval(&env, secs);
+        Ok(())
+    }
+
+    /// Get contract-wide escrow counts per lifecycle status (read-only).
+    ///
+    /// Maintained as counters on every transition, so no escrow scan is needed.
+    pub fn status_counts(env: Env) -> StatusCounts {
+        get_status_counts(&env)
+    }
+
+    /// Get the number of ledgers until an escrow's storage entry may be archived (read-only).
+    ///
+    /// Every write to an escrow extends its TTL; this reports the remaining ledgers from
+    /// the last extension the contract recorded. Clients should call
+    /// [`extend_escrow_ttl`](QuickexContract::extend_escrow_ttl) well before it reaches 0.
+    /// Returns `None` if the escrow does not exist.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - 32-byte commitment hash identifying the escrow
+    pub fn escrow_ttl_remaining(env: Env, commitment: BytesN<32>) -> Option<u32> {
+        get_escrow_ttl_remaining(&env, &commitment.into())
+    }
+
+    /// Extend an escrow's storage TTL so it is not archived. Callable by anyone.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - 32-byte commitment hash identifying the escrow
+    ///
+    /// # Errors
+    /// * `CommitmentNotFound` - No escrow exists for the commitment
+    pub fn extend_escrow_ttl(env: Env, commitment: BytesN<32>) -> Result<(), QuickexError> {
+        let commitment: Bytes = commitment.into();
+        if !has_escrow(&env, &commitment) {
+            return Err(QuickexError::CommitmentNotFound);
+        }
+        storage::extend_escrow_ttl(&env, &commitment);
+        Ok(())
+    }
+
+    /// Extend the storage TTL of every active escrow `owner` holds.
+    ///
+    /// Walks the owner's index of non-terminal escrows and extends each entry to at
+    /// least `ledgers` ledgers, clamped to the network maximum; entries already living
+    /// longer are left alone. Returns the number of escrows visited.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - Escrow owner (must authorize)
+    /// * `ledgers` - Minimum number of ledgers each escrow should stay live for
+    pub fn bump_all_ttls(env: Env, owner: Address, ledgers: u32) -> u32 {
+        owner.require_auth();
+        let commitments = get_owner_escrows(&env, &owner);
+        for commitment in commitments.iter() {
+            storage::extend_escrow_ttl_to(&env, &commitment.into(), ledgers);
+        }
+        commitments.len()
+    }
+
+    /// Get the number of non-terminal escrows held for a token (read-only).
+    ///
+    /// Incremented on every deposit and decremented when an escrow is withdrawn
+    /// or refunded. Complements per-token value figures with an escrow count.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `token` - The token contract address
+    pub fn escrow_count_by_token(env: Env, token: Address) -> u64 {
+        get_token_escrow_count(&env, &token)
+    }
+
+    /// Verify withdrawal parameters without submitting a transaction (read-only).
+    ///
+    /// Recomputes the commitment from `amount`, `salt`, and `owner`, then checks that an
+    /// escrow exists with status `Pending`, matching amount, and not yet expired.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `amount` - Amount to verify (non-negative)
+    /// * `salt` - Salt used when creating the deposit
+    /// * `owner` - Owner of the escrow
+    pub fn verify_proof_view(env: Env, amount: i128, salt: Bytes, owner: Address) -> bool {
+        let commitment_result =
+            commitment::create_amount_commitment(&env, owner.clone(), amount, salt);
+
+        let commitment = match commitment_result {
+            Ok(c) => c,
+            Err(_) => return false,
+        };
+
+        let commitment_bytes: Bytes = commitment.into();
+        let entry: Option<EscrowEntry> = get_escrow(&env, &commitment_bytes);
+
+        match entry {
+            Some(e) => {
+                if e.status != EscrowStatus::Pending {
+                    return false;
+                }
+                if e.expires_at > 0 && env.ledger().timestamp() >= e.expires_at {
+                    return false;
+                }
+                e.amount == amount
+            }
+            None => false,
+        }
+    }
+
+    /// Get a privacy-aware view of escrow details for a commitment hash (read-only).
+    ///
+    /// Returns a [`PrivacyAwareEscrowView`] if an escrow exists for the commitment,
+    /// or `None` otherwise.
+    ///
+    /// ## Privacy behaviour
+    /// - If `caller` is neither the owner nor a viewer granted by the owner, fields
+    ///   are hidden according to the owner's privacy level: 1 hides `amount`, 2 also
+    ///   hides `owner`, and 3 or above hides everything except `status`. The boolean
+    ///   privacy flag counts as level 2; the higher of the two applies.
+    /// - If the owner's level is 0 and privacy is off, or `caller` is the escrow
+    ///   owner or a granted viewer, all fields are returned in full.
+    /// - If the owner is in **stealth mode** (see
+    ///   [`set_stealth`](QuickexContract::set_stealth)), callers other than the owner
+    ///   and granted viewers get `None`, the same as for a missing escrow, instead of
+    ///   a redacted view.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - 32-byte commitment hash identifying the escrow
+    /// * `caller` - Address of the caller; used to determine whether full details
+    ///   are returned when privacy is enabled
+    pub fn get_escrow_details(
+        env: Env,
+        commitment: BytesN<32>,
+        caller: Address,
+    ) -> Option<PrivacyAwareEscrowView> {
+        let commitment_bytes: Bytes = commitment.into();
+        let entry = get_escrow(&env, &commitment_bytes)?;
+        if !privacy::can_see(&env, &entry.owner, &caller) {
+            return None;
+        }
+        let level = privacy::view_level(&env, &entry.owner, &caller);
+        Some(PrivacyAwareEscrowView::from_entry(entry, level))
+    }
+
+    /// Check whether a stranger currently sees less than the full details of an escrow
+    /// (read-only).
+    ///
+    /// `true` if the owner has privacy enabled or a non-zero privacy level, so
+    /// [`get_escrow_details`](QuickexContract::get_escrow_details) redacts some
+    /// fields, or is in stealth mode, so it hides the escrow entirely. Returns
+    /// `false` if no escrow exists for the commitment.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - 32-byte commitment hash identifying the escrow
+    pub fn is_escrow_private(env: Env, commitment: BytesN<32>) -> bool {
+        match get_escrow(&env, &commitment.into()) {
+            Some(entry) => privacy::is_hidden_from_strangers(&env, &entry.owner),
+            None => false,
+        }
+    }
+
+    /// Get an escrow's raw amount together with its token's decimal count (read-only).
+    ///
+    /// Saves clients a separate `decimals` call to the token when displaying amounts.
+    /// Returns `None` if the escrow does not exist, if the owner's privacy hides the
+    /// amount from `caller` (level 1 or above, as in
+    /// [`get_escrow_details`](QuickexContract::get_escrow_details)), or if the owner
+    /// is in stealth mode and `caller` is neither the owner nor a granted viewer.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - 32-byte commitment hash identifying the escrow
+    /// * `caller` - Address of the caller; used for privacy enforcement
+    pub fn escrow_amount_scaled(
+        env: Env,
+        commitment: BytesN<32>,
+        caller: Address,
+    ) -> Option<(i128, u32)> {
+        let entry = get_escrow(&env, &commitment.into())?;
+        if !privacy::can_view_amount(&env, &entry.owner, &caller)
+            || !privacy::can_see(&env, &entry.owner, &caller)
+        {
+            return None;
+        }
+        let decimals = token::Client::new(&env, &entry.token).decimals();
+        Some((entry.amount, decimals))
+    }
+
+    /// Compare a claimed amount with an escrow's stored amount, for dispute tooling
+    /// (read-only).
+    ///
+    /// Returns `stored_amount - claimed`: 0 for a correct claim, negative for an
+    /// over-claim, positive for an under-claim. Returns `None` if the escrow does not
+    /// exist, if the owner's privacy hides the amount from `caller` (level 1 or above,
+    /// as in [`get_escrow_details`](QuickexContract::get_escrow_details)), if the
+    /// owner is in stealth mode and `caller` is neither the owner nor a granted
+    /// viewer, or if the difference overflows.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - 32-byte commitment hash identifying the escrow
+    /// * `claimed` - Amount the disputing party claims was escrowed
+    /// * `caller` - Address of the caller; used for privacy enforcement
+    pub fn amount_discrepancy(
+        env: Env,
+        commitment: BytesN<32>,
+        claimed: i128,
+        caller: Address,
+    ) -> Option<i128> {
+        let entry = get_escrow(&env, &commitment.into())?;
+        if !privacy::can_view_amount(&env, &entry.owner, &caller)
+            || !privacy::can_see(&env, &entry.owner, &caller)
+        {
+            return None;
+        }
+        entry.amount.checked_sub(claimed)
+    }
+
+    /// Get only the owner of an escrow, with privacy gating (read-only).
+    ///
+    /// A cheaper alternative to [`get_escrow_details`](QuickexContract::get_escrow_details)
+    /// for clients that just need to contact the owner. Returns `None` if the escrow
+    /// does not exist, if the owner's privacy hides the owner from `caller` (level 2
+    /// or above, as in [`get_escrow_details`](QuickexContract::get_escrow_details)),
+    /// or if the owner is in stealth mode and `caller` is neither the owner nor a
+    /// granted viewer.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment` - 32-byte commitment hash identifying the escrow
+    /// * `caller` - Address of the caller; used for privacy enforcement
+    pub fn escrow_owner(env: Env, commitment: BytesN<32>, caller: Address) -> Option<Address> {
+        let entry = get_escrow(&env, &commitment.into())?;
+        if !privacy::can_view_owner(&env, &entry.owner, &caller)
+            || !privacy::can_see(&env, &entry.owner, &caller)
+        {
+            return None;
+        }
+        Some(entry.owner)
+    }
+
+    /// Check whether two escrows have the same owner without revealing who it is (read-only).
+    ///
+    /// Returns `None` if either escrow is missing, or if either owner's privacy hides
+    /// the owner from `caller` (level 2 or above, as in
+    /// [`get_escrow_details`](QuickexContract::get_escrow_details)), or if either
+    /// owner is in stealth mode and `caller` is neither that owner nor one of their
+    /// granted viewers. Otherwise only the equality bit is returned.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `commitment_a` - Commitment of the first escrow
+    /// * `commitment_b` - Commitment of the second escrow
+    /// * `caller` - Address of the caller; used for privacy enforcement
+    pub fn same_owner(
+        env: Env,
+        commitment_a: BytesN<32>,
+        commitment_b: BytesN<32>,
+        caller: Address,
+    ) -> Option<bool> {
+        let a = get_escrow(&env, &commitment_a.into())?;
+        let b = get_escrow(&env, &commitment_b.into())?;
+        if !privacy::can_view_owner(&env, &a.owner, &caller)
+            || !privacy::can_view_owner(&env, &b.owner, &caller)
+            || !privacy::can_see(&env, &a.owner, &caller)
+            || !privacy::can_see(&env, &b.owner, &caller)
+        {
+            return None;
+        }
+        Some(a.owner == b.owner)
+    }
+
+    /// Commitment of the owner's oldest pending escrow (read-only).
+    ///
+    /// Escrows past their expiry but not yet refunded still count, so stale escrows
+    /// surface here. Returns `None` if the owner has no pending escrow, or if `caller`
+    /// is neither the owner nor a granted viewer and the owner's privacy level hides
+    /// escrow owners (level 2 or above) or stealth mode is on.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `owner` - Owner whose escrows are searched
+    /// * `caller` - Address of the caller; used for privacy enforcement
+    pub fn oldest_active_escrow(env: Env, owner: Address, caller: Address) -> Option<BytesN<32>> {
+        if !privacy::can_view_owner(&env, &owner, &caller)
+            || !privacy::can_see(&env, &owner, &caller)
+        {
+            return None;
+        }
+        escrow::oldest_active_escrow(&env, &owner)
+    }
+
+    /// List escrows created within a timestamp range, for period-based reporting
+    /// (read-only).
+    ///
+    /// Scans escrow IDs `start` to `start + limit - 1` (IDs start at 1; `limit` is
+    /// capped at the max batch size) and returns the commitments of those whose `created_at` lies in
+    /// `[from_ts, to_ts]`, in ID order. A page may hold fewer matches than `limit`, or
+    /// none; advance `start` by `limit` until it passes the latest ID. Purged escrows
+    /// and escrows of owners in stealth mode are skipped.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `from_ts` - Earliest creation timestamp, inclusive
+    /// * `to_ts` - Latest creation timestamp, inclusive
+    /// * `start` - First escrow ID to scan
+    /// * `limit` - Number of escrow IDs to scan
+    pub fn escrows_created_between(
+        env: Env,
+        from_ts: u64,
+        to_ts: u64,
+        start: u64,
+        limit: u32,
+    ) -> Vec<BytesN<32>> {
+        escrow::escrows_created_between(&env, from_ts, to_ts, start, limit)
+    }
+
+    /// Sum the amounts of a token's escrows that have expired but were never refunded
+    /// (read-only).
+    ///
+    /// Highlights funds owners have forgotten to reclaim. Scans escrow IDs `start` to
+    /// `start + limit - 1` (IDs start at 1; `limit` is capped at the max batch size) so each call stays
+    /// within read limits; add up the results of successive pages, advancing `start`
+    /// by `limit`, for the contract-wide total.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `token` - Token to total
+    /// * `start` - First escrow ID to scan
+    /// * `limit` - Number of escrow IDs to scan
+    ///
+    /// # Errors
+    /// * `InternalError` - The sum overflows
+    pub fn unrefunded_expired_value(
+        env: Env,
+        token: Address,
+        start: u64,
+        limit: u32,
+    ) -> Result<i128, QuickexError> {
+        escrow::unrefunded_expired_value(&env, &token, start, limit)
+    }
+
+    /// Look up an escrow by the sequential ID assigned at deposit time (read-only).
+    ///
+    /// The ID is published in the `Deposit` event. Returns the escrow's commitment
+    /// together with the same privacy-aware view as
+    /// [`get_escrow_details`](QuickexContract::get_escrow_details), or `None` if no
+    /// escrow was assigned `escrow_id`.
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `escrow_id` - ID assigned when the escrow was created
+    /// * `caller` - Address of the caller; used for privacy enforcement
+    pub fn get_escrow_by_id(
+        env: Env,
+        escrow_id: u64,
+        caller: Address,
+    ) -> Option<(BytesN<32>, PrivacyAwareEscrowView)> {
+        let commitment = get_escrow_commitment_by_id(&env, escrow_id)?;
+        let view = Self::get_escrow_details(env, commitment.clone(), caller)?;
+        Some((commitment, view))
+    }
+
+    /// Upgrade the contract to a new WASM implementation (**Admin only**).
+    ///
+    /// Caller must equal admin and authorize. The new WASM must be pre-uploaded to the network.
+    /// Emits an upgrade event for audit and records the upgrade for
+    /// [`last_upgrade`](QuickexContract::last_upgrade).
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `caller` - Caller address (must equal admin; must authorize)
+    /// * `new_wasm_hash` - 32-byte hash of the new WASM code
+    ///
+    /// # Errors
+    /// * `Unauthorized` - Caller is not the admin, or admin not set
     ///
     /// # Security
     /// Updates the contract's executable code. Use with care in production.
@@ -483,8 +2926,23 @@ impl QuickexContract {
             .update_current_contract_wasm(new_wasm_hash.clone());
 
         let timestamp = env.ledger().timestamp();
+        set_last_upgrade(
+            &env,
+            &UpgradeInfo {
+                wasm_hash: new_wasm_hash.clone(),
+                timestamp,
+                admin: admin.clone(),
+            },
+        );
         events::publish_contract_upgraded(&env, new_wasm_hash, &admin, timestamp);
 
         Ok(())
     }
+
+    /// Get the WASM hash, timestamp and admin of the most recent upgrade (read-only).
+    ///
+    /// Returns `None` if the contract has never been upgraded.
+    pub fn last_upgrade(env: Env) -> Option<UpgradeInfo> {
+        get_last_upgrade(&env)
+    }
 }
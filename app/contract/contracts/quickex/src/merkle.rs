@@ -0,0 +1,36 @@
+//! Merkle inclusion proofs over SHA-256.
+//!
+//! Inner nodes hash their two children in sorted order,
+//! `SHA256(min(a, b) || max(a, b))`, so proofs carry sibling hashes only and no
+//! left/right flags. Leaves are used as-is (typically escrow commitments).
+
+use soroban_sdk::{Bytes, BytesN, Env, Vec};
+
+/// Hash two sibling nodes into their parent.
+pub fn hash_pair(env: &Env, a: &BytesN<32>, b: &BytesN<32>) -> BytesN<32> {
+    let (first, second) = if a.to_array() <= b.to_array() {
+        (a, b)
+    } else {
+        (b, a)
+    };
+    let mut payload = Bytes::new(env);
+    payload.append(&first.clone().into());
+    payload.append(&second.clone().into());
+    env.crypto().sha256(&payload).into()
+}
+
+/// Return `true` if folding `leaf` with each hash in `proof` yields `root`.
+///
+/// Read-only; an empty proof verifies only when `leaf == root`.
+pub fn verify_inclusion(
+    env: &Env,
+    root: &BytesN<32>,
+    leaf: &BytesN<32>,
+    proof: &Vec<BytesN<32>>,
+) -> bool {
+    let mut node = leaf.clone();
+    for sibling in proof.iter() {
+        node = hash_pair(env, &node, &sibling);
+    }
+    node == *root
+}
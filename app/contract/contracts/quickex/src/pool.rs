@@ -0,0 +1,137 @@
+//! Pooled escrows.
+//!
+//! Several contributors fund a single commitment-keyed pool in one token. Each
+//! contribution is tracked per contributor, and once the pool's release time has
+//! passed every contributor withdraws exactly what they put in, less the
+//! withdrawal fee. Pool funds count towards the token's TVL like escrowed funds.
+
+use soroban_sdk::{token, Address, Bytes, BytesN, Env};
+
+use crate::{
+    errors::QuickexError,
+    escrow::{check_token_allowed, trip_if_insolvent},
+    events, fees,
+    storage::{adjust_token_tvl, get_pool, get_pool_share, put_pool, set_pool_share},
+    types::Pool,
+};
+
+/// Open an empty pool under `commitment` holding `token`, releasable at `release_at`.
+///
+/// # Errors
+/// - [`CommitmentAlreadyExists`] – a pool already exists for the commitment.
+/// - [`InvalidSchedule`] – `release_at` is not in the future.
+/// - [`TokenNotAllowed`] – the token allow-list is set and `token` is not on it.
+pub fn open_pool(
+    env: &Env,
+    commitment: BytesN<32>,
+    creator: Address,
+    token: Address,
+    release_at: u64,
+) -> Result<(), QuickexError> {
+    creator.require_auth();
+
+    let key: Bytes = commitment.into();
+    if get_pool(env, &key).is_some() {
+        return Err(QuickexError::CommitmentAlreadyExists);
+    }
+    if release_at <= env.ledger().timestamp() {
+        return Err(QuickexError::InvalidSchedule);
+    }
+    check_token_allowed(env, &token)?;
+
+    let pool = Pool {
+        token,
+        creator,
+        release_at,
+        total: 0,
+    };
+    put_pool(env, &key, &pool);
+    Ok(())
+}
+
+/// Transfer `amount` of `token` from `from` into the pool and add it to their share.
+/// Returns the contributor's new share.
+///
+/// # Errors
+/// - [`InvalidAmount`] – amount ≤ 0.
+/// - [`CommitmentNotFound`] – no pool for the commitment.
+/// - [`TokenMismatch`] – `token` is not the pool's token.
+/// - [`EscrowExpired`] – the pool has already been released.
+pub fn contribute(
+    env: &Env,
+    commitment: BytesN<32>,
+    from: Address,
+    token: Address,
+    amount: i128,
+) -> Result<i128, QuickexError> {
+    if amount <= 0 {
+        return Err(QuickexError::InvalidAmount);
+    }
+
+    from.require_auth();
+
+    let key: Bytes = commitment.clone().into();
+    let mut pool = get_pool(env, &key).ok_or(QuickexError::CommitmentNotFound)?;
+    if pool.token != token {
+        return Err(QuickexError::TokenMismatch);
+    }
+    if env.ledger().timestamp() >= pool.release_at {
+        return Err(QuickexError::EscrowExpired);
+    }
+
+    let share = get_pool_share(env, &key, &from)
+        .checked_add(amount)
+        .ok_or(QuickexError::InternalError)?;
+    pool.total = pool
+        .total
+        .checked_add(amount)
+        .ok_or(QuickexError::InternalError)?;
+
+    token::Client::new(env, &token).transfer(&from, env.current_contract_address(), &amount);
+    set_pool_share(env, &key, &from, share);
+    put_pool(env, &key, &pool);
+    adjust_token_tvl(env, &token, amount);
+
+    events::publish_pool_contribution(env, commitment, from, amount, share);
+    Ok(share)
+}
+
+/// Pay `contributor` their whole share of a released pool, less the withdrawal fee.
+/// Returns the net amount paid, or 0 if the auto-pause circuit breaker tripped.
+///
+/// # Errors
+/// - [`CommitmentNotFound`] – no pool for the commitment.
+/// - [`EscrowNotYetClaimable`] – the pool's release time has not passed.
+/// - [`NoPoolShare`] – `contributor` holds nothing in the pool.
+pub fn withdraw_share(
+    env: &Env,
+    commitment: BytesN<32>,
+    contributor: Address,
+) -> Result<i128, QuickexError> {
+    contributor.require_auth();
+
+    let key: Bytes = commitment.clone().into();
+    let mut pool = get_pool(env, &key).ok_or(QuickexError::CommitmentNotFound)?;
+    if env.ledger().timestamp() < pool.release_at {
+        return Err(QuickexError::EscrowNotYetClaimable);
+    }
+
+    let share = get_pool_share(env, &key, &contributor);
+    if share == 0 {
+        return Err(QuickexError::NoPoolShare);
+    }
+
+    if trip_if_insolvent(env, &pool.token) {
+        return Ok(0);
+    }
+
+    pool.total -= share;
+    set_pool_share(env, &key, &contributor, 0);
+    put_pool(env, &key, &pool);
+    adjust_token_tvl(env, &pool.token, -share);
+
+    let net = fees::pay_out(env, &pool.token, &contributor, share)?;
+
+    events::publish_pool_share_withdrawn(env, commitment, contributor, share);
+    Ok(net)
+}
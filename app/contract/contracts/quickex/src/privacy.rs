@@ -1,13 +1,20 @@
 use crate::errors::QuickexError;
+use crate::escrow::check_batch_size;
 use crate::events::publish_privacy_toggled;
-use crate::storage::PRIVACY_ENABLED_KEY;
-use soroban_sdk::{Address, Env, Symbol};
+use crate::storage::{
+    self, add_privacy_history, add_viewer, get_escrow, get_owner_escrows, get_privacy_history,
+    get_privacy_level, get_viewers, is_privacy_disabled_globally, is_stealth, remove_viewer,
+    set_privacy_level, update_privacy_enabled_count, PRIVACY_ENABLED_KEY,
+};
+use crate::types::{AccountExport, PrivacyAwareEscrowView, PrivacyState};
+use soroban_sdk::{Address, Env, Symbol, Vec};
 
 /// Enable or disable privacy for an account.
 ///
 /// Reads the current state first and returns [`QuickexError::PrivacyAlreadySet`]
 /// if the requested value matches the current value. Otherwise persists the new
-/// state and publishes a [`crate::events::publish_privacy_toggled`] event.
+/// state, updates the global privacy-enabled account count, and publishes a
+/// [`crate::events::publish_privacy_toggled`] event.
 pub fn set_privacy(env: &Env, owner: Address, enabled: bool) -> Result<(), QuickexError> {
     owner.require_auth();
 
@@ -23,6 +30,7 @@ pub fn set_privacy(env: &Env, owner: Address, enabled: bool) -> Result<(), Quick
     }
 
     env.storage().persistent().set(&storage_key, &enabled);
+    update_privacy_enabled_count(env, enabled)?;
 
     let timestamp = env.ledger().timestamp();
     publish_privacy_toggled(env, owner, enabled, timestamp);
@@ -39,3 +47,199 @@ pub fn get_privacy(env: &Env, owner: Address) -> bool {
         .get(&(key, owner))
         .unwrap_or(false)
 }
+
+/// Numeric privacy level the boolean privacy flag corresponds to when redacting
+/// escrow views: `amount` and `owner` hidden.
+pub const BOOLEAN_PRIVACY_LEVEL: u32 = 2;
+
+/// Privacy level at which a stranger sees `owner`'s escrows.
+///
+/// The higher of the owner's numeric level and, if the boolean flag is on,
+/// [`BOOLEAN_PRIVACY_LEVEL`]. `0` when privacy is disabled globally.
+pub fn stranger_privacy_level(env: &Env, owner: &Address) -> u32 {
+    if is_privacy_disabled_globally(env) {
+        return 0;
+    }
+    let level = get_privacy_level(env, owner).unwrap_or(0);
+    if get_privacy(env, owner.clone()) {
+        level.max(BOOLEAN_PRIVACY_LEVEL)
+    } else {
+        level
+    }
+}
+
+/// Privacy level at which `caller` sees `owner`'s escrows: `0` for the owner and
+/// granted viewers, otherwise [`stranger_privacy_level`].
+pub fn view_level(env: &Env, owner: &Address, caller: &Address) -> u32 {
+    if caller == owner || get_viewers(env, owner).contains(caller) {
+        0
+    } else {
+        stranger_privacy_level(env, owner)
+    }
+}
+
+/// Lowest privacy level at which an escrow's amount is hidden from a caller.
+pub const AMOUNT_HIDDEN_LEVEL: u32 = 1;
+
+/// Lowest privacy level at which an escrow's owner is hidden from a caller.
+pub const OWNER_HIDDEN_LEVEL: u32 = 2;
+
+/// Return `true` if `caller`'s privacy level for `owner` reveals escrow amounts.
+///
+/// Does not check stealth mode; combine with [`can_see`].
+pub fn can_view_amount(env: &Env, owner: &Address, caller: &Address) -> bool {
+    view_level(env, owner, caller) < AMOUNT_HIDDEN_LEVEL
+}
+
+/// Return `true` if `caller`'s privacy level for `owner` reveals who owns an escrow.
+///
+/// Does not check stealth mode; combine with [`can_see`].
+pub fn can_view_owner(env: &Env, owner: &Address, caller: &Address) -> bool {
+    view_level(env, owner, caller) < OWNER_HIDDEN_LEVEL
+}
+
+/// Return whether an account's privacy currently takes effect: its stored flag,
+/// unless the admin has disabled privacy globally.
+pub fn is_privacy_active(env: &Env, owner: &Address) -> bool {
+    !is_privacy_disabled_globally(env) && get_privacy(env, owner.clone())
+}
+
+/// Return whether an owner's stealth mode currently takes effect: its stored flag,
+/// unless the admin has disabled privacy globally.
+pub fn is_stealth_active(env: &Env, owner: &Address) -> bool {
+    !is_privacy_disabled_globally(env) && is_stealth(env, owner)
+}
+
+/// Return the boolean privacy flag for an account, distinguishing "never set".
+pub fn get_privacy_state(env: &Env, owner: Address) -> PrivacyState {
+    let key = Symbol::new(env, PRIVACY_ENABLED_KEY);
+    match env.storage().persistent().get(&(key, owner)) {
+        None => PrivacyState::Unset,
+        Some(true) => PrivacyState::Enabled,
+        Some(false) => PrivacyState::Disabled,
+    }
+}
+
+/// Copy `account`'s boolean privacy flag into its numeric privacy level.
+///
+/// Writes [`BOOLEAN_PRIVACY_LEVEL`] if the flag is on and 0 otherwise, records it
+/// in the privacy history, and returns it. The account must authorize. The boolean
+/// flag is left in place because the private withdrawal fee rate still reads it.
+pub fn migrate_privacy_to_level(env: &Env, account: Address) -> u32 {
+    account.require_auth();
+
+    let level = if get_privacy(env, account.clone()) {
+        BOOLEAN_PRIVACY_LEVEL
+    } else {
+        0
+    };
+    set_privacy_level(env, &account, level);
+    add_privacy_history(env, &account, level);
+    level
+}
+
+/// Set privacy for `owner`, then return how each of their active escrows now
+/// appears to a stranger.
+///
+/// Fails exactly as [`set_privacy`] does; nothing is returned in that case.
+pub fn set_privacy_and_preview(
+    env: &Env,
+    owner: Address,
+    enabled: bool,
+) -> Result<Vec<PrivacyAwareEscrowView>, QuickexError> {
+    set_privacy(env, owner.clone(), enabled)?;
+
+    let level = stranger_privacy_level(env, &owner);
+    let mut views = Vec::new(env);
+    for commitment in get_owner_escrows(env, &owner).iter() {
+        if let Some(entry) = get_escrow(env, &commitment.into()) {
+            views.push_back(PrivacyAwareEscrowView::from_entry(entry, level));
+        }
+    }
+    Ok(views)
+}
+
+/// Grant `viewer` permission to see `owner`'s private escrow details.
+///
+/// The owner must authorize. Granting an existing viewer again is a no-op.
+pub fn grant_viewer(env: &Env, owner: Address, viewer: Address) -> Result<(), QuickexError> {
+    owner.require_auth();
+    add_viewer(env, &owner, &viewer)
+}
+
+/// Revoke a previously granted viewer. The owner must authorize.
+pub fn revoke_viewer(env: &Env, owner: Address, viewer: Address) {
+    owner.require_auth();
+    remove_viewer(env, &owner, &viewer);
+}
+
+/// Grant several viewers under a single owner authorization.
+///
+/// Viewers already granted, or repeated within `viewers`, are skipped.
+///
+/// # Errors
+/// - [`QuickexError::BatchTooLarge`] – more than [`max_batch_size`](crate::escrow::max_batch_size) viewers.
+/// - [`QuickexError::TooManyViewers`] – the grants would exceed the per-owner cap.
+pub fn grant_viewers(env: &Env, owner: Address, viewers: Vec<Address>) -> Result<(), QuickexError> {
+    check_batch_size(env, viewers.len())?;
+    owner.require_auth();
+    for viewer in viewers.iter() {
+        add_viewer(env, &owner, &viewer)?;
+    }
+    Ok(())
+}
+
+/// Revoke several viewers under a single owner authorization.
+///
+/// Viewers without a grant are skipped.
+///
+/// # Errors
+/// - [`QuickexError::BatchTooLarge`] – more than [`max_batch_size`](crate::escrow::max_batch_size) viewers.
+pub fn revoke_viewers(
+    env: &Env,
+    owner: Address,
+    viewers: Vec<Address>,
+) -> Result<(), QuickexError> {
+    check_batch_size(env, viewers.len())?;
+    owner.require_auth();
+    for viewer in viewers.iter() {
+        remove_viewer(env, &owner, &viewer);
+    }
+    Ok(())
+}
+
+/// Enable or disable stealth mode for an owner. The owner must authorize.
+pub fn set_stealth(env: &Env, owner: Address, enabled: bool) {
+    owner.require_auth();
+    storage::set_stealth(env, &owner, enabled);
+}
+
+/// Return `true` if `caller` may learn that `owner`'s escrows exist at all.
+///
+/// Only owners in stealth mode hide their escrows; the owner and granted viewers
+/// always see them.
+pub fn can_see(env: &Env, owner: &Address, caller: &Address) -> bool {
+    caller == owner || !is_stealth_active(env, owner) || get_viewers(env, owner).contains(caller)
+}
+
+/// Return `true` if a caller with no grant from `owner` sees less than the full
+/// details of `owner`'s escrows, either redacted or hidden by stealth mode.
+pub fn is_hidden_from_strangers(env: &Env, owner: &Address) -> bool {
+    stranger_privacy_level(env, owner) > 0 || is_stealth_active(env, owner)
+}
+
+/// Bundle `owner`'s privacy settings and active escrow commitments.
+///
+/// The owner must authorize, so no one else can export their state.
+pub fn export_account_state(env: &Env, owner: Address) -> AccountExport {
+    owner.require_auth();
+
+    AccountExport {
+        privacy_enabled: get_privacy(env, owner.clone()),
+        privacy_level: get_privacy_level(env, &owner),
+        privacy_history: get_privacy_history(env, &owner),
+        viewers: get_viewers(env, &owner),
+        active_escrows: get_owner_escrows(env, &owner),
+        owner,
+    }
+}
@@ -8,12 +8,51 @@
 //!
 //! | Key Variant            | Value Type     | Description |
 //! |------------------------|----------------|-------------|
-//! | [`Escrow`](DataKey::Escrow) | `EscrowEntry`  | Escrow entry keyed by commitment hash (32 bytes). One entry per unique deposit. |
+//! | [`Escrow`](DataKey::Escrow) | `StoredEscrow`  | Core escrow fields keyed by commitment hash (32 bytes). One entry per unique deposit. |
+//! | [`EscrowExtra`](DataKey::EscrowExtra) | `EscrowExtra` | Options and bookkeeping of an escrow, keyed by commitment; absent for escrows stored before it existed. |
 //! | [`EscrowCounter`](DataKey::EscrowCounter) | `u64`       | Global monotonic counter for escrow creation. |
+//! | [`PlaceholderIdCount`](DataKey::PlaceholderIdCount) | `u64` | Number of escrow IDs handed out by `create_escrow`, which stores no escrow. |
+//! | [`EscrowIdToCommitment`](DataKey::EscrowIdToCommitment) | `BytesN<32>` | Commitment of the escrow assigned a given ID. |
+//! | [`OwnerNonce`](DataKey::OwnerNonce) | `u64` | Next deposit nonce per owner, used by `deposit_nonced`. |
+//! | [`EscrowLiveUntil`](DataKey::EscrowLiveUntil) | `u32` | Ledger through which the escrow entry was last extended by this contract. |
+//! | [`OwnerEscrows`](DataKey::OwnerEscrows) | `Vec<BytesN<32>>` | Commitments of an owner's non-terminal escrows, in creation order. |
+//! | [`StatusCounts`](DataKey::StatusCounts) | `StatusCounts` | Contract-wide number of escrows per status. |
+//! | [`FeeConfig`](DataKey::FeeConfig) | `FeeConfig` | Withdrawal fee rates, including the privacy discount rate. |
+//! | [`PrivacyHistoryDedup`](DataKey::PrivacyHistoryDedup) | `bool` | When true, `PrivacyHistory` skips a level equal to its newest entry. |
+//! | [`TokenTvl`](DataKey::TokenTvl) | `i128` | Total amount of a token held in non-terminal escrows. |
+//! | [`AutoPause`](DataKey::AutoPause) | `bool` | When true, payouts pause the contract if a token's TVL exceeds its balance. |
+//! | [`GatedOperations`](DataKey::GatedOperations) | `Vec<Symbol>` | Operations restricted to the admin. |
+//! | [`WithdrawNonce`](DataKey::WithdrawNonce) | `u64` | Next nonce a recipient's `withdraw_signed` must carry. |
+//! | [`FeesCollected`](DataKey::FeesCollected) | `i128` | Cumulative withdrawal fees taken per token. |
+//! | [`KnownTokens`](DataKey::KnownTokens) | `Vec<Address>` | Every token that has ever been escrowed, in first-use order. |
+//! | [`SaltTracking`](DataKey::SaltTracking) | `bool` | When true, an owner's `deposit` salts are fingerprinted. |
+//! | [`Stealth`](DataKey::Stealth) | `bool` | When true, an owner's escrows are hidden entirely from strangers in `get_escrow_details`. |
+//! | [`UsedSalt`](DataKey::UsedSalt) | `bool` | Set for `(owner, SHA256(salt))` once a tracked owner deposits with that salt. |
+//! | [`TerminalRedeposit`](DataKey::TerminalRedeposit) | `bool` | When true, `deposit_with_commitment` may overwrite a `Spent` or `Refunded` escrow. |
+//! | [`PrivacyDisabledGlobally`](DataKey::PrivacyDisabledGlobally) | `bool` | When true, privacy and stealth settings are ignored for disclosure. |
+//! | [`Pool`](DataKey::Pool) | `Pool` | Pooled escrow keyed by commitment: token, creator, release time, and total held. |
+//! | [`PoolShare`](DataKey::PoolShare) | `i128` | Amount a contributor holds in a pool, keyed by `(commitment, contributor)`. |
+//! | [`MaxBatchSize`](DataKey::MaxBatchSize) | `u32` | Admin-configured cap on batch inputs and page sizes; unset means the built-in default. |
+//! | [`ActivitySnapshots`](DataKey::ActivitySnapshots) | `Vec<ActivitySnapshot>` | Most recent activity snapshots, newest first, capped at [`MAX_ACTIVITY_SNAPSHOTS`]. |
+//! | [`SnapshotInterval`](DataKey::SnapshotInterval) | `u64` | Minimum seconds between activity snapshots; unset means [`DEFAULT_SNAPSHOT_INTERVAL`]. |
+//! | [`SweepGracePeriod`](DataKey::SweepGracePeriod) | `u64` | Seconds past expiry before the admin may sweep an escrow; unset means [`DEFAULT_SWEEP_GRACE_PERIOD`]. |
+//! | [`StuckAge`](DataKey::StuckAge) | `u64` | Age after which a non-expiring pending escrow is reported as stuck; unset means [`DEFAULT_STUCK_AGE`]. |
+//! | [`OwnerEscrowsCreated`](DataKey::OwnerEscrowsCreated) | `u64` | Number of escrows ever created for an owner. |
+//! | [`RefundAllowList`](DataKey::RefundAllowList) | `Vec<Address>` | Addresses refunds may be paid to; empty means unrestricted. |
+//! | [`TokenAllowed`](DataKey::TokenAllowed) | `bool` | Set for each token on the deposit allow-list. |
+//! | [`AllowedTokenCount`](DataKey::AllowedTokenCount) | `u32` | Number of tokens on the deposit allow-list; 0 means every token is accepted. |
+//! | [`LastUpgrade`](DataKey::LastUpgrade) | `UpgradeInfo` | WASM hash, timestamp and admin of the most recent `upgrade`. |
 //! | [`Admin`](DataKey::Admin) | `Address`     | Contract admin address. Set during initialisation, transferable by admin. |
+//! | [`PendingAdmin`](DataKey::PendingAdmin) | `Address` | Address nominated as the next admin; cleared once it accepts. |
 //! | [`Paused`](DataKey::Paused) | `bool`       | Global pause flag. When true, critical operations may be blocked. |
+//! | [`InitializedAt`](DataKey::InitializedAt) | `u64` | Ledger timestamp at which `initialize` was called. |
+//! | [`PrivacyEnabledCount`](DataKey::PrivacyEnabledCount) | `u64` | Number of accounts with boolean privacy enabled. |
+//! | [`Viewers`](DataKey::Viewers) | `Vec<Address>` | Accounts an owner has granted viewing access to. |
+//! | [`ViewerOf`](DataKey::ViewerOf) | `Vec<Address>` | Reverse index: owners that granted viewing access to an account. |
 //! | [`PrivacyLevel`](DataKey::PrivacyLevel) | `u32`  | Numeric privacy level per account (0 = off). Used by `enable_privacy`. |
-//! | [`PrivacyHistory`](DataKey::PrivacyHistory) | `Vec<u32>` | Per-account history of privacy level changes (chronological). |
+//! | [`PrivacyHistory`](DataKey::PrivacyHistory) | `Vec<u32>` | Per-account history of privacy level changes, newest first, capped at [`MAX_PRIVACY_HISTORY`]. |
+//! | [`TokenEscrowCount`](DataKey::TokenEscrowCount) | `u64` | Number of non-terminal escrows per token. |
+//! | [`StrictOwnerCheck`](DataKey::StrictOwnerCheck) | `bool` | When true, `withdraw` also requires `entry.owner == to`. |
 //!
 //! ## Related Keys (outside `DataKey`)
 //!
@@ -24,23 +63,31 @@
 //! ## Relations
 //!
 //! - **Escrow ↔ Commitment**: Each `Escrow(Bytes)` key is derived from a 32-byte commitment hash
-//!   (`SHA256(owner || amount || salt)`). The stored [`EscrowEntry`] contains token, amount, owner,
-//!   status, and created_at.
+//!   (`SHA256(owner || amount || salt)`). The stored [`StoredEscrow`] contains token, amount, owner,
+//!   status, created_at and expires_at; everything else about the escrow is in its `EscrowExtra`.
 //! - **Admin ↔ Paused**: Admin can set the paused flag. Both are singleton keys.
-//! - **PrivacyLevel ↔ PrivacyHistory**: Same account may have both; level is current, history is append-only.
+//! - **PrivacyLevel ↔ PrivacyHistory**: Same account may have both; level is current, history keeps the most recent changes.
 //! - **PrivacyLevel / PrivacyHistory ↔ privacy_enabled**: Separate APIs; level-based vs boolean. Both persist per `Address`.
+//! - **Viewers ↔ ViewerOf**: Kept in sync; `viewer ∈ Viewers(owner)` iff `owner ∈ ViewerOf(viewer)`.
 //!
 //! ## Backwards Compatibility
 //!
 //! For future upgrades:
 //! - **Do not** remove or change the discriminant of existing [`DataKey`] variants.
 //! - **Add** new variants for new keys; they will not collide with existing ones.
-//! - **Value layout**: Changing `EscrowEntry` fields may require migration logic; adding optional
-//!   fields can be done carefully with defaults.
+//! - **Value layout**: Never change the fields of a stored struct such as [`StoredEscrow`];
+//!   Soroban cannot decode an entry whose fields differ, so existing entries would trap on
+//!   read. Store new data under a new key read with a default, as [`EscrowExtra`] does.
 
-use soroban_sdk::{contracttype, Address, Bytes, Env, Vec};
+use soroban_sdk::{contracttype, Address, Bytes, BytesN, Env, Symbol, Vec};
 
-use crate::types::EscrowEntry;
+use crate::{
+    errors::QuickexError,
+    types::{
+        ActivitySnapshot, EscrowEntry, EscrowExtra, EscrowStatus, FeeConfig, Pool, StatusCounts,
+        StoredEscrow, UpgradeInfo,
+    },
+};
 
 // -----------------------------------------------------------------------------
 // Key constants (for keys not using DataKey)
@@ -75,31 +122,183 @@ pub enum DataKey {
     PrivacyLevel(Address),
     /// Privacy level change history per account.
     PrivacyHistory(Address),
+    /// Count of non-terminal escrows per token.
+    TokenEscrowCount(Address),
+    /// Strict owner check flag (singleton).
+    StrictOwnerCheck,
+    /// Initialization timestamp (singleton).
+    InitializedAt,
+    /// Number of accounts with boolean privacy enabled (singleton).
+    PrivacyEnabledCount,
+    /// Viewers granted access by an owner.
+    Viewers(Address),
+    /// Owners that granted access to a viewer.
+    ViewerOf(Address),
+    /// Escrow ID → commitment lookup.
+    EscrowIdToCommitment(u64),
+    /// Next deposit nonce per owner.
+    OwnerNonce(Address),
+    /// Last recorded `live_until` ledger of an escrow entry, keyed by commitment.
+    EscrowLiveUntil(Bytes),
+    /// Non-terminal escrow commitments per owner.
+    OwnerEscrows(Address),
+    /// Escrow counts per status (singleton).
+    StatusCounts,
+    /// Withdrawal fee configuration (singleton).
+    FeeConfig,
+    /// Privacy history dedup flag (singleton).
+    PrivacyHistoryDedup,
+    /// Total value locked in non-terminal escrows per token.
+    TokenTvl(Address),
+    /// Auto-pause circuit breaker flag (singleton).
+    AutoPause,
+    /// Operations restricted to the admin (singleton).
+    GatedOperations,
+    /// Next signed-withdrawal nonce per recipient.
+    WithdrawNonce(Address),
+    /// Cumulative fees collected per token.
+    FeesCollected(Address),
+    /// Registry of tokens that have been escrowed (singleton).
+    KnownTokens,
+    /// Salt fingerprint tracking opt-in per owner.
+    SaltTracking(Address),
+    /// Salt fingerprint an owner has deposited with.
+    UsedSalt(Address, BytesN<32>),
+    /// Stealth mode flag per owner.
+    Stealth(Address),
+    /// Most recent upgrade record (singleton).
+    LastUpgrade,
+    /// Re-deposit onto terminal commitments flag (singleton).
+    TerminalRedeposit,
+    /// Global privacy kill-switch (singleton).
+    PrivacyDisabledGlobally,
+    /// Pooled escrow keyed by commitment.
+    Pool(Bytes),
+    /// Contributor's balance in a pooled escrow.
+    PoolShare(Bytes, Address),
+    /// Maximum batch size (singleton).
+    MaxBatchSize,
+    /// Ring buffer of activity snapshots (singleton).
+    ActivitySnapshots,
+    /// Minimum interval between activity snapshots (singleton).
+    SnapshotInterval,
+    /// Grace period before expired escrows may be swept (singleton).
+    SweepGracePeriod,
+    /// Age at which non-expiring escrows are reported as stuck (singleton).
+    StuckAge,
+    /// Number of escrows ever created per owner.
+    OwnerEscrowsCreated(Address),
+    /// Addresses refunds may be paid to (singleton).
+    RefundAllowList,
+    /// Whether a token is on the deposit allow-list.
+    TokenAllowed(Address),
+    /// Number of tokens on the deposit allow-list (singleton).
+    AllowedTokenCount,
+    /// Address nominated to become admin (singleton).
+    PendingAdmin,
+    /// Number of escrow IDs not backed by an escrow (singleton).
+    PlaceholderIdCount,
+    /// Escrow options and bookkeeping keyed by commitment hash.
+    EscrowExtra(Bytes),
 }
 
+/// Number of ledgers an escrow entry is kept alive for on every write (~30 days
+/// at 5s per ledger). Clamped to the network's maximum entry TTL.
+pub const ESCROW_TTL_LEDGERS: u32 = 518_400;
+
 // -----------------------------------------------------------------------------
 // Escrow helpers
 // -----------------------------------------------------------------------------
 
 /// Put an escrow entry into storage.
 ///
-/// **Contract**: Overwrites any existing entry for the same commitment.
+/// **Contract**: Overwrites any existing entry for the same commitment and
+/// extends its TTL via [`extend_escrow_ttl`]. The entry is split into its
+/// [`StoredEscrow`] and [`EscrowExtra`] halves, each under its own key.
 /// The commitment should be the 32-byte `SHA256(owner || amount || salt)` hash.
 pub fn put_escrow(env: &Env, commitment: &Bytes, entry: &EscrowEntry) {
-    let key = DataKey::Escrow(commitment.clone());
-    env.storage().persistent().set(&key, entry);
+    let (stored, extra) = entry.clone().into_parts();
+    let persistent = env.storage().persistent();
+    persistent.set(&DataKey::Escrow(commitment.clone()), &stored);
+    persistent.set(&DataKey::EscrowExtra(commitment.clone()), &extra);
+    extend_escrow_ttl(env, commitment);
+}
+
+/// Extend an escrow entry's TTL to [`ESCROW_TTL_LEDGERS`] and record the resulting
+/// `live_until` ledger.
+///
+/// **Contract**: The caller must ensure the escrow exists.
+pub fn extend_escrow_ttl(env: &Env, commitment: &Bytes) {
+    extend_escrow_ttl_to(env, commitment, ESCROW_TTL_LEDGERS);
+}
+
+/// Extend an escrow entry's TTL to at least `ledgers`, clamped to the network
+/// maximum, and record the resulting `live_until` ledger.
+///
+/// **Contract**: The caller must ensure the escrow exists. Never shortens a TTL,
+/// and the recorded `live_until` never moves backwards.
+pub fn extend_escrow_ttl_to(env: &Env, commitment: &Bytes, ledgers: u32) {
+    let extend_to = ledgers.min(env.storage().max_ttl());
+
+    let persistent = env.storage().persistent();
+    let escrow_key = DataKey::Escrow(commitment.clone());
+    let extra_key = DataKey::EscrowExtra(commitment.clone());
+    let live_until_key = DataKey::EscrowLiveUntil(commitment.clone());
+    let recorded: u32 = persistent.get(&live_until_key).unwrap_or(0);
+    let live_until = env
+        .ledger()
+        .sequence()
+        .saturating_add(extend_to)
+        .max(recorded);
+    persistent.extend_ttl(&escrow_key, extend_to, extend_to);
+    if persistent.has(&extra_key) {
+        persistent.extend_ttl(&extra_key, extend_to, extend_to);
+    }
+    persistent.set(&live_until_key, &live_until);
+    persistent.extend_ttl(&live_until_key, extend_to, extend_to);
+}
+
+/// Get the number of ledgers before an escrow entry becomes eligible for archival.
+///
+/// **Contract**: Returns `None` if no escrow exists. The value is derived from the
+/// last extension this contract recorded, so it is a lower bound: TTL bumps made
+/// outside the contract are not reflected. Entries with no recorded extension
+/// report 0.
+pub fn get_escrow_ttl_remaining(env: &Env, commitment: &Bytes) -> Option<u32> {
+    if !has_escrow(env, commitment) {
+        return None;
+    }
+    let key = DataKey::EscrowLiveUntil(commitment.clone());
+    let live_until: u32 = env.storage().persistent().get(&key).unwrap_or(0);
+    Some(live_until.saturating_sub(env.ledger().sequence()))
 }
 
 /// Get an escrow entry from storage.
 ///
-/// **Contract**: Returns `None` if no escrow exists for the commitment.
+/// **Contract**: Returns `None` if no escrow exists for the commitment. An escrow
+/// with no [`EscrowExtra`] stored, i.e. one written before that key existed, reads
+/// with the default extras.
 pub fn get_escrow(env: &Env, commitment: &Bytes) -> Option<EscrowEntry> {
-    let key = DataKey::Escrow(commitment.clone());
-    env.storage().persistent().get(&key)
+    let persistent = env.storage().persistent();
+    let stored: StoredEscrow = persistent.get(&DataKey::Escrow(commitment.clone()))?;
+    let extra: EscrowExtra = persistent
+        .get(&DataKey::EscrowExtra(commitment.clone()))
+        .unwrap_or_default();
+    Some(EscrowEntry::from_parts(stored, extra))
+}
+
+/// Remove an escrow entry and its recorded TTL from storage.
+///
+/// **Contract**: Leaves all counters and indexes untouched; callers must only
+/// remove escrows that have already been removed from them.
+pub fn remove_escrow(env: &Env, commitment: &Bytes) {
+    let persistent = env.storage().persistent();
+    persistent.remove(&DataKey::Escrow(commitment.clone()));
+    persistent.remove(&DataKey::EscrowExtra(commitment.clone()));
+    persistent.remove(&DataKey::EscrowLiveUntil(commitment.clone()));
 }
 
 /// Check if an escrow entry exists in storage.
-#[allow(dead_code)]
 pub fn has_escrow(env: &Env, commitment: &Bytes) -> bool {
     let key = DataKey::Escrow(commitment.clone());
     env.storage().persistent().has(&key)
@@ -107,8 +306,7 @@ pub fn has_escrow(env: &Env, commitment: &Bytes) -> bool {
 
 /// Get the next escrow counter value.
 ///
-/// **Contract**: Returns 0 if never set. Counter is used for `create_escrow`.
-#[allow(dead_code)]
+/// **Contract**: Returns 0 if never set. Counter is shared by deposits and `create_escrow`.
 pub fn get_escrow_counter(env: &Env) -> u64 {
     let key = DataKey::EscrowCounter;
     env.storage().persistent().get(&key).unwrap_or(0)
@@ -125,6 +323,252 @@ pub fn increment_escrow_counter(env: &Env) -> u64 {
     count
 }
 
+/// Get the number of escrow IDs handed out by `create_escrow`.
+///
+/// **Contract**: Returns 0 if never set.
+pub fn get_placeholder_id_count(env: &Env) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::PlaceholderIdCount)
+        .unwrap_or(0)
+}
+
+/// Record one escrow ID handed out by `create_escrow`.
+pub fn increment_placeholder_id_count(env: &Env) {
+    let count = get_placeholder_id_count(env).saturating_add(1);
+    env.storage()
+        .persistent()
+        .set(&DataKey::PlaceholderIdCount, &count);
+}
+
+/// Map an escrow ID to the commitment of the escrow it was assigned to.
+pub fn set_escrow_commitment_by_id(env: &Env, escrow_id: u64, commitment: &BytesN<32>) {
+    let key = DataKey::EscrowIdToCommitment(escrow_id);
+    env.storage().persistent().set(&key, commitment);
+}
+
+/// Remove the mapping from an escrow ID to its commitment.
+pub fn remove_escrow_commitment_by_id(env: &Env, escrow_id: u64) {
+    env.storage()
+        .persistent()
+        .remove(&DataKey::EscrowIdToCommitment(escrow_id));
+}
+
+/// Get the commitment for an escrow ID.
+///
+/// **Contract**: Returns `None` for unknown IDs, including IDs handed out by
+/// `create_escrow`, which does not create a real escrow.
+pub fn get_escrow_commitment_by_id(env: &Env, escrow_id: u64) -> Option<BytesN<32>> {
+    let key = DataKey::EscrowIdToCommitment(escrow_id);
+    env.storage().persistent().get(&key)
+}
+
+/// Get the commitments of an owner's non-terminal escrows, in creation order.
+///
+/// **Contract**: Returns empty vec if none.
+pub fn get_owner_escrows(env: &Env, owner: &Address) -> Vec<BytesN<32>> {
+    let key = DataKey::OwnerEscrows(owner.clone());
+    env.storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or(Vec::new(env))
+}
+
+/// Append a commitment to the owner's escrow index and count it as created.
+pub fn add_owner_escrow(env: &Env, owner: &Address, commitment: &BytesN<32>) {
+    let mut commitments = get_owner_escrows(env, owner);
+    commitments.push_back(commitment.clone());
+    env.storage()
+        .persistent()
+        .set(&DataKey::OwnerEscrows(owner.clone()), &commitments);
+
+    let created = get_owner_escrows_created(env, owner).saturating_add(1);
+    env.storage()
+        .persistent()
+        .set(&DataKey::OwnerEscrowsCreated(owner.clone()), &created);
+}
+
+/// Get the number of escrows ever created for an owner.
+///
+/// **Contract**: Returns 0 if none.
+pub fn get_owner_escrows_created(env: &Env, owner: &Address) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::OwnerEscrowsCreated(owner.clone()))
+        .unwrap_or(0)
+}
+
+/// Remove a commitment from the owner's escrow index.
+///
+/// **Contract**: No-op if the commitment is not indexed.
+pub fn remove_owner_escrow(env: &Env, owner: &Address, commitment: &BytesN<32>) {
+    let mut commitments = get_owner_escrows(env, owner);
+    if let Some(index) = commitments.first_index_of(commitment) {
+        commitments.remove(index);
+        env.storage()
+            .persistent()
+            .set(&DataKey::OwnerEscrows(owner.clone()), &commitments);
+    }
+}
+
+/// Get the contract-wide escrow counts per status.
+///
+/// **Contract**: Returns all zeros if never set.
+pub fn get_status_counts(env: &Env) -> StatusCounts {
+    env.storage()
+        .persistent()
+        .get(&DataKey::StatusCounts)
+        .unwrap_or_default()
+}
+
+/// Record an escrow moving from `from` (or newly created, if `None`) to `to`.
+pub fn update_status_counts(env: &Env, from: Option<EscrowStatus>, to: EscrowStatus) {
+    let mut counts = get_status_counts(env);
+    counts.transition(from, to);
+    env.storage()
+        .persistent()
+        .set(&DataKey::StatusCounts, &counts);
+}
+
+/// Get the nonce the owner's next nonced deposit will use.
+///
+/// **Contract**: Returns 0 if never set.
+pub fn get_owner_nonce(env: &Env, owner: &Address) -> u64 {
+    let key = DataKey::OwnerNonce(owner.clone());
+    env.storage().persistent().get(&key).unwrap_or(0)
+}
+
+/// Consume and return the owner's current nonce, advancing it by one.
+///
+/// **Contract**: Fails with `InternalError` on overflow.
+pub fn take_owner_nonce(env: &Env, owner: &Address) -> Result<u64, QuickexError> {
+    let key = DataKey::OwnerNonce(owner.clone());
+    let nonce: u64 = env.storage().persistent().get(&key).unwrap_or(0);
+    let next = nonce.checked_add(1).ok_or(QuickexError::InternalError)?;
+    env.storage().persistent().set(&key, &next);
+    Ok(nonce)
+}
+
+/// Check whether `owner` has opted in to salt fingerprint tracking.
+///
+/// **Contract**: Returns `false` if never set.
+pub fn is_salt_tracking(env: &Env, owner: &Address) -> bool {
+    let key = DataKey::SaltTracking(owner.clone());
+    env.storage().persistent().get(&key).unwrap_or(false)
+}
+
+/// Opt `owner` in to or out of salt fingerprint tracking.
+///
+/// **Contract**: Fingerprints already recorded are kept when opting out.
+pub fn set_salt_tracking(env: &Env, owner: &Address, enabled: bool) {
+    let key = DataKey::SaltTracking(owner.clone());
+    env.storage().persistent().set(&key, &enabled);
+}
+
+/// Check whether `owner` has deposited with the salt whose SHA-256 is `fingerprint`.
+pub fn is_salt_used(env: &Env, owner: &Address, fingerprint: &BytesN<32>) -> bool {
+    let key = DataKey::UsedSalt(owner.clone(), fingerprint.clone());
+    env.storage().persistent().has(&key)
+}
+
+/// Record that `owner` has deposited with the salt whose SHA-256 is `fingerprint`.
+pub fn mark_salt_used(env: &Env, owner: &Address, fingerprint: &BytesN<32>) {
+    let key = DataKey::UsedSalt(owner.clone(), fingerprint.clone());
+    env.storage().persistent().set(&key, &true);
+}
+
+/// Get the nonce the recipient's next signed withdrawal must carry.
+///
+/// **Contract**: Returns 0 if never set.
+pub fn get_withdraw_nonce(env: &Env, who: &Address) -> u64 {
+    let key = DataKey::WithdrawNonce(who.clone());
+    env.storage().persistent().get(&key).unwrap_or(0)
+}
+
+/// Consume the recipient's signed-withdrawal nonce if it equals `nonce`.
+///
+/// **Contract**: Fails with `StaleNonce` on any other value and with
+/// `InternalError` on overflow.
+pub fn take_withdraw_nonce(env: &Env, who: &Address, nonce: u64) -> Result<(), QuickexError> {
+    let key = DataKey::WithdrawNonce(who.clone());
+    let current: u64 = env.storage().persistent().get(&key).unwrap_or(0);
+    if nonce != current {
+        return Err(QuickexError::StaleNonce);
+    }
+    let next = current.checked_add(1).ok_or(QuickexError::InternalError)?;
+    env.storage().persistent().set(&key, &next);
+    Ok(())
+}
+
+/// Get the number of non-terminal escrows for a token.
+///
+/// **Contract**: Returns 0 if never set.
+pub fn get_token_escrow_count(env: &Env, token: &Address) -> u64 {
+    let key = DataKey::TokenEscrowCount(token.clone());
+    env.storage().persistent().get(&key).unwrap_or(0)
+}
+
+/// Increment the escrow count for a token.
+///
+/// **Contract**: Fails with [`QuickexError::InternalError`] on overflow. The first
+/// increment for a token also adds it to [`DataKey::KnownTokens`].
+pub fn increment_token_escrow_count(env: &Env, token: &Address) -> Result<(), QuickexError> {
+    let key = DataKey::TokenEscrowCount(token.clone());
+    let Some(count) = env.storage().persistent().get::<_, u64>(&key) else {
+        let mut tokens = get_known_tokens(env);
+        tokens.push_back(token.clone());
+        env.storage()
+            .persistent()
+            .set(&DataKey::KnownTokens, &tokens);
+        env.storage().persistent().set(&key, &1u64);
+        return Ok(());
+    };
+    let count = count.checked_add(1).ok_or(QuickexError::InternalError)?;
+    env.storage().persistent().set(&key, &count);
+    Ok(())
+}
+
+/// Decrement the escrow count for a token.
+///
+/// **Contract**: Fails with [`QuickexError::InternalError`] on underflow.
+pub fn decrement_token_escrow_count(env: &Env, token: &Address) -> Result<(), QuickexError> {
+    let key = DataKey::TokenEscrowCount(token.clone());
+    let count: u64 = env.storage().persistent().get(&key).unwrap_or(0);
+    let count = count.checked_sub(1).ok_or(QuickexError::InternalError)?;
+    env.storage().persistent().set(&key, &count);
+    Ok(())
+}
+
+/// Get every token that has been escrowed, in first-use order.
+///
+/// **Contract**: Returns an empty vector if none. Tokens escrowed before the
+/// registry existed are only added on their next deposit.
+pub fn get_known_tokens(env: &Env) -> Vec<Address> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::KnownTokens)
+        .unwrap_or(Vec::new(env))
+}
+
+/// Get the total amount of a token held in non-terminal escrows.
+///
+/// **Contract**: Returns 0 if never set.
+pub fn get_token_tvl(env: &Env, token: &Address) -> i128 {
+    let key = DataKey::TokenTvl(token.clone());
+    env.storage().persistent().get(&key).unwrap_or(0)
+}
+
+/// Adjust the tracked TVL of a token by `delta`.
+///
+/// **Contract**: Saturates at 0, so escrows created before TVL tracking existed
+/// can still be paid out.
+pub fn adjust_token_tvl(env: &Env, token: &Address, delta: i128) {
+    let key = DataKey::TokenTvl(token.clone());
+    let tvl: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+    let tvl = tvl.saturating_add(delta).max(0);
+    env.storage().persistent().set(&key, &tvl);
+}
+
 // -----------------------------------------------------------------------------
 // Admin helpers
 // -----------------------------------------------------------------------------
@@ -141,6 +585,137 @@ pub fn get_admin(env: &Env) -> Option<Address> {
     env.storage().persistent().get(&key)
 }
 
+/// Set the address nominated to become admin.
+pub fn set_pending_admin(env: &Env, nominee: &Address) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::PendingAdmin, nominee);
+}
+
+/// Get the address nominated to become admin, if any.
+pub fn get_pending_admin(env: &Env) -> Option<Address> {
+    env.storage().persistent().get(&DataKey::PendingAdmin)
+}
+
+/// Clear the pending admin nomination.
+pub fn clear_pending_admin(env: &Env) {
+    env.storage().persistent().remove(&DataKey::PendingAdmin);
+}
+
+/// Set the initialization timestamp.
+pub fn set_initialized_at(env: &Env, timestamp: u64) {
+    let key = DataKey::InitializedAt;
+    env.storage().persistent().set(&key, &timestamp);
+}
+
+/// Get the initialization timestamp.
+///
+/// **Contract**: Returns `None` before `initialize` has been called.
+pub fn get_initialized_at(env: &Env) -> Option<u64> {
+    let key = DataKey::InitializedAt;
+    env.storage().persistent().get(&key)
+}
+
+/// Record the most recent contract upgrade.
+pub fn set_last_upgrade(env: &Env, info: &UpgradeInfo) {
+    let key = DataKey::LastUpgrade;
+    env.storage().persistent().set(&key, info);
+}
+
+/// Get the most recent contract upgrade.
+///
+/// **Contract**: Returns `None` if the contract has never been upgraded.
+pub fn get_last_upgrade(env: &Env) -> Option<UpgradeInfo> {
+    let key = DataKey::LastUpgrade;
+    env.storage().persistent().get(&key)
+}
+
+/// Maximum number of activity snapshots kept.
+pub const MAX_ACTIVITY_SNAPSHOTS: u32 = 30;
+
+/// Snapshot interval used until the admin configures one: one day.
+pub const DEFAULT_SNAPSHOT_INTERVAL: u64 = 24 * 60 * 60;
+
+/// Get the recorded activity snapshots, newest first.
+///
+/// **Contract**: Returns empty vec if none.
+pub fn get_activity_snapshots(env: &Env) -> Vec<ActivitySnapshot> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::ActivitySnapshots)
+        .unwrap_or(Vec::new(env))
+}
+
+/// Record an activity snapshot as the newest entry.
+///
+/// **Contract**: Once more than [`MAX_ACTIVITY_SNAPSHOTS`] are held the oldest is
+/// dropped.
+pub fn add_activity_snapshot(env: &Env, snapshot: &ActivitySnapshot) {
+    let mut snapshots = get_activity_snapshots(env);
+    snapshots.push_front(snapshot.clone());
+    if snapshots.len() > MAX_ACTIVITY_SNAPSHOTS {
+        snapshots.pop_back();
+    }
+    env.storage()
+        .persistent()
+        .set(&DataKey::ActivitySnapshots, &snapshots);
+}
+
+/// Set the minimum interval between activity snapshots.
+pub fn set_snapshot_interval(env: &Env, secs: u64) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::SnapshotInterval, &secs);
+}
+
+/// Get the minimum interval between activity snapshots.
+///
+/// **Contract**: Defaults to [`DEFAULT_SNAPSHOT_INTERVAL`].
+pub fn get_snapshot_interval(env: &Env) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::SnapshotInterval)
+        .unwrap_or(DEFAULT_SNAPSHOT_INTERVAL)
+}
+
+/// Sweep grace period used until the admin configures one: 90 days.
+pub const DEFAULT_SWEEP_GRACE_PERIOD: u64 = 90 * 24 * 60 * 60;
+
+/// Set the grace period before expired escrows may be swept.
+pub fn set_sweep_grace_period(env: &Env, secs: u64) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::SweepGracePeriod, &secs);
+}
+
+/// Get the grace period before expired escrows may be swept.
+///
+/// **Contract**: Defaults to [`DEFAULT_SWEEP_GRACE_PERIOD`].
+pub fn get_sweep_grace_period(env: &Env) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::SweepGracePeriod)
+        .unwrap_or(DEFAULT_SWEEP_GRACE_PERIOD)
+}
+
+/// Stuck age used until the admin configures one: 365 days.
+pub const DEFAULT_STUCK_AGE: u64 = 365 * 24 * 60 * 60;
+
+/// Set the age at which non-expiring escrows are reported as stuck.
+pub fn set_stuck_age(env: &Env, secs: u64) {
+    env.storage().persistent().set(&DataKey::StuckAge, &secs);
+}
+
+/// Get the age at which non-expiring escrows are reported as stuck.
+///
+/// **Contract**: Defaults to [`DEFAULT_STUCK_AGE`].
+pub fn get_stuck_age(env: &Env) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::StuckAge)
+        .unwrap_or(DEFAULT_STUCK_AGE)
+}
+
 /// Set paused state.
 pub fn set_paused(env: &Env, paused: bool) {
     let key = DataKey::Paused;
@@ -153,6 +728,347 @@ pub fn is_paused(env: &Env) -> bool {
     env.storage().persistent().get(&key).unwrap_or(false)
 }
 
+/// Set the strict owner check flag.
+pub fn set_strict_owner_check(env: &Env, enabled: bool) {
+    let key = DataKey::StrictOwnerCheck;
+    env.storage().persistent().set(&key, &enabled);
+}
+
+/// Get the strict owner check flag.
+///
+/// **Contract**: Defaults to `false`.
+pub fn is_strict_owner_check(env: &Env) -> bool {
+    let key = DataKey::StrictOwnerCheck;
+    env.storage().persistent().get(&key).unwrap_or(false)
+}
+
+/// Set the auto-pause flag.
+pub fn set_auto_pause(env: &Env, enabled: bool) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::AutoPause, &enabled);
+}
+
+/// Set the terminal re-deposit flag.
+pub fn set_terminal_redeposit(env: &Env, enabled: bool) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::TerminalRedeposit, &enabled);
+}
+
+/// Get the terminal re-deposit flag.
+///
+/// **Contract**: Defaults to `false`.
+pub fn is_terminal_redeposit(env: &Env) -> bool {
+    env.storage()
+        .persistent()
+        .get(&DataKey::TerminalRedeposit)
+        .unwrap_or(false)
+}
+
+/// Set the maximum batch size.
+pub fn set_max_batch_size(env: &Env, size: u32) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::MaxBatchSize, &size);
+}
+
+/// Get the maximum batch size, if the admin has configured one.
+pub fn get_max_batch_size(env: &Env) -> Option<u32> {
+    env.storage().persistent().get(&DataKey::MaxBatchSize)
+}
+
+/// Get the auto-pause flag.
+///
+/// **Contract**: Defaults to `false`.
+pub fn is_auto_pause(env: &Env) -> bool {
+    env.storage()
+        .persistent()
+        .get(&DataKey::AutoPause)
+        .unwrap_or(false)
+}
+
+/// Get the operations restricted to the admin.
+///
+/// **Contract**: Returns an empty vector if never set.
+pub fn get_gated_operations(env: &Env) -> Vec<Symbol> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::GatedOperations)
+        .unwrap_or(Vec::new(env))
+}
+
+/// Check whether an operation is restricted to the admin.
+pub fn is_operation_gated(env: &Env, operation: &Symbol) -> bool {
+    get_gated_operations(env).contains(operation)
+}
+
+/// Add or remove an operation from the gated set.
+///
+/// **Contract**: Idempotent; gating an already gated operation is a no-op.
+pub fn set_operation_gated(env: &Env, operation: &Symbol, gated: bool) {
+    let mut operations = get_gated_operations(env);
+    match (operations.first_index_of(operation), gated) {
+        (None, true) => operations.push_back(operation.clone()),
+        (Some(index), false) => {
+            operations.remove(index);
+        }
+        _ => return,
+    }
+    env.storage()
+        .persistent()
+        .set(&DataKey::GatedOperations, &operations);
+}
+
+/// Get the refund allow-list.
+///
+/// **Contract**: Returns an empty vector if never set.
+pub fn get_refund_allow_list(env: &Env) -> Vec<Address> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::RefundAllowList)
+        .unwrap_or(Vec::new(env))
+}
+
+/// Check whether refunds may be paid to `recipient`.
+///
+/// **Contract**: Every address is allowed while the allow-list is empty.
+pub fn is_refund_recipient_allowed(env: &Env, recipient: &Address) -> bool {
+    let allowed = get_refund_allow_list(env);
+    allowed.is_empty() || allowed.contains(recipient)
+}
+
+/// Add or remove an address from the refund allow-list.
+///
+/// **Contract**: Idempotent; adding an already listed address is a no-op.
+pub fn set_refund_recipient_allowed(env: &Env, recipient: &Address, allowed: bool) {
+    let mut recipients = get_refund_allow_list(env);
+    match (recipients.first_index_of(recipient), allowed) {
+        (None, true) => recipients.push_back(recipient.clone()),
+        (Some(index), false) => {
+            recipients.remove(index);
+        }
+        _ => return,
+    }
+    env.storage()
+        .persistent()
+        .set(&DataKey::RefundAllowList, &recipients);
+}
+
+/// Check whether deposits in `token` are accepted.
+///
+/// **Contract**: Every token is accepted while the allow-list is empty.
+pub fn is_token_allowed(env: &Env, token: &Address) -> bool {
+    get_allowed_token_count(env) == 0
+        || env
+            .storage()
+            .persistent()
+            .has(&DataKey::TokenAllowed(token.clone()))
+}
+
+/// Get the number of tokens on the deposit allow-list.
+///
+/// **Contract**: Returns 0 if never set.
+pub fn get_allowed_token_count(env: &Env) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::AllowedTokenCount)
+        .unwrap_or(0)
+}
+
+/// Add or remove a token from the deposit allow-list.
+///
+/// **Contract**: Idempotent; adding an already listed token is a no-op.
+pub fn set_token_allowed(env: &Env, token: &Address, allowed: bool) {
+    let storage = env.storage().persistent();
+    let key = DataKey::TokenAllowed(token.clone());
+    let count = get_allowed_token_count(env);
+    match (storage.has(&key), allowed) {
+        (false, true) => {
+            storage.set(&key, &true);
+            storage.set(&DataKey::AllowedTokenCount, &(count + 1));
+        }
+        (true, false) => {
+            storage.remove(&key);
+            storage.set(&DataKey::AllowedTokenCount, &count.saturating_sub(1));
+        }
+        _ => {}
+    }
+}
+
+/// Set the withdrawal fee configuration.
+pub fn set_fee_config(env: &Env, config: &FeeConfig) {
+    env.storage().persistent().set(&DataKey::FeeConfig, config);
+}
+
+/// Get the withdrawal fee configuration.
+///
+/// **Contract**: Defaults to no fee.
+pub fn get_fee_config(env: &Env) -> FeeConfig {
+    env.storage()
+        .persistent()
+        .get(&DataKey::FeeConfig)
+        .unwrap_or_default()
+}
+
+/// Get a pooled escrow.
+///
+/// **Contract**: Returns `None` if no pool exists for the commitment.
+pub fn get_pool(env: &Env, commitment: &Bytes) -> Option<Pool> {
+    let key = DataKey::Pool(commitment.clone());
+    env.storage().persistent().get(&key)
+}
+
+/// Put a pooled escrow into storage, overwriting any existing one.
+pub fn put_pool(env: &Env, commitment: &Bytes, pool: &Pool) {
+    let key = DataKey::Pool(commitment.clone());
+    env.storage().persistent().set(&key, pool);
+}
+
+/// Get a contributor's balance in a pool.
+///
+/// **Contract**: Returns 0 if the contributor holds nothing.
+pub fn get_pool_share(env: &Env, commitment: &Bytes, contributor: &Address) -> i128 {
+    let key = DataKey::PoolShare(commitment.clone(), contributor.clone());
+    env.storage().persistent().get(&key).unwrap_or(0)
+}
+
+/// Set a contributor's balance in a pool.
+///
+/// **Contract**: A zero balance removes the entry.
+pub fn set_pool_share(env: &Env, commitment: &Bytes, contributor: &Address, share: i128) {
+    let key = DataKey::PoolShare(commitment.clone(), contributor.clone());
+    if share == 0 {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, &share);
+    }
+}
+
+/// Get the cumulative fees collected for a token.
+///
+/// **Contract**: Returns 0 if never set.
+pub fn get_fees_collected(env: &Env, token: &Address) -> i128 {
+    let key = DataKey::FeesCollected(token.clone());
+    env.storage().persistent().get(&key).unwrap_or(0)
+}
+
+/// Add `fee` to the cumulative fees collected for a token.
+///
+/// **Contract**: Fails with `InternalError` on overflow.
+pub fn add_fees_collected(env: &Env, token: &Address, fee: i128) -> Result<(), QuickexError> {
+    let key = DataKey::FeesCollected(token.clone());
+    let total: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+    let total = total.checked_add(fee).ok_or(QuickexError::InternalError)?;
+    env.storage().persistent().set(&key, &total);
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// Privacy helpers (boolean API)
+// -----------------------------------------------------------------------------
+
+/// Get the number of accounts with boolean privacy enabled.
+///
+/// **Contract**: Returns 0 if never set.
+pub fn get_privacy_enabled_count(env: &Env) -> u64 {
+    let key = DataKey::PrivacyEnabledCount;
+    env.storage().persistent().get(&key).unwrap_or(0)
+}
+
+/// Adjust the privacy-enabled account count after an account toggles privacy.
+///
+/// **Contract**: Increments when `enabled` is true, decrements otherwise. Fails with
+/// [`QuickexError::InternalError`] on overflow/underflow.
+pub fn update_privacy_enabled_count(env: &Env, enabled: bool) -> Result<(), QuickexError> {
+    let key = DataKey::PrivacyEnabledCount;
+    let count: u64 = env.storage().persistent().get(&key).unwrap_or(0);
+    let count = if enabled {
+        count.checked_add(1)
+    } else {
+        count.checked_sub(1)
+    }
+    .ok_or(QuickexError::InternalError)?;
+    env.storage().persistent().set(&key, &count);
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// Viewer helpers
+// -----------------------------------------------------------------------------
+
+/// Get the viewers an owner has granted access to.
+///
+/// **Contract**: Returns empty vec if none. Order is grant order.
+pub fn get_viewers(env: &Env, owner: &Address) -> Vec<Address> {
+    let key = DataKey::Viewers(owner.clone());
+    env.storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or(Vec::new(env))
+}
+
+/// Get the owners that have granted access to a viewer.
+///
+/// **Contract**: Returns empty vec if none. Order is grant order.
+pub fn get_viewer_of(env: &Env, viewer: &Address) -> Vec<Address> {
+    let key = DataKey::ViewerOf(viewer.clone());
+    env.storage()
+        .persistent()
+        .get(&key)
+        .unwrap_or(Vec::new(env))
+}
+
+/// Maximum number of viewers a single owner may grant access to.
+pub const MAX_VIEWERS: u32 = 50;
+
+/// Record that `owner` granted viewing access to `viewer`, in both indexes.
+///
+/// **Contract**: Idempotent; an existing grant is left as-is. Fails with
+/// `TooManyViewers` if the owner already has [`MAX_VIEWERS`] viewers.
+pub fn add_viewer(env: &Env, owner: &Address, viewer: &Address) -> Result<(), QuickexError> {
+    let mut viewers = get_viewers(env, owner);
+    if viewers.contains(viewer) {
+        return Ok(());
+    }
+    if viewers.len() >= MAX_VIEWERS {
+        return Err(QuickexError::TooManyViewers);
+    }
+    viewers.push_back(viewer.clone());
+    env.storage()
+        .persistent()
+        .set(&DataKey::Viewers(owner.clone()), &viewers);
+
+    let mut owners = get_viewer_of(env, viewer);
+    owners.push_back(owner.clone());
+    env.storage()
+        .persistent()
+        .set(&DataKey::ViewerOf(viewer.clone()), &owners);
+    Ok(())
+}
+
+/// Remove `owner`'s grant to `viewer` from both indexes.
+///
+/// **Contract**: No-op if no grant exists.
+pub fn remove_viewer(env: &Env, owner: &Address, viewer: &Address) {
+    let mut viewers = get_viewers(env, owner);
+    let Some(index) = viewers.first_index_of(viewer) else {
+        return;
+    };
+    viewers.remove(index);
+    env.storage()
+        .persistent()
+        .set(&DataKey::Viewers(owner.clone()), &viewers);
+
+    let mut owners = get_viewer_of(env, viewer);
+    if let Some(index) = owners.first_index_of(owner) {
+        owners.remove(index);
+        env.storage()
+            .persistent()
+            .set(&DataKey::ViewerOf(viewer.clone()), &owners);
+    }
+}
+
 // -----------------------------------------------------------------------------
 // Privacy helpers (level-based API)
 // -----------------------------------------------------------------------------
@@ -169,10 +1085,60 @@ pub fn get_privacy_level(env: &Env, account: &Address) -> Option<u32> {
     env.storage().persistent().get(&key)
 }
 
+/// Set the privacy history dedup flag.
+pub fn set_privacy_history_dedup(env: &Env, enabled: bool) {
+    let key = DataKey::PrivacyHistoryDedup;
+    env.storage().persistent().set(&key, &enabled);
+}
+
+/// Get the privacy history dedup flag.
+///
+/// **Contract**: Defaults to `false`.
+pub fn is_privacy_history_dedup(env: &Env) -> bool {
+    let key = DataKey::PrivacyHistoryDedup;
+    env.storage().persistent().get(&key).unwrap_or(false)
+}
+
+/// Set an owner's stealth mode flag.
+pub fn set_stealth(env: &Env, owner: &Address, enabled: bool) {
+    let key = DataKey::Stealth(owner.clone());
+    env.storage().persistent().set(&key, &enabled);
+}
+
+/// Get an owner's stealth mode flag.
+///
+/// **Contract**: Defaults to `false`.
+pub fn is_stealth(env: &Env, owner: &Address) -> bool {
+    let key = DataKey::Stealth(owner.clone());
+    env.storage().persistent().get(&key).unwrap_or(false)
+}
+
+/// Set the global privacy kill-switch.
+pub fn set_privacy_disabled_globally(env: &Env, disabled: bool) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::PrivacyDisabledGlobally, &disabled);
+}
+
+/// Get the global privacy kill-switch.
+///
+/// **Contract**: Defaults to `false`.
+pub fn is_privacy_disabled_globally(env: &Env) -> bool {
+    env.storage()
+        .persistent()
+        .get(&DataKey::PrivacyDisabledGlobally)
+        .unwrap_or(false)
+}
+
+/// Maximum number of entries kept in an account's privacy history.
+pub const MAX_PRIVACY_HISTORY: u32 = 64;
+
 /// Add to privacy history for an account.
 ///
 /// **Contract**: Pushes `level` to the front of the history (newest first).
-/// History is unbounded; consider capping in future if needed.
+/// With dedup enabled, a `level` equal to the newest entry is not recorded.
+/// Once the history holds more than [`MAX_PRIVACY_HISTORY`] entries the oldest
+/// is dropped, so it acts as a ring buffer of the most recent changes.
 pub fn add_privacy_history(env: &Env, account: &Address, level: u32) {
     let key = DataKey::PrivacyHistory(account.clone());
     let mut history: Vec<u32> = env
@@ -180,7 +1146,13 @@ pub fn add_privacy_history(env: &Env, account: &Address, level: u32) {
         .persistent()
         .get(&key)
         .unwrap_or(Vec::new(env));
+    if is_privacy_history_dedup(env) && history.first() == Some(level) {
+        return;
+    }
     history.push_front(level);
+    if history.len() > MAX_PRIVACY_HISTORY {
+        history.pop_back();
+    }
     env.storage().persistent().set(&key, &history);
 }
 
@@ -1,4 +1,4 @@
-use soroban_sdk::{testutils::Address as _, Address, Bytes, Env};
+use soroban_sdk::{contracttype, testutils::Address as _, Address, Bytes, Env};
 
 use crate::{
     storage::*,
@@ -24,6 +24,21 @@ fn test_escrow_storage() {
             status: EscrowStatus::Pending,
             created_at,
             expires_at: 0,
+            extend_on_withdraw: 0,
+            allowed_recipient: None,
+            salt_hash: None,
+            nonce: None,
+            created_ledger: 0,
+            min_confirmations: 0,
+            on_expiry_recipient: None,
+            installments: None,
+            installments_claimed: 0,
+            max_extensions: None,
+            extensions_used: 0,
+            earliest_withdraw: 0,
+            next_of: None,
+            beneficiary: None,
+            escrow_id: 0,
         };
 
         // Test put_escrow
@@ -65,6 +80,21 @@ fn test_escrow_status_update() {
             status: EscrowStatus::Pending,
             created_at,
             expires_at: 0,
+            extend_on_withdraw: 0,
+            allowed_recipient: None,
+            salt_hash: None,
+            nonce: None,
+            created_ledger: 0,
+            min_confirmations: 0,
+            on_expiry_recipient: None,
+            installments: None,
+            installments_claimed: 0,
+            max_extensions: None,
+            extensions_used: 0,
+            earliest_withdraw: 0,
+            next_of: None,
+            beneficiary: None,
+            escrow_id: 0,
         };
 
         put_escrow(&env, &commitment, &entry);
@@ -178,3 +208,78 @@ fn test_privacy_storage() {
         assert_eq!(get_privacy_history(&env, &non_existent_account).len(), 0);
     });
 }
+
+#[test]
+fn test_privacy_history_is_capped() {
+    let env = Env::default();
+    let contract_id = env.register(crate::QuickexContract, ());
+    env.as_contract(&contract_id, || {
+        let account = Address::generate(&env);
+        for level in 0..100u32 {
+            add_privacy_history(&env, &account, level);
+        }
+
+        let history = get_privacy_history(&env, &account);
+        assert_eq!(history.len(), MAX_PRIVACY_HISTORY);
+        assert_eq!(history.get(0).unwrap(), 99u32);
+        assert_eq!(history.get(MAX_PRIVACY_HISTORY - 1).unwrap(), 36u32);
+    });
+}
+
+/// Layout of an escrow as stored by the first release of the contract.
+#[contracttype]
+#[derive(Clone)]
+struct BaselineEscrowEntry {
+    token: Address,
+    amount: i128,
+    owner: Address,
+    status: EscrowStatus,
+    created_at: u64,
+    expires_at: u64,
+}
+
+#[test]
+fn test_baseline_escrow_entry_still_decodes() {
+    let env = Env::default();
+    let contract_id = env.register(crate::QuickexContract, ());
+    env.as_contract(&contract_id, || {
+        let commitment: Bytes = Bytes::from_array(&env, &[3u8; 32]);
+        let token = Address::generate(&env);
+        let owner = Address::generate(&env);
+        let baseline = BaselineEscrowEntry {
+            token: token.clone(),
+            amount: 1000,
+            owner: owner.clone(),
+            status: EscrowStatus::Pending,
+            created_at: 5,
+            expires_at: 100,
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::Escrow(commitment.clone()), &baseline);
+
+        let mut entry = get_escrow(&env, &commitment).unwrap();
+        assert_eq!(entry.token, token);
+        assert_eq!(entry.amount, 1000);
+        assert_eq!(entry.owner, owner);
+        assert_eq!(entry.status, EscrowStatus::Pending);
+        assert_eq!(entry.created_at, 5);
+        assert_eq!(entry.expires_at, 100);
+        assert_eq!(entry.extend_on_withdraw, 0);
+        assert!(entry.allowed_recipient.is_none());
+        assert!(entry.installments.is_none());
+        assert_eq!(entry.escrow_id, 0);
+
+        // Writing the entry back keeps the baseline layout under the escrow key.
+        entry.status = EscrowStatus::Spent;
+        entry.escrow_id = 7;
+        put_escrow(&env, &commitment, &entry);
+        let stored: BaselineEscrowEntry = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Escrow(commitment.clone()))
+            .unwrap();
+        assert_eq!(stored.status, EscrowStatus::Spent);
+        assert_eq!(get_escrow(&env, &commitment).unwrap().escrow_id, 7);
+    });
+}
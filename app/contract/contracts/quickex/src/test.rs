@@ -22,14 +22,22 @@
 //! contract directory for how to extend the suite when adding new features.
 
 use crate::{
-    errors::QuickexError, storage::put_escrow, EscrowEntry, EscrowStatus, QuickexContract,
-    QuickexContractClient,
+    errors::QuickexError,
+    escrow::{record_new_escrow, GIFT_TIMEOUT_SECS},
+    storage::{
+        get_last_upgrade, put_escrow, update_status_counts, DataKey, DEFAULT_STUCK_AGE,
+        ESCROW_TTL_LEDGERS,
+    },
+    AccountExport, ActivitySnapshot, EscrowEntry, EscrowOptions, EscrowStatus, OwnerStats,
+    PrivacyState, QuickexContract, QuickexContractClient, StatusCounts, UpgradeInfo,
 };
 use soroban_sdk::{
-    testutils::{Address as _, Ledger},
+    testutils::{
+        storage::Persistent as _, Address as _, IssuerFlags, Ledger, MockAuth, MockAuthInvoke,
+    },
     token,
     xdr::ToXdr,
-    Address, Bytes, BytesN, ConversionError, Env, InvokeError,
+    Address, Bytes, BytesN, Env, IntoVal, InvokeError, Symbol,
 };
 
 fn setup<'a>() -> (Env, QuickexContractClient<'a>) {
@@ -57,12 +65,26 @@ fn setup_escrow(
         status: EscrowStatus::Pending,
         created_at: env.ledger().timestamp(),
         expires_at,
+        extend_on_withdraw: 0,
+        allowed_recipient: None,
+        salt_hash: None,
+        nonce: None,
+        created_ledger: 0,
+        min_confirmations: 0,
+        on_expiry_recipient: None,
+        installments: None,
+        installments_claimed: 0,
+        max_extensions: None,
+        extensions_used: 0,
+        earliest_withdraw: 0,
+        next_of: None,
+        beneficiary: None,
+        escrow_id: 0,
     };
 
     env.as_contract(contract_id, || {
-        // Use the new storage system to put the escrow entry
-        let storage_commitment: Bytes = commitment.into();
-        put_escrow(env, &storage_commitment, &entry);
+        // Register the entry with the same bookkeeping a real deposit performs
+        record_new_escrow(env, &commitment, &entry).unwrap();
     });
 }
 
@@ -87,10 +109,25 @@ fn setup_escrow_with_owner(
         status: EscrowStatus::Pending,
         created_at: env.ledger().timestamp(),
         expires_at,
+        extend_on_withdraw: 0,
+        allowed_recipient: None,
+        salt_hash: None,
+        nonce: None,
+        created_ledger: 0,
+        min_confirmations: 0,
+        on_expiry_recipient: None,
+        installments: None,
+        installments_claimed: 0,
+        max_extensions: None,
+        extensions_used: 0,
+        earliest_withdraw: 0,
+        next_of: None,
+        beneficiary: None,
+        escrow_id: 0,
     };
     env.as_contract(contract_id, || {
-        let storage_commitment: Bytes = commitment.into();
-        put_escrow(env, &storage_commitment, &entry);
+        // Register the entry with the same bookkeeping a real deposit performs
+        record_new_escrow(env, &commitment, &entry).unwrap();
     });
 }
 
@@ -126,7 +163,7 @@ fn test_get_escrow_details_privacy_enabled_hides_sensitive_fields() {
 
     // Stranger queries â€” sensitive fields must be hidden
     let view = client.get_escrow_details(&commitment, &stranger).unwrap();
-    assert_eq!(view.token, token);
+    assert_eq!(view.token, Some(token.clone()));
     assert_eq!(view.status, EscrowStatus::Pending);
     assert_eq!(view.amount, None);
     assert_eq!(view.owner, None);
@@ -162,7 +199,7 @@ fn test_get_escrow_details_privacy_enabled_owner_sees_full_details() {
 
     // Owner queries their own escrow â€” must see full details
     let view = client.get_escrow_details(&commitment, &owner).unwrap();
-    assert_eq!(view.token, token);
+    assert_eq!(view.token, Some(token.clone()));
     assert_eq!(view.status, EscrowStatus::Pending);
     assert_eq!(view.amount, Some(amount));
     assert_eq!(view.owner, Some(owner.clone()));
@@ -237,8 +274,8 @@ fn create_test_token(env: &Env) -> Address {
         .address()
 }
 
-fn assert_contract_error<T>(
-    result: Result<Result<T, ConversionError>, Result<QuickexError, InvokeError>>,
+fn assert_contract_error<T, E>(
+    result: Result<Result<T, E>, Result<QuickexError, InvokeError>>,
     expected: QuickexError,
 ) {
     match result {
@@ -490,6 +527,9 @@ fn test_canonical_error_code_ranges() {
     assert_eq!(QuickexError::InvalidAmount as u32, 100);
     assert_eq!(QuickexError::InvalidSalt as u32, 101);
     assert_eq!(QuickexError::InvalidPrivacyLevel as u32, 102);
+    assert_eq!(QuickexError::BatchTooLarge as u32, 103);
+    assert_eq!(QuickexError::InvalidSchedule as u32, 104);
+    assert_eq!(QuickexError::InvalidFee as u32, 105);
 
     // Auth/admin failures (200-299)
     assert_eq!(QuickexError::Unauthorized as u32, 200);
@@ -506,11 +546,53 @@ fn test_canonical_error_code_ranges() {
     assert_eq!(QuickexError::EscrowExpired as u32, 307);
     assert_eq!(QuickexError::EscrowNotExpired as u32, 308);
     assert_eq!(QuickexError::InvalidOwner as u32, 309);
+    assert_eq!(QuickexError::RecipientNotAllowed as u32, 310);
+    assert_eq!(QuickexError::TokenMismatch as u32, 311);
+    assert_eq!(QuickexError::InsufficientConfirmations as u32, 312);
+    assert_eq!(QuickexError::RefundDisabled as u32, 313);
+    assert_eq!(QuickexError::NoExpiryRecipient as u32, 314);
+    assert_eq!(QuickexError::NoMaturedInstallments as u32, 315);
+    assert_eq!(QuickexError::EscrowNotTerminal as u32, 316);
+    assert_eq!(QuickexError::StaleNonce as u32, 317);
+    assert_eq!(QuickexError::TooManyViewers as u32, 318);
+    assert_eq!(QuickexError::ExtensionLimitReached as u32, 319);
+    assert_eq!(QuickexError::EscrowNotExpirable as u32, 320);
+    assert_eq!(QuickexError::SlippageExceeded as u32, 321);
+    assert_eq!(QuickexError::EscrowNotYetClaimable as u32, 322);
+    assert_eq!(QuickexError::NoPoolShare as u32, 323);
+    assert_eq!(QuickexError::PartialWithdrawExceedsBalance as u32, 324);
+    assert_eq!(QuickexError::PrerequisiteNotMet as u32, 325);
+    assert_eq!(QuickexError::SnapshotTooSoon as u32, 326);
+    assert_eq!(QuickexError::TokenNotAllowed as u32, 327);
+    assert_eq!(QuickexError::ContractNotPaused as u32, 328);
+    assert_eq!(QuickexError::EscrowNotStuck as u32, 329);
+    assert_eq!(QuickexError::SwapInputMismatch as u32, 330);
 
     // Internal/unexpected conditions (900-999)
     assert_eq!(QuickexError::InternalError as u32, 900);
 }
 
+#[test]
+fn test_error_catalog_lists_codes_with_names() {
+    let (env, client) = setup();
+    let catalog = client.error_catalog();
+
+    assert_eq!(
+        catalog.first().unwrap(),
+        (100, Symbol::new(&env, "InvalidAmount"))
+    );
+    assert_eq!(
+        catalog.last().unwrap(),
+        (900, Symbol::new(&env, "InternalError"))
+    );
+    assert!(catalog.contains((311, Symbol::new(&env, "TokenMismatch"))));
+
+    // Codes are unique and ascending.
+    for i in 1..catalog.len() {
+        assert!(catalog.get(i - 1).unwrap().0 < catalog.get(i).unwrap().0);
+    }
+}
+
 /// Regression suite: deposit with commitment — create escrow (golden path).
 #[test]
 fn test_deposit() {
@@ -651,27 +733,63 @@ fn test_set_paused_by_non_admin_fails() {
 }
 
 #[test]
-fn test_set_admin() {
+fn test_nominate_then_accept_admin() {
     let (env, client) = setup();
     let admin = Address::generate(&env);
-    let new_admin = Address::generate(&env);
-
-    // Initialize admin
+    let nominee = Address::generate(&env);
     client.initialize(&admin);
 
-    // Transfer admin rights
-    client.set_admin(&admin, &new_admin);
+    client.nominate_admin(&admin, &nominee);
+    assert_eq!(client.get_pending_admin(), Some(nominee.clone()));
+    assert_eq!(client.get_admin(), Some(admin.clone()));
 
-    // Verify new admin is set
-    assert_eq!(client.get_admin(), Some(new_admin.clone()));
+    client.accept_admin(&nominee);
+    assert_eq!(client.get_admin(), Some(nominee.clone()));
+    assert_eq!(client.get_pending_admin(), None);
 
-    // Verify new admin can pause
-    client.set_paused(&new_admin, &true);
+    // The new admin can pause
+    client.set_paused(&nominee, &true);
     assert!(client.is_paused());
 }
 
 #[test]
-fn test_set_admin_by_non_admin_fails() {
+fn test_accept_admin_by_wrong_address_fails() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let nominee = Address::generate(&env);
+    let other = Address::generate(&env);
+    client.initialize(&admin);
+
+    assert_contract_error(client.try_accept_admin(&other), QuickexError::Unauthorized);
+
+    client.nominate_admin(&admin, &nominee);
+    assert_contract_error(client.try_accept_admin(&other), QuickexError::Unauthorized);
+    assert_contract_error(
+        client.try_nominate_admin(&nominee, &other),
+        QuickexError::Unauthorized,
+    );
+    assert_eq!(client.get_admin(), Some(admin));
+}
+
+#[test]
+fn test_renomination_overwrites_pending_admin() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+    client.initialize(&admin);
+
+    client.nominate_admin(&admin, &first);
+    client.nominate_admin(&admin, &second);
+    assert_eq!(client.get_pending_admin(), Some(second.clone()));
+
+    assert_contract_error(client.try_accept_admin(&first), QuickexError::Unauthorized);
+    client.accept_admin(&second);
+    assert_eq!(client.get_admin(), Some(second));
+}
+
+#[test]
+fn test_nominate_admin_by_non_admin_fails() {
     let (env, client) = setup();
     let admin = Address::generate(&env);
     let non_admin = Address::generate(&env);
@@ -681,10 +799,23 @@ fn test_set_admin_by_non_admin_fails() {
     client.initialize(&admin);
 
     // Non-admin tries to transfer admin rights - should fail
-    let result = client.try_set_admin(&non_admin, &new_admin);
+    let result = client.try_nominate_admin(&non_admin, &new_admin);
     assert_contract_error(result, QuickexError::Unauthorized);
 }
 
+#[test]
+fn test_set_paused_requires_admin_auth() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    // Passing the admin's address without the admin's signature must fail.
+    env.set_auths(&[]);
+    assert!(client.try_set_paused(&admin, &true).is_err());
+    env.mock_all_auths();
+    assert!(!client.is_paused());
+}
+
 #[test]
 fn test_old_admin_cannot_pause_after_transfer() {
     let (env, client) = setup();
@@ -695,7 +826,8 @@ fn test_old_admin_cannot_pause_after_transfer() {
     client.initialize(&admin);
 
     // Transfer admin rights
-    client.set_admin(&admin, &new_admin);
+    client.nominate_admin(&admin, &new_admin);
+    client.accept_admin(&new_admin);
 
     // Old admin tries to pause - should fail
     let result = client.try_set_paused(&admin, &true);
@@ -746,6 +878,21 @@ fn test_get_commitment_state_spent() {
         status: EscrowStatus::Spent,
         created_at: env.ledger().timestamp(),
         expires_at: 0,
+        extend_on_withdraw: 0,
+        allowed_recipient: None,
+        salt_hash: None,
+        nonce: None,
+        created_ledger: 0,
+        min_confirmations: 0,
+        on_expiry_recipient: None,
+        installments: None,
+        installments_claimed: 0,
+        max_extensions: None,
+        extensions_used: 0,
+        earliest_withdraw: 0,
+        next_of: None,
+        beneficiary: None,
+        escrow_id: 0,
     };
 
     env.as_contract(&client.address, || {
@@ -892,6 +1039,21 @@ fn test_verify_proof_view_spent_commitment() {
         status: EscrowStatus::Spent,
         created_at: env.ledger().timestamp(),
         expires_at: 0,
+        extend_on_withdraw: 0,
+        allowed_recipient: None,
+        salt_hash: None,
+        nonce: None,
+        created_ledger: 0,
+        min_confirmations: 0,
+        on_expiry_recipient: None,
+        installments: None,
+        installments_claimed: 0,
+        max_extensions: None,
+        extensions_used: 0,
+        earliest_withdraw: 0,
+        next_of: None,
+        beneficiary: None,
+        escrow_id: 0,
     };
 
     let escrow_key = soroban_sdk::Symbol::new(&env, "escrow");
@@ -940,7 +1102,7 @@ fn test_get_escrow_details_found() {
 
     let entry = details.unwrap();
     assert_eq!(entry.amount, Some(amount));
-    assert_eq!(entry.token, token);
+    assert_eq!(entry.token, Some(token));
     assert_eq!(entry.status, EscrowStatus::Pending);
 }
 
@@ -985,6 +1147,21 @@ fn test_get_escrow_details_spent_status() {
         status: EscrowStatus::Spent,
         created_at: env.ledger().timestamp(),
         expires_at: 0,
+        extend_on_withdraw: 0,
+        allowed_recipient: None,
+        salt_hash: None,
+        nonce: None,
+        created_ledger: 0,
+        min_confirmations: 0,
+        on_expiry_recipient: None,
+        installments: None,
+        installments_claimed: 0,
+        max_extensions: None,
+        extensions_used: 0,
+        earliest_withdraw: 0,
+        next_of: None,
+        beneficiary: None,
+        escrow_id: 0,
     };
 
     env.as_contract(&client.address, || {
@@ -1000,7 +1177,7 @@ fn test_get_escrow_details_spent_status() {
     let retrieved = details.unwrap();
     assert_eq!(retrieved.status, EscrowStatus::Spent);
     assert_eq!(retrieved.amount, Some(amount));
-    assert_eq!(retrieved.token, token);
+    assert_eq!(retrieved.token, Some(token));
 }
 // ============================================================================
 // Upgrade Tests
@@ -1218,6 +1395,101 @@ fn test_double_refund_fails() {
     assert_eq!(res, Err(Ok(crate::errors::QuickexError::AlreadySpent)));
 }
 
+#[test]
+fn test_list_tokens_pages_registry() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+    assert_eq!(client.token_count(), 0);
+    assert!(client.list_tokens(&0, &2).is_empty());
+
+    let mut tokens = soroban_sdk::Vec::new(&env);
+    for i in 0..5u8 {
+        let token = create_test_token(&env);
+        setup_fee_escrow(
+            &env,
+            &client,
+            &token,
+            &owner,
+            10,
+            &Bytes::from_array(&env, &[i]),
+        );
+        tokens.push_back(token);
+    }
+
+    assert_eq!(client.token_count(), 5);
+    assert_eq!(client.list_tokens(&0, &2), tokens.slice(0..2));
+    assert_eq!(client.list_tokens(&2, &2), tokens.slice(2..4));
+    assert_eq!(client.list_tokens(&4, &2), tokens.slice(4..5));
+    assert!(client.list_tokens(&5, &2).is_empty());
+    assert_eq!(client.list_tokens(&0, &100), tokens);
+}
+
+// ============================================================================
+// Expiry Extension Tests
+// ============================================================================
+
+#[test]
+fn test_withdraw_extends_expiry_when_configured() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"extend_salt");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+
+    let options = EscrowOptions {
+        extend_on_withdraw: 50,
+        ..Default::default()
+    };
+    let commitment = client.deposit_with_options(&token, &amount, &owner, &salt, &100, &options);
+    let expires_at = env.ledger().timestamp() + 100;
+
+    env.ledger().set_timestamp(expires_at - 10);
+    client.withdraw(&token, &amount, &commitment, &owner, &salt);
+
+    let view = client.get_escrow_details(&commitment, &owner).unwrap();
+    assert_eq!(view.expires_at, Some(expires_at + 50));
+}
+
+#[test]
+fn test_withdraw_without_extension_keeps_expiry() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"no_extend_salt");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+
+    let commitment = client.deposit(&token, &amount, &owner, &salt, &100);
+    let expires_at = env.ledger().timestamp() + 100;
+
+    client.withdraw(&token, &amount, &commitment, &owner, &salt);
+
+    let view = client.get_escrow_details(&commitment, &owner).unwrap();
+    assert_eq!(view.expires_at, Some(expires_at));
+}
+
+#[test]
+fn test_withdraw_extension_ignored_for_non_expiring_escrow() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"no_expiry_extend_salt");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+
+    let options = EscrowOptions {
+        extend_on_withdraw: 50,
+        ..Default::default()
+    };
+    let commitment = client.deposit_with_options(&token, &amount, &owner, &salt, &0, &options);
+
+    client.withdraw(&token, &amount, &commitment, &owner, &salt);
+
+    let view = client.get_escrow_details(&commitment, &owner).unwrap();
+    assert_eq!(view.expires_at, Some(0));
+}
+
 // ============================================================================
 // Regression suite: single full-flow golden path (run after upgrades)
 // ============================================================================
@@ -1260,3 +1532,4618 @@ fn regression_golden_path_full_flow() {
         Some(EscrowStatus::Spent)
     );
 }
+
+// ============================================================================
+// Per-token Escrow Count Tests
+// ============================================================================
+
+#[test]
+fn test_escrow_count_by_token_tracks_lifecycle() {
+    let (env, client) = setup();
+    let token_a = create_test_token(&env);
+    let token_b = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token_a).mint(&owner, &(amount * 2));
+    token::StellarAssetClient::new(&env, &token_b).mint(&owner, &amount);
+
+    assert_eq!(client.escrow_count_by_token(&token_a), 0);
+
+    let salt_1 = Bytes::from_slice(&env, b"count_salt_1");
+    let salt_2 = Bytes::from_slice(&env, b"count_salt_2");
+    let salt_3 = Bytes::from_slice(&env, b"count_salt_3");
+    let commitment_1 = client.deposit(&token_a, &amount, &owner, &salt_1, &100);
+    client.deposit(&token_a, &amount, &owner, &salt_2, &0);
+    client.deposit(&token_b, &amount, &owner, &salt_3, &0);
+
+    assert_eq!(client.escrow_count_by_token(&token_a), 2);
+    assert_eq!(client.escrow_count_by_token(&token_b), 1);
+
+    // Spending an escrow removes it from its token's count.
+    client.withdraw(&token_a, &amount, &commitment_1, &owner, &salt_1);
+    assert_eq!(client.escrow_count_by_token(&token_a), 1);
+    assert_eq!(client.escrow_count_by_token(&token_b), 1);
+}
+
+#[test]
+fn test_escrow_count_by_token_decrements_on_refund() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"count_refund_salt");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+
+    let commitment = client.deposit(&token, &amount, &owner, &salt, &100);
+    assert_eq!(client.escrow_count_by_token(&token), 1);
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + 100);
+    client.refund(&commitment, &owner);
+    assert_eq!(client.escrow_count_by_token(&token), 0);
+}
+
+// ============================================================================
+// Strict Owner Check Tests
+// ============================================================================
+
+#[test]
+fn test_strict_owner_check_rejects_mismatched_entry() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let admin = Address::generate(&env);
+    let to = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"strict_mismatch_salt");
+    let commitment = client.create_amount_commitment(&to, &amount, &salt);
+
+    // `setup_escrow` stores a random owner, so the entry's owner disagrees with
+    // the address the commitment was derived from.
+    setup_escrow(&env, &client.address, &token, amount, commitment.clone(), 0);
+    token::StellarAssetClient::new(&env, &token).mint(&client.address, &amount);
+
+    client.initialize(&admin);
+    client.set_strict_owner_check(&admin, &true);
+    assert!(client.is_strict_owner_check());
+
+    let result = client.try_withdraw(&token, &amount, &commitment, &to, &salt);
+    assert_contract_error(result, QuickexError::InvalidOwner);
+
+    // Disabling the check restores the commitment-only behaviour.
+    client.set_strict_owner_check(&admin, &false);
+    assert!(client.withdraw(&token, &amount, &commitment, &to, &salt));
+}
+
+#[test]
+fn test_strict_owner_check_allows_matching_owner() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"strict_match_salt");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+
+    client.initialize(&admin);
+    client.set_strict_owner_check(&admin, &true);
+
+    let commitment = client.deposit(&token, &amount, &owner, &salt, &0);
+    assert!(client.withdraw(&token, &amount, &commitment, &owner, &salt));
+}
+
+#[test]
+fn test_set_strict_owner_check_by_non_admin_fails() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let non_admin = Address::generate(&env);
+
+    client.initialize(&admin);
+
+    let result = client.try_set_strict_owner_check(&non_admin, &true);
+    assert_contract_error(result, QuickexError::Unauthorized);
+    assert!(!client.is_strict_owner_check());
+}
+
+#[test]
+fn test_initialized_at_records_ledger_time() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+
+    assert_eq!(client.initialized_at(), None);
+
+    env.ledger().set_timestamp(1_700_000_000);
+    client.initialize(&admin);
+    assert_eq!(client.initialized_at(), Some(1_700_000_000));
+
+    // Later ledgers do not move the recorded timestamp.
+    env.ledger().set_timestamp(1_700_000_500);
+    assert_eq!(client.initialized_at(), Some(1_700_000_000));
+}
+
+#[test]
+fn test_preview_refund_reports_refundable_amount() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"preview_refund_salt");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+
+    let commitment = client.deposit(&token, &amount, &owner, &salt, &100);
+
+    // Not yet expired: nothing to refund.
+    assert_eq!(client.preview_refund(&commitment, &owner), None);
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + 100);
+    assert_eq!(client.preview_refund(&commitment, &owner), Some(amount));
+
+    // The preview matches what the owner actually receives.
+    client.refund(&commitment, &owner);
+    assert_eq!(token::Client::new(&env, &token).balance(&owner), amount);
+
+    // Terminal escrows are no longer refundable.
+    assert_eq!(client.preview_refund(&commitment, &owner), None);
+}
+
+#[test]
+fn test_preview_refund_none_for_missing_or_non_expiring_escrow() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"preview_no_expiry_salt");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+
+    let missing = BytesN::from_array(&env, &[7u8; 32]);
+    assert_eq!(client.preview_refund(&missing, &owner), None);
+
+    let commitment = client.deposit(&token, &amount, &owner, &salt, &0);
+    env.ledger()
+        .set_timestamp(env.ledger().timestamp() + 10_000);
+    assert_eq!(client.preview_refund(&commitment, &owner), None);
+}
+
+#[test]
+fn test_preview_refund_hidden_by_privacy_and_stealth() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let viewer = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+    let salt = Bytes::from_slice(&env, b"preview_private");
+    let commitment = client.deposit(&token, &amount, &owner, &salt, &100);
+    env.ledger().set_timestamp(env.ledger().timestamp() + 100);
+    assert_eq!(client.preview_refund(&commitment, &stranger), Some(amount));
+
+    client.enable_privacy(&owner, &1);
+    client.grant_viewer(&owner, &viewer);
+    assert_eq!(client.preview_refund(&commitment, &stranger), None);
+    assert_eq!(client.preview_refund(&commitment, &viewer), Some(amount));
+    assert_eq!(client.preview_refund(&commitment, &owner), Some(amount));
+
+    client.enable_privacy(&owner, &0);
+    client.set_stealth(&owner, &true);
+    assert_eq!(client.preview_refund(&commitment, &stranger), None);
+    assert_eq!(client.preview_refund(&commitment, &owner), Some(amount));
+}
+
+// ============================================================================
+// Allowed Recipient Tests
+// ============================================================================
+
+#[test]
+fn test_allowed_recipient_rejects_other_recipient() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let compliance_wallet = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"restricted_salt");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+
+    let options = EscrowOptions {
+        allowed_recipient: Some(compliance_wallet),
+        ..Default::default()
+    };
+    let commitment = client.deposit_with_options(&token, &amount, &owner, &salt, &0, &options);
+
+    let result = client.try_withdraw(&token, &amount, &commitment, &owner, &salt);
+    assert_contract_error(result, QuickexError::RecipientNotAllowed);
+    assert_eq!(
+        client.get_commitment_state(&commitment),
+        Some(EscrowStatus::Pending)
+    );
+}
+
+#[test]
+fn test_allowed_recipient_accepts_allowed_address() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"allowed_salt");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+
+    let options = EscrowOptions {
+        allowed_recipient: Some(owner.clone()),
+        ..Default::default()
+    };
+    let commitment = client.deposit_with_options(&token, &amount, &owner, &salt, &0, &options);
+
+    assert!(client.withdraw(&token, &amount, &commitment, &owner, &salt));
+    assert_eq!(token::Client::new(&env, &token).balance(&owner), amount);
+}
+
+#[test]
+fn test_privacy_adoption_tracks_toggles() {
+    let (env, client) = setup();
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let carol = Address::generate(&env);
+
+    assert_eq!(client.privacy_adoption(), 0);
+
+    client.set_privacy(&alice, &true);
+    client.set_privacy(&bob, &true);
+    client.set_privacy(&carol, &true);
+    assert_eq!(client.privacy_adoption(), 3);
+
+    client.set_privacy(&bob, &false);
+    assert_eq!(client.privacy_adoption(), 2);
+
+    // A rejected no-op toggle leaves the count untouched.
+    let result = client.try_set_privacy(&alice, &true);
+    assert_contract_error(result, QuickexError::PrivacyAlreadySet);
+    assert_eq!(client.privacy_adoption(), 2);
+
+    client.set_privacy(&alice, &false);
+    client.set_privacy(&carol, &false);
+    client.set_privacy(&bob, &true);
+    assert_eq!(client.privacy_adoption(), 1);
+}
+
+// ============================================================================
+// Salt-hash Deposit Tests
+// ============================================================================
+
+#[test]
+fn test_salt_hash_deposit_withdraw_with_correct_salt() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"secret_salt_preimage");
+    let salt_hash: BytesN<32> = env.crypto().sha256(&salt).into();
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+
+    let commitment = client.deposit_with_salt_hash(&token, &amount, &owner, &salt_hash, &0);
+    assert_eq!(
+        commitment,
+        client.create_amount_commitment(&owner, &amount, &salt_hash.clone().into())
+    );
+
+    assert!(client.withdraw_with_salt_hash(&amount, &owner, &salt));
+    assert_eq!(token::Client::new(&env, &token).balance(&owner), amount);
+    assert_eq!(
+        client.get_commitment_state(&commitment),
+        Some(EscrowStatus::Spent)
+    );
+}
+
+#[test]
+fn test_salt_hash_withdraw_with_wrong_salt_fails() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"secret_salt_preimage");
+    let salt_hash: BytesN<32> = env.crypto().sha256(&salt).into();
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+
+    client.deposit_with_salt_hash(&token, &amount, &owner, &salt_hash, &0);
+
+    let wrong_salt = Bytes::from_slice(&env, b"guessed_salt");
+    let result = client.try_withdraw_with_salt_hash(&amount, &owner, &wrong_salt);
+    assert_contract_error(result, QuickexError::CommitmentNotFound);
+}
+
+#[test]
+fn test_salt_hash_escrow_rejects_plain_withdraw_with_public_hash() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"secret_salt_preimage");
+    let salt_hash: BytesN<32> = env.crypto().sha256(&salt).into();
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+
+    let commitment = client.deposit_with_salt_hash(&token, &amount, &owner, &salt_hash, &0);
+
+    // Knowing only the (public) salt hash is not enough to withdraw.
+    let hash_as_salt: Bytes = salt_hash.into();
+    let result = client.try_withdraw(&token, &amount, &commitment, &owner, &hash_as_salt);
+    assert_contract_error(result, QuickexError::CommitmentMismatch);
+}
+
+#[test]
+fn test_salt_hash_withdraw_rejects_regular_escrow() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+
+    // A regular escrow whose salt happens to be the hash of some preimage.
+    let preimage = Bytes::from_slice(&env, b"preimage");
+    let hashed: Bytes = BytesN::<32>::from(env.crypto().sha256(&preimage)).into();
+    client.deposit(&token, &amount, &owner, &hashed, &0);
+
+    let result = client.try_withdraw_with_salt_hash(&amount, &owner, &preimage);
+    assert_contract_error(result, QuickexError::CommitmentMismatch);
+}
+
+// ============================================================================
+// Viewer Grant Tests
+// ============================================================================
+
+#[test]
+fn test_grant_viewer_updates_both_indexes() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+    let viewer_a = Address::generate(&env);
+    let viewer_b = Address::generate(&env);
+
+    client.grant_viewer(&owner, &viewer_a);
+    client.grant_viewer(&owner, &viewer_b);
+    // Granting again is a no-op
+    client.grant_viewer(&owner, &viewer_a);
+
+    let viewers = client.my_granted_viewers(&owner);
+    assert_eq!(viewers.len(), 2);
+    assert_eq!(viewers.get(0).unwrap(), viewer_a);
+    assert_eq!(viewers.get(1).unwrap(), viewer_b);
+
+    let owners = client.escrows_i_can_view(&viewer_a);
+    assert_eq!(owners.len(), 1);
+    assert_eq!(owners.get(0).unwrap(), owner);
+}
+
+#[test]
+fn test_revoke_viewer_updates_both_indexes() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+    let other_owner = Address::generate(&env);
+    let viewer = Address::generate(&env);
+
+    client.grant_viewer(&owner, &viewer);
+    client.grant_viewer(&other_owner, &viewer);
+    client.revoke_viewer(&owner, &viewer);
+    // Revoking a missing grant is a no-op
+    client.revoke_viewer(&owner, &viewer);
+
+    assert_eq!(client.my_granted_viewers(&owner).len(), 0);
+    let owners = client.escrows_i_can_view(&viewer);
+    assert_eq!(owners.len(), 1);
+    assert_eq!(owners.get(0).unwrap(), other_owner);
+}
+
+#[test]
+fn test_granted_viewer_sees_private_escrow_details() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let viewer = Address::generate(&env);
+    let amount: i128 = 5000;
+    let commitment: BytesN<32> = BytesN::from_array(&env, &[7; 32]);
+
+    setup_escrow_with_owner(
+        &env,
+        &client.address,
+        &token,
+        &owner,
+        amount,
+        commitment.clone(),
+        0,
+    );
+    client.set_privacy(&owner, &true);
+    client.grant_viewer(&owner, &viewer);
+
+    let view = client.get_escrow_details(&commitment, &viewer).unwrap();
+    assert_eq!(view.amount, Some(amount));
+    assert_eq!(view.owner, Some(owner.clone()));
+
+    client.revoke_viewer(&owner, &viewer);
+    let view = client.get_escrow_details(&commitment, &viewer).unwrap();
+    assert_eq!(view.amount, None);
+    assert_eq!(view.owner, None);
+}
+
+// ============================================================================
+// Refund Fallback Tests
+// ============================================================================
+
+#[test]
+fn test_refund_with_fallback_pays_owner_when_receivable() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let fallback = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"fallback_ok_salt");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+
+    let commitment = client.deposit(&token, &amount, &owner, &salt, &100);
+    env.ledger().set_timestamp(env.ledger().timestamp() + 100);
+
+    client.refund_with_fallback(&commitment, &owner, &fallback);
+
+    let token_client = token::Client::new(&env, &token);
+    assert_eq!(token_client.balance(&owner), amount);
+    assert_eq!(token_client.balance(&fallback), 0);
+    assert_eq!(
+        client.get_commitment_state(&commitment),
+        Some(EscrowStatus::Refunded)
+    );
+}
+
+#[test]
+fn test_refund_with_fallback_pays_fallback_when_owner_cannot_receive() {
+    let (env, client) = setup();
+    let sac = env.register_stellar_asset_contract_v2(Address::generate(&env));
+    sac.issuer().set_flag(IssuerFlags::RevocableFlag);
+    let token = sac.address();
+    let owner = Address::generate(&env);
+    let fallback = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"fallback_redirect_salt");
+    let asset_admin = token::StellarAssetClient::new(&env, &token);
+    asset_admin.mint(&owner, &amount);
+
+    let commitment = client.deposit(&token, &amount, &owner, &salt, &100);
+    env.ledger().set_timestamp(env.ledger().timestamp() + 100);
+
+    // The owner's trustline is deauthorized, so a plain refund can never succeed.
+    asset_admin.set_authorized(&owner, &false);
+    assert!(client.try_refund(&commitment, &owner).is_err());
+
+    client.refund_with_fallback(&commitment, &owner, &fallback);
+
+    let token_client = token::Client::new(&env, &token);
+    assert_eq!(token_client.balance(&fallback), amount);
+    assert_eq!(token_client.balance(&owner), 0);
+    assert_eq!(
+        client.get_commitment_state(&commitment),
+        Some(EscrowStatus::Refunded)
+    );
+}
+
+#[test]
+fn test_refund_with_fallback_rejects_non_owner() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let attacker = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"fallback_owner_salt");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+
+    let commitment = client.deposit(&token, &amount, &owner, &salt, &100);
+    env.ledger().set_timestamp(env.ledger().timestamp() + 100);
+
+    let result = client.try_refund_with_fallback(&commitment, &attacker, &attacker);
+    assert_contract_error(result, QuickexError::InvalidOwner);
+}
+
+// ============================================================================
+// Level-based Privacy Auth Tests
+// ============================================================================
+
+#[test]
+fn test_enable_privacy_requires_account_auth() {
+    let env = Env::default();
+    let contract_id = env.register(QuickexContract, ());
+    let client = QuickexContractClient::new(&env, &contract_id);
+    let account = Address::generate(&env);
+    let other = Address::generate(&env);
+
+    // Authorization from someone other than the account is rejected.
+    let result = client
+        .mock_auths(&[MockAuth {
+            address: &other,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "enable_privacy",
+                args: (&account, 2u32).into_val(&env),
+                sub_invokes: &[],
+            },
+        }])
+        .try_enable_privacy(&account, &2);
+    assert!(result.is_err());
+    assert_eq!(client.privacy_status(&account), None);
+
+    // The account itself can set its level.
+    let ok = client
+        .mock_auths(&[MockAuth {
+            address: &account,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "enable_privacy",
+                args: (&account, 2u32).into_val(&env),
+                sub_invokes: &[],
+            },
+        }])
+        .enable_privacy(&account, &2);
+    assert!(ok);
+    assert_eq!(client.privacy_status(&account), Some(2));
+}
+
+// ============================================================================
+// Escrow ID Lookup Tests
+// ============================================================================
+
+#[test]
+fn test_get_escrow_by_id_resolves_deposits() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &(amount * 2));
+
+    let first = client.deposit(
+        &token,
+        &amount,
+        &owner,
+        &Bytes::from_slice(&env, b"id_1"),
+        &0,
+    );
+    let second = client.deposit(
+        &token,
+        &amount,
+        &owner,
+        &Bytes::from_slice(&env, b"id_2"),
+        &0,
+    );
+
+    let (commitment, view) = client.get_escrow_by_id(&1, &owner).unwrap();
+    assert_eq!(commitment, first);
+    assert_eq!(view.amount, Some(amount));
+    assert_eq!(view.status, EscrowStatus::Pending);
+
+    let (commitment, _) = client.get_escrow_by_id(&2, &owner).unwrap();
+    assert_eq!(commitment, second);
+}
+
+#[test]
+fn test_get_escrow_by_id_unknown_returns_none() {
+    let (env, client) = setup();
+    let caller = Address::generate(&env);
+    assert!(client.get_escrow_by_id(&1, &caller).is_none());
+
+    // IDs from the placeholder create_escrow are not backed by an escrow.
+    let id = client.create_escrow(&caller, &caller, &1);
+    assert!(client.get_escrow_by_id(&id, &caller).is_none());
+}
+
+#[test]
+fn test_get_escrow_by_id_respects_privacy() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+
+    client.deposit(
+        &token,
+        &amount,
+        &owner,
+        &Bytes::from_slice(&env, b"id_p"),
+        &0,
+    );
+    client.set_privacy(&owner, &true);
+
+    let (_, view) = client.get_escrow_by_id(&1, &stranger).unwrap();
+    assert_eq!(view.amount, None);
+    assert_eq!(view.owner, None);
+}
+
+// ============================================================================
+// Withdraw Token Validation Tests
+// ============================================================================
+
+#[test]
+fn test_withdraw_with_matching_token_succeeds() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"token_match_salt");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+
+    let commitment = client.deposit(&token, &amount, &owner, &salt, &0);
+    assert!(client.withdraw(&token, &amount, &commitment, &owner, &salt));
+    assert_eq!(token::Client::new(&env, &token).balance(&owner), amount);
+}
+
+#[test]
+fn test_withdraw_with_mismatched_token_fails() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let other_token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"token_mismatch_salt");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+
+    let commitment = client.deposit(&token, &amount, &owner, &salt, &0);
+    let result = client.try_withdraw(&other_token, &amount, &commitment, &owner, &salt);
+    assert_contract_error(result, QuickexError::TokenMismatch);
+    assert_eq!(
+        client.get_commitment_state(&commitment),
+        Some(EscrowStatus::Pending)
+    );
+}
+
+// ============================================================================
+// Nonced Deposit Tests
+// ============================================================================
+
+#[test]
+fn test_deposit_nonced_identical_inputs_yield_distinct_commitments() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"reused_salt");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &(amount * 2));
+
+    assert_eq!(client.owner_nonce(&owner), 0);
+    let first = client.deposit_nonced(&token, &amount, &owner, &salt, &0);
+    let second = client.deposit_nonced(&token, &amount, &owner, &salt, &0);
+    assert_ne!(first, second);
+    assert_eq!(client.owner_nonce(&owner), 2);
+
+    assert_eq!(
+        client.get_commitment_state(&first),
+        Some(EscrowStatus::Pending)
+    );
+    assert_eq!(
+        client.get_commitment_state(&second),
+        Some(EscrowStatus::Pending)
+    );
+}
+
+#[test]
+fn test_withdraw_nonced_recomputes_from_stored_nonce() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"nonced_salt");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &(amount * 2));
+
+    let first = client.deposit_nonced(&token, &amount, &owner, &salt, &0);
+    let second = client.deposit_nonced(&token, &amount, &owner, &salt, &0);
+
+    assert!(client.withdraw_nonced(&second, &amount, &owner, &salt));
+    assert_eq!(
+        client.get_commitment_state(&second),
+        Some(EscrowStatus::Spent)
+    );
+    assert_eq!(
+        client.get_commitment_state(&first),
+        Some(EscrowStatus::Pending)
+    );
+
+    let wrong_salt = Bytes::from_slice(&env, b"other_salt");
+    let result = client.try_withdraw_nonced(&first, &amount, &owner, &wrong_salt);
+    assert_contract_error(result, QuickexError::CommitmentMismatch);
+}
+
+#[test]
+fn test_nonced_and_plain_escrows_use_separate_withdraw_paths() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"path_salt");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &(amount * 2));
+
+    let nonced = client.deposit_nonced(&token, &amount, &owner, &salt, &0);
+    let plain = client.deposit(&token, &amount, &owner, &salt, &0);
+
+    let result = client.try_withdraw_nonced(&plain, &amount, &owner, &salt);
+    assert_contract_error(result, QuickexError::CommitmentMismatch);
+
+    // The nonced preimage fed through plain withdraw finds the escrow but is refused.
+    let mut nonced_salt = salt.clone();
+    nonced_salt.extend_from_array(&0u64.to_be_bytes());
+    let result = client.try_withdraw(&token, &amount, &nonced, &owner, &nonced_salt);
+    assert_contract_error(result, QuickexError::CommitmentMismatch);
+}
+
+// ============================================================================
+// Escrow TTL Tests
+// ============================================================================
+
+#[test]
+fn test_escrow_ttl_remaining_positive_after_deposit() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"ttl_salt");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+
+    let commitment = client.deposit(&token, &amount, &owner, &salt, &0);
+    let remaining = client.escrow_ttl_remaining(&commitment).unwrap();
+    assert!(remaining > 0);
+
+    // The reported value never overstates the host's actual TTL.
+    let actual = env.as_contract(&client.address, || {
+        env.storage()
+            .persistent()
+            .get_ttl(&DataKey::Escrow(commitment.clone().into()))
+    });
+    assert!(remaining <= actual);
+}
+
+#[test]
+fn test_escrow_ttl_remaining_counts_down_and_extends() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"ttl_extend_salt");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+
+    let commitment = client.deposit(&token, &amount, &owner, &salt, &0);
+    let initial = client.escrow_ttl_remaining(&commitment).unwrap();
+
+    env.ledger().with_mut(|l| l.sequence_number += 100);
+    assert_eq!(
+        client.escrow_ttl_remaining(&commitment),
+        Some(initial - 100)
+    );
+
+    client.extend_escrow_ttl(&commitment);
+    assert_eq!(client.escrow_ttl_remaining(&commitment), Some(initial));
+}
+
+#[test]
+fn test_escrow_ttl_remaining_missing_escrow() {
+    let (env, client) = setup();
+    let commitment = BytesN::from_array(&env, &[9; 32]);
+    assert_eq!(client.escrow_ttl_remaining(&commitment), None);
+    let result = client.try_extend_escrow_ttl(&commitment);
+    assert_contract_error(result, QuickexError::CommitmentNotFound);
+}
+
+// ============================================================================
+// Expired Status Refund Tests
+// ============================================================================
+
+#[test]
+fn test_refund_accepts_expired_status() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    let commitment: BytesN<32> = BytesN::from_array(&env, &[5; 32]);
+    token::StellarAssetClient::new(&env, &token).mint(&client.address, &amount);
+
+    setup_escrow_with_owner(
+        &env,
+        &client.address,
+        &token,
+        &owner,
+        amount,
+        commitment.clone(),
+        env.ledger().timestamp() + 100,
+    );
+    env.as_contract(&client.address, || {
+        let key: Bytes = commitment.clone().into();
+        let mut entry = crate::storage::get_escrow(&env, &key).unwrap();
+        entry.status = EscrowStatus::Expired;
+        put_escrow(&env, &key, &entry);
+    });
+    env.ledger().set_timestamp(env.ledger().timestamp() + 100);
+
+    assert_eq!(client.preview_refund(&commitment, &owner), Some(amount));
+    client.refund(&commitment, &owner);
+
+    assert_eq!(token::Client::new(&env, &token).balance(&owner), amount);
+    assert_eq!(
+        client.get_commitment_state(&commitment),
+        Some(EscrowStatus::Refunded)
+    );
+    assert_eq!(client.escrow_count_by_token(&token), 0);
+}
+
+// ============================================================================
+// Bulk Status Query Tests
+// ============================================================================
+
+#[test]
+fn test_get_states_is_positional() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &(amount * 3));
+
+    let spent_salt = Bytes::from_slice(&env, b"states_spent");
+    let pending = client.deposit(
+        &token,
+        &amount,
+        &owner,
+        &Bytes::from_slice(&env, b"states_pending"),
+        &0,
+    );
+    let spent = client.deposit(&token, &amount, &owner, &spent_salt, &0);
+    let lapsed = client.deposit(
+        &token,
+        &amount,
+        &owner,
+        &Bytes::from_slice(&env, b"states_lapsed"),
+        &10,
+    );
+    client.withdraw(&token, &amount, &spent, &owner, &spent_salt);
+    env.ledger().set_timestamp(env.ledger().timestamp() + 10);
+    let missing = BytesN::from_array(&env, &[3; 32]);
+
+    let states = client.get_states(&soroban_sdk::vec![
+        &env,
+        pending,
+        missing.clone(),
+        spent,
+        lapsed,
+        missing
+    ]);
+    assert_eq!(states.len(), 5);
+    assert_eq!(states.get(0).unwrap(), Some(EscrowStatus::Pending));
+    assert_eq!(states.get(1).unwrap(), None);
+    assert_eq!(states.get(2).unwrap(), Some(EscrowStatus::Spent));
+    assert_eq!(states.get(3).unwrap(), Some(EscrowStatus::Expired));
+    assert_eq!(states.get(4).unwrap(), None);
+}
+
+#[test]
+fn test_get_states_rejects_oversized_batch() {
+    let (env, client) = setup();
+    let mut commitments = soroban_sdk::Vec::new(&env);
+    for i in 0..=crate::escrow::MAX_BATCH_SIZE {
+        commitments.push_back(BytesN::from_array(&env, &[i as u8; 32]));
+    }
+    let result = client.try_get_states(&commitments);
+    assert_contract_error(result, QuickexError::BatchTooLarge);
+}
+
+// ============================================================================
+// Minimum Confirmation Tests
+// ============================================================================
+
+#[test]
+fn test_withdraw_waits_for_min_confirmations() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"confirm_salt");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+
+    let options = EscrowOptions {
+        min_confirmations: 5,
+        ..Default::default()
+    };
+    let commitment = client.deposit_with_options(&token, &amount, &owner, &salt, &0, &options);
+
+    env.ledger().with_mut(|l| l.sequence_number += 4);
+    let result = client.try_withdraw(&token, &amount, &commitment, &owner, &salt);
+    assert_contract_error(result, QuickexError::InsufficientConfirmations);
+
+    env.ledger().with_mut(|l| l.sequence_number += 1);
+    assert!(client.withdraw(&token, &amount, &commitment, &owner, &salt));
+}
+
+#[test]
+fn test_withdraw_without_min_confirmations_allowed_same_ledger() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"no_confirm_salt");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+
+    let commitment = client.deposit(&token, &amount, &owner, &salt, &0);
+    assert!(client.withdraw(&token, &amount, &commitment, &owner, &salt));
+}
+
+// ============================================================================
+// Privacy Preview Tests
+// ============================================================================
+
+#[test]
+fn test_set_privacy_and_preview_returns_redacted_views() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &(amount * 3));
+
+    let spent_salt = Bytes::from_slice(&env, b"preview_spent");
+    client.deposit(
+        &token,
+        &amount,
+        &owner,
+        &Bytes::from_slice(&env, b"preview_a"),
+        &0,
+    );
+    client.deposit(
+        &token,
+        &amount,
+        &owner,
+        &Bytes::from_slice(&env, b"preview_b"),
+        &0,
+    );
+    let spent = client.deposit(&token, &amount, &owner, &spent_salt, &0);
+    client.withdraw(&token, &amount, &spent, &owner, &spent_salt);
+
+    let views = client.set_privacy_and_preview(&owner, &true);
+    assert!(client.get_privacy(&owner));
+    // Only active escrows are listed.
+    assert_eq!(views.len(), 2);
+    for view in views.iter() {
+        assert_eq!(view.amount, None);
+        assert_eq!(view.owner, None);
+        assert_eq!(view.token, Some(token.clone()));
+    }
+
+    let views = client.set_privacy_and_preview(&owner, &false);
+    assert_eq!(views.get(0).unwrap().amount, Some(amount));
+}
+
+#[test]
+fn test_set_privacy_and_preview_propagates_already_set() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+    client.set_privacy(&owner, &true);
+    let result = client.try_set_privacy_and_preview(&owner, &true);
+    assert_contract_error(result, QuickexError::PrivacyAlreadySet);
+}
+
+// ============================================================================
+// Expiry Forward Tests
+// ============================================================================
+
+#[test]
+fn test_sweep_expired_forward_pays_designated_recipient() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"forward_salt");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+
+    let options = EscrowOptions {
+        on_expiry_recipient: Some(treasury.clone()),
+        ..Default::default()
+    };
+    let commitment = client.deposit_with_options(&token, &amount, &owner, &salt, &100, &options);
+
+    let result = client.try_sweep_expired_forward(&commitment);
+    assert_contract_error(result, QuickexError::EscrowNotExpired);
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + 100);
+    // The owner can no longer take the funds back.
+    let result = client.try_refund(&commitment, &owner);
+    assert_contract_error(result, QuickexError::RefundDisabled);
+    assert_eq!(client.preview_refund(&commitment, &owner), None);
+
+    // Permissionless: no address authorizes the forward.
+    client.sweep_expired_forward(&commitment);
+    assert!(env.auths().is_empty());
+
+    let token_client = token::Client::new(&env, &token);
+    assert_eq!(token_client.balance(&treasury), amount);
+    assert_eq!(token_client.balance(&owner), 0);
+    assert_eq!(
+        client.get_commitment_state(&commitment),
+        Some(EscrowStatus::Refunded)
+    );
+}
+
+#[test]
+fn test_sweep_expired_forward_requires_recipient() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"no_forward_salt");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+
+    let commitment = client.deposit(&token, &amount, &owner, &salt, &100);
+    env.ledger().set_timestamp(env.ledger().timestamp() + 100);
+
+    let result = client.try_sweep_expired_forward(&commitment);
+    assert_contract_error(result, QuickexError::NoExpiryRecipient);
+    client.refund(&commitment, &owner);
+}
+
+// ============================================================================
+// Merkle Inclusion Tests
+// ============================================================================
+
+#[test]
+fn test_verify_merkle_inclusion() {
+    let (env, client) = setup();
+    let leaves: [BytesN<32>; 4] =
+        core::array::from_fn(|i| BytesN::from_array(&env, &[i as u8 + 1; 32]));
+    let n01 = crate::merkle::hash_pair(&env, &leaves[0], &leaves[1]);
+    let n23 = crate::merkle::hash_pair(&env, &leaves[2], &leaves[3]);
+    let root = crate::merkle::hash_pair(&env, &n01, &n23);
+
+    let proof = soroban_sdk::vec![&env, leaves[3].clone(), n01.clone()];
+    assert!(client.verify_merkle_inclusion(&root, &leaves[2], &proof));
+
+    // Tampered sibling
+    let tampered = soroban_sdk::vec![&env, leaves[0].clone(), n01];
+    assert!(!client.verify_merkle_inclusion(&root, &leaves[2], &tampered));
+
+    // Right proof, wrong leaf
+    assert!(!client.verify_merkle_inclusion(&root, &leaves[1], &proof));
+}
+
+// ============================================================================
+// Installment Escrow Tests
+// ============================================================================
+
+#[test]
+fn test_withdraw_matured_releases_installments_one_at_a_time() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let beneficiary = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"vesting_salt");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+
+    let start = env.ledger().timestamp();
+    let options = EscrowOptions {
+        allowed_recipient: Some(beneficiary.clone()),
+        installments: Some(soroban_sdk::vec![
+            &env,
+            start + 100,
+            start + 200,
+            start + 300
+        ]),
+        ..Default::default()
+    };
+    let commitment = client.deposit_with_options(&token, &amount, &owner, &salt, &0, &options);
+    let token_client = token::Client::new(&env, &token);
+
+    let result = client.try_withdraw_matured(&commitment, &beneficiary);
+    assert_contract_error(result, QuickexError::NoMaturedInstallments);
+
+    env.ledger().set_timestamp(start + 100);
+    assert_eq!(client.withdraw_matured(&commitment, &beneficiary), 333);
+    assert_eq!(token_client.balance(&beneficiary), 333);
+
+    // Nothing new has unlocked since the last claim.
+    let result = client.try_withdraw_matured(&commitment, &beneficiary);
+    assert_contract_error(result, QuickexError::NoMaturedInstallments);
+
+    env.ledger().set_timestamp(start + 200);
+    assert_eq!(client.withdraw_matured(&commitment, &beneficiary), 333);
+    assert_eq!(
+        client.get_commitment_state(&commitment),
+        Some(EscrowStatus::Pending)
+    );
+
+    // The final installment carries the rounding remainder.
+    env.ledger().set_timestamp(start + 300);
+    assert_eq!(client.withdraw_matured(&commitment, &beneficiary), 334);
+    assert_eq!(token_client.balance(&beneficiary), amount);
+    assert_eq!(
+        client.get_commitment_state(&commitment),
+        Some(EscrowStatus::Spent)
+    );
+}
+
+#[test]
+fn test_withdraw_matured_releases_several_at_once_to_owner() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let amount: i128 = 900;
+    let salt = Bytes::from_slice(&env, b"vesting_owner_salt");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+
+    let start = env.ledger().timestamp();
+    let options = EscrowOptions {
+        installments: Some(soroban_sdk::vec![&env, start + 10, start + 20, start + 30]),
+        ..Default::default()
+    };
+    let commitment = client.deposit_with_options(&token, &amount, &owner, &salt, &0, &options);
+
+    env.ledger().set_timestamp(start + 25);
+    let result = client.try_withdraw_matured(&commitment, &stranger);
+    assert_contract_error(result, QuickexError::RecipientNotAllowed);
+    assert_eq!(client.withdraw_matured(&commitment, &owner), 600);
+
+    // The plain withdraw path cannot bypass the schedule.
+    let result = client.try_withdraw(&token, &300, &commitment, &owner, &salt);
+    assert_contract_error(result, QuickexError::CommitmentNotFound);
+    let result = client.try_withdraw(&token, &amount, &commitment, &owner, &salt);
+    assert_contract_error(result, QuickexError::CommitmentMismatch);
+}
+
+#[test]
+fn test_deposit_rejects_invalid_installment_schedule() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+
+    for schedule in [
+        soroban_sdk::Vec::new(&env),
+        soroban_sdk::vec![&env, 200u64, 100u64],
+        soroban_sdk::vec![&env, 100u64, 100u64],
+    ] {
+        let options = EscrowOptions {
+            installments: Some(schedule),
+            ..Default::default()
+        };
+        let salt = Bytes::from_slice(&env, b"bad_schedule");
+        let result = client.try_deposit_with_options(&token, &amount, &owner, &salt, &0, &options);
+        assert_contract_error(result, QuickexError::InvalidSchedule);
+    }
+}
+
+// ============================================================================
+// Status Count Tests
+// ============================================================================
+
+#[test]
+fn test_status_counts_track_transitions() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &(amount * 3));
+
+    assert_eq!(client.status_counts(), StatusCounts::default());
+
+    let spent_salt = Bytes::from_slice(&env, b"counts_spent");
+    let spent = client.deposit(&token, &amount, &owner, &spent_salt, &0);
+    let refunded = client.deposit(
+        &token,
+        &amount,
+        &owner,
+        &Bytes::from_slice(&env, b"counts_refund"),
+        &10,
+    );
+    client.deposit(
+        &token,
+        &amount,
+        &owner,
+        &Bytes::from_slice(&env, b"counts_pending"),
+        &0,
+    );
+    assert_eq!(client.status_counts().pending, 3);
+
+    client.withdraw(&token, &amount, &spent, &owner, &spent_salt);
+    env.ledger().set_timestamp(env.ledger().timestamp() + 10);
+    client.refund(&refunded, &owner);
+
+    assert_eq!(
+        client.status_counts(),
+        StatusCounts {
+            pending: 1,
+            spent: 1,
+            refunded: 1,
+            expired: 0,
+        }
+    );
+}
+
+// ============================================================================
+// Withdrawal Fee Tests
+// ============================================================================
+
+fn setup_fee_escrow(
+    env: &Env,
+    client: &QuickexContractClient,
+    token: &Address,
+    owner: &Address,
+    amount: i128,
+    salt: &Bytes,
+) -> BytesN<32> {
+    token::StellarAssetClient::new(env, token).mint(owner, &amount);
+    client.deposit(token, &amount, owner, salt, &0)
+}
+
+#[test]
+fn test_withdrawal_fee_discount_for_private_recipient() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_withdrawal_fees(&admin, &100, &Some(25));
+
+    let token = create_test_token(&env);
+    let token_client = token::Client::new(&env, &token);
+    let public_user = Address::generate(&env);
+    let private_user = Address::generate(&env);
+    let amount: i128 = 10_000;
+    client.set_privacy(&private_user, &true);
+
+    let salt = Bytes::from_slice(&env, b"fee_public");
+    let commitment = setup_fee_escrow(&env, &client, &token, &public_user, amount, &salt);
+    client.withdraw(&token, &amount, &commitment, &public_user, &salt);
+    assert_eq!(token_client.balance(&public_user), 9_900);
+    assert_eq!(token_client.balance(&admin), 100);
+
+    let salt = Bytes::from_slice(&env, b"fee_private");
+    let commitment = setup_fee_escrow(&env, &client, &token, &private_user, amount, &salt);
+    client.withdraw(&token, &amount, &commitment, &private_user, &salt);
+    assert_eq!(token_client.balance(&private_user), 9_975);
+    assert_eq!(token_client.balance(&admin), 125);
+}
+
+#[test]
+fn test_withdrawal_fee_private_rate_defaults_to_normal() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_withdrawal_fees(&admin, &100, &None);
+
+    let token = create_test_token(&env);
+    let private_user = Address::generate(&env);
+    let amount: i128 = 10_000;
+    client.set_privacy(&private_user, &true);
+
+    let salt = Bytes::from_slice(&env, b"fee_default");
+    let commitment = setup_fee_escrow(&env, &client, &token, &private_user, amount, &salt);
+    client.withdraw(&token, &amount, &commitment, &private_user, &salt);
+    assert_eq!(
+        token::Client::new(&env, &token).balance(&private_user),
+        9_900
+    );
+}
+
+#[test]
+fn test_set_withdrawal_fees_validation() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    client.initialize(&admin);
+
+    let result = client.try_set_withdrawal_fees(&stranger, &100, &None);
+    assert_contract_error(result, QuickexError::Unauthorized);
+    let result = client.try_set_withdrawal_fees(&admin, &10_001, &None);
+    assert_contract_error(result, QuickexError::InvalidFee);
+    let result = client.try_set_withdrawal_fees(&admin, &100, &Some(10_001));
+    assert_contract_error(result, QuickexError::InvalidFee);
+    assert_eq!(client.get_fee_config().fee_bps, 0);
+}
+
+// ============================================================================
+// Privacy History Dedup Tests
+// ============================================================================
+
+#[test]
+fn test_privacy_history_keeps_duplicates_by_default() {
+    let (env, client) = setup();
+    let account = Address::generate(&env);
+
+    client.enable_privacy(&account, &2);
+    client.enable_privacy(&account, &2);
+
+    assert!(!client.is_privacy_history_dedup());
+    assert_eq!(client.privacy_history(&account).len(), 2);
+}
+
+#[test]
+fn test_privacy_history_dedup_skips_repeated_level() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let account = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_privacy_history_dedup(&admin, &true);
+
+    client.enable_privacy(&account, &2);
+    client.enable_privacy(&account, &2);
+    client.enable_privacy(&account, &3);
+    client.enable_privacy(&account, &2);
+
+    let history = client.privacy_history(&account);
+    assert_eq!(history.len(), 3);
+    assert_eq!(client.privacy_status(&account), Some(2));
+}
+
+#[test]
+fn test_set_privacy_history_dedup_requires_admin() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    client.initialize(&admin);
+
+    let result = client.try_set_privacy_history_dedup(&stranger, &true);
+    assert_contract_error(result, QuickexError::Unauthorized);
+}
+
+// ============================================================================
+// Cross-contract Escrow Creation Tests
+// ============================================================================
+
+mod marketplace {
+    use super::QuickexContractClient;
+    use soroban_sdk::{
+        auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
+        contract, contractimpl, vec, Address, Bytes, BytesN, Env, IntoVal, Symbol,
+    };
+
+    /// Minimal caller contract that opens QuickEx escrows for its buyers.
+    #[contract]
+    pub struct Marketplace;
+
+    #[contractimpl]
+    impl Marketplace {
+        /// Pay `seller` from the marketplace's own balance.
+        pub fn settle_from_treasury(
+            env: Env,
+            quickex: Address,
+            token: Address,
+            amount: i128,
+            seller: Address,
+            salt: Bytes,
+        ) -> BytesN<32> {
+            let payer = env.current_contract_address();
+            // QuickEx, not this contract, invokes the token transfer, so it must be
+            // pre-authorized as a sub-invocation.
+            env.authorize_as_current_contract(vec![
+                &env,
+                InvokerContractAuthEntry::Contract(SubContractInvocation {
+                    context: ContractContext {
+                        contract: token.clone(),
+                        fn_name: Symbol::new(&env, "transfer"),
+                        args: (payer.clone(), quickex.clone(), amount).into_val(&env),
+                    },
+                    sub_invocations: vec![&env],
+                }),
+            ]);
+            QuickexContractClient::new(&env, &quickex)
+                .create_escrow_cpi(&payer, &token, &amount, &seller, &salt, &0)
+        }
+
+        /// Pay `seller` from `buyer`'s balance; `buyer` authorizes the whole tree.
+        pub fn buy(
+            env: Env,
+            quickex: Address,
+            buyer: Address,
+            token: Address,
+            amount: i128,
+            seller: Address,
+            salt: Bytes,
+        ) -> BytesN<32> {
+            buyer.require_auth();
+            QuickexContractClient::new(&env, &quickex)
+                .create_escrow_cpi(&buyer, &token, &amount, &seller, &salt, &0)
+        }
+    }
+}
+
+#[test]
+fn test_create_escrow_cpi_from_caller_contract_balance() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let seller = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"cpi_treasury_salt");
+    let market_id = env.register(marketplace::Marketplace, ());
+    let market = marketplace::MarketplaceClient::new(&env, &market_id);
+    token::StellarAssetClient::new(&env, &token).mint(&market_id, &amount);
+
+    // No mocked auths: the marketplace is implicitly authorized as the direct caller.
+    env.set_auths(&[]);
+    let commitment = market.settle_from_treasury(&client.address, &token, &amount, &seller, &salt);
+
+    env.mock_all_auths();
+    client.withdraw(&token, &amount, &commitment, &seller, &salt);
+    let token_client = token::Client::new(&env, &token);
+    assert_eq!(token_client.balance(&seller), amount);
+    assert_eq!(token_client.balance(&market_id), 0);
+}
+
+#[test]
+fn test_create_escrow_cpi_with_user_payer() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let buyer = Address::generate(&env);
+    let seller = Address::generate(&env);
+    let amount: i128 = 1000;
+    let salt = Bytes::from_slice(&env, b"cpi_buyer_salt");
+    let market_id = env.register(marketplace::Marketplace, ());
+    let market = marketplace::MarketplaceClient::new(&env, &market_id);
+    token::StellarAssetClient::new(&env, &token).mint(&buyer, &amount);
+
+    let commitment = market.buy(&client.address, &buyer, &token, &amount, &seller, &salt);
+
+    // The buyer's single authorization covers the marketplace call, the escrow
+    // creation, and the token transfer beneath it.
+    let auths = env.auths();
+    assert_eq!(auths.len(), 1);
+    assert_eq!(auths[0].0, buyer);
+
+    assert_eq!(
+        client
+            .get_escrow_details(&commitment, &buyer)
+            .unwrap()
+            .owner,
+        Some(buyer.clone())
+    );
+    client.withdraw(&token, &amount, &commitment, &seller, &salt);
+    assert_eq!(token::Client::new(&env, &token).balance(&seller), amount);
+}
+
+// ============================================================================
+// Owner Linkage Tests
+// ============================================================================
+
+#[test]
+fn test_same_owner_reports_equality_only() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let auditor = Address::generate(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&alice, &(amount * 2));
+    token::StellarAssetClient::new(&env, &token).mint(&bob, &amount);
+
+    let a1 = client.deposit(
+        &token,
+        &amount,
+        &alice,
+        &Bytes::from_slice(&env, b"link_a1"),
+        &0,
+    );
+    let a2 = client.deposit(
+        &token,
+        &amount,
+        &alice,
+        &Bytes::from_slice(&env, b"link_a2"),
+        &0,
+    );
+    let b1 = client.deposit(
+        &token,
+        &amount,
+        &bob,
+        &Bytes::from_slice(&env, b"link_b1"),
+        &0,
+    );
+    let missing = BytesN::from_array(&env, &[4; 32]);
+
+    assert_eq!(client.same_owner(&a1, &a2, &auditor), Some(true));
+    assert_eq!(client.same_owner(&a1, &b1, &auditor), Some(false));
+    assert_eq!(client.same_owner(&a1, &missing, &auditor), None);
+}
+
+#[test]
+fn test_same_owner_respects_privacy() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let alice = Address::generate(&env);
+    let auditor = Address::generate(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&alice, &(amount * 2));
+
+    let a1 = client.deposit(
+        &token,
+        &amount,
+        &alice,
+        &Bytes::from_slice(&env, b"link_p1"),
+        &0,
+    );
+    let a2 = client.deposit(
+        &token,
+        &amount,
+        &alice,
+        &Bytes::from_slice(&env, b"link_p2"),
+        &0,
+    );
+    client.set_privacy(&alice, &true);
+
+    assert_eq!(client.same_owner(&a1, &a2, &auditor), None);
+    assert_eq!(client.same_owner(&a1, &a2, &alice), Some(true));
+
+    client.grant_viewer(&alice, &auditor);
+    assert_eq!(client.same_owner(&a1, &a2, &auditor), Some(true));
+}
+
+// ============================================================================
+// Auto-Pause Tests
+// ============================================================================
+
+#[test]
+fn test_solvency_check_tracks_escrow_lifecycle() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+    let salt = Bytes::from_slice(&env, b"solvency");
+
+    let commitment = client.deposit(&token, &amount, &owner, &salt, &0);
+    assert_eq!(client.solvency_check(&token), 0);
+    env.as_contract(&client.address, || {
+        let tvl: i128 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::TokenTvl(token.clone()))
+            .unwrap();
+        assert_eq!(tvl, amount);
+    });
+
+    assert!(client.withdraw(&token, &amount, &commitment, &owner, &salt));
+    assert_eq!(client.solvency_check(&token), 0);
+}
+
+#[test]
+fn test_auto_pause_trips_on_deficit() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_auto_pause(&admin, &true);
+    assert!(client.is_auto_pause());
+
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+    let salt = Bytes::from_slice(&env, b"auto_pause");
+    let commitment = client.deposit(&token, &amount, &owner, &salt, &0);
+
+    // Simulate an accounting bug: the books claim more than the contract holds.
+    env.as_contract(&client.address, || {
+        env.storage()
+            .persistent()
+            .set(&DataKey::TokenTvl(token.clone()), &(amount + 1));
+    });
+    assert_eq!(client.solvency_check(&token), -1);
+
+    assert!(!client.withdraw(&token, &amount, &commitment, &owner, &salt));
+    assert!(client.is_paused());
+    let states = client.get_states(&soroban_sdk::vec![&env, commitment.clone()]);
+    assert_eq!(states.get(0).unwrap(), Some(EscrowStatus::Pending));
+    assert_eq!(token::Client::new(&env, &token).balance(&owner), 0);
+
+    let result = client.try_withdraw(&token, &amount, &commitment, &owner, &salt);
+    assert_contract_error(result, QuickexError::ContractPaused);
+}
+
+#[test]
+fn test_auto_pause_disabled_by_default() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+    let salt = Bytes::from_slice(&env, b"no_auto_pause");
+    let commitment = client.deposit(&token, &amount, &owner, &salt, &0);
+
+    env.as_contract(&client.address, || {
+        env.storage()
+            .persistent()
+            .set(&DataKey::TokenTvl(token.clone()), &(amount + 1));
+    });
+
+    assert!(client.withdraw(&token, &amount, &commitment, &owner, &salt));
+    assert!(!client.is_paused());
+}
+
+// ============================================================================
+// Account Export Tests
+// ============================================================================
+
+#[test]
+fn test_export_account_state_reflects_current_state() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let viewer = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &(amount * 2));
+
+    let empty = client.export_account_state(&owner);
+    assert!(!empty.privacy_enabled);
+    assert_eq!(empty.privacy_level, None);
+    assert!(empty.active_escrows.is_empty());
+
+    client.set_privacy(&owner, &true);
+    client.enable_privacy(&owner, &2);
+    client.grant_viewer(&owner, &viewer);
+    let kept = client.deposit(
+        &token,
+        &amount,
+        &owner,
+        &Bytes::from_slice(&env, b"kept"),
+        &0,
+    );
+    let spent_salt = Bytes::from_slice(&env, b"spent");
+    let spent = client.deposit(&token, &amount, &owner, &spent_salt, &0);
+    client.withdraw(&token, &amount, &spent, &owner, &spent_salt);
+
+    let export = client.export_account_state(&owner);
+    assert_eq!(
+        export,
+        AccountExport {
+            owner: owner.clone(),
+            privacy_enabled: true,
+            privacy_level: Some(2),
+            privacy_history: soroban_sdk::vec![&env, 2],
+            viewers: soroban_sdk::vec![&env, viewer],
+            active_escrows: soroban_sdk::vec![&env, kept],
+        }
+    );
+    assert!(client.export_account_state(&recipient).viewers.is_empty());
+}
+
+#[test]
+fn test_export_account_state_requires_owner_auth() {
+    let env = Env::default();
+    let contract_id = env.register(QuickexContract, ());
+    let client = QuickexContractClient::new(&env, &contract_id);
+    let owner = Address::generate(&env);
+
+    assert!(client.try_export_account_state(&owner).is_err());
+}
+
+// ============================================================================
+// Gift Deposit Tests
+// ============================================================================
+
+#[test]
+fn test_gift_recipient_withdraws_with_secret() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let giver = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&giver, &amount);
+    let secret = Bytes::from_slice(&env, b"recipient_secret");
+
+    let commitment = client.create_amount_commitment(&recipient, &amount, &secret);
+    client.gift_deposit(&giver, &token, &amount, &commitment);
+
+    // The giver cannot withdraw: the commitment binds the recipient.
+    let result = client.try_withdraw(&token, &amount, &commitment, &giver, &secret);
+    assert_contract_error(result, QuickexError::CommitmentNotFound);
+
+    assert!(client.withdraw(&token, &amount, &commitment, &recipient, &secret));
+    let token_client = token::Client::new(&env, &token);
+    assert_eq!(token_client.balance(&recipient), amount);
+    assert_eq!(token_client.balance(&giver), 0);
+}
+
+#[test]
+fn test_gift_refunds_to_giver_after_expiry() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let giver = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&giver, &amount);
+    let secret = Bytes::from_slice(&env, b"unclaimed_secret");
+
+    let commitment = client.create_amount_commitment(&recipient, &amount, &secret);
+    client.gift_deposit(&giver, &token, &amount, &commitment);
+
+    let result = client.try_refund(&commitment, &giver);
+    assert_contract_error(result, QuickexError::EscrowNotExpired);
+
+    env.ledger()
+        .with_mut(|li| li.timestamp += GIFT_TIMEOUT_SECS);
+    client.refund(&commitment, &giver);
+    assert_eq!(token::Client::new(&env, &token).balance(&giver), amount);
+
+    let result = client.try_withdraw(&token, &amount, &commitment, &recipient, &secret);
+    assert_contract_error(result, QuickexError::AlreadySpent);
+}
+
+// ============================================================================
+// Max Withdrawable Tests
+// ============================================================================
+
+#[test]
+fn test_max_withdrawable_reports_reduced_amount_when_underfunded() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let drain = Address::generate(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+    let salt = Bytes::from_slice(&env, b"max_withdrawable");
+    let commitment = client.deposit(&token, &amount, &owner, &salt, &0);
+
+    assert_eq!(client.max_withdrawable(&commitment, &owner), amount);
+
+    env.as_contract(&client.address, || {
+        token::Client::new(&env, &token).transfer(&client.address, &drain, &400);
+    });
+    assert_eq!(client.max_withdrawable(&commitment, &owner), 600);
+}
+
+#[test]
+fn test_max_withdrawable_zero_for_missing_or_terminal() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &(amount * 2));
+    let salt = Bytes::from_slice(&env, b"max_terminal");
+    let spent = client.deposit(&token, &amount, &owner, &salt, &0);
+    let expiring = client.deposit(
+        &token,
+        &amount,
+        &owner,
+        &Bytes::from_slice(&env, b"exp"),
+        &10,
+    );
+    client.withdraw(&token, &amount, &spent, &owner, &salt);
+
+    assert_eq!(
+        client.max_withdrawable(&BytesN::from_array(&env, &[9; 32]), &owner),
+        0
+    );
+    assert_eq!(client.max_withdrawable(&spent, &owner), 0);
+    assert_eq!(client.max_withdrawable(&expiring, &owner), amount);
+
+    env.ledger().with_mut(|li| li.timestamp += 10);
+    assert_eq!(client.max_withdrawable(&expiring, &owner), 0);
+}
+
+// ============================================================================
+// Gated Operation Tests
+// ============================================================================
+
+#[test]
+fn test_gated_deposit_blocks_non_admin() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    client.initialize(&admin);
+    let token = create_test_token(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&user, &(amount * 2));
+    token::StellarAssetClient::new(&env, &token).mint(&admin, &amount);
+    let deposit_op = Symbol::new(&env, "deposit");
+
+    assert!(client.gated_operations().is_empty());
+    client.set_operation_gated(&admin, &deposit_op, &true);
+    assert_eq!(
+        client.gated_operations(),
+        soroban_sdk::vec![&env, deposit_op.clone()]
+    );
+
+    let salt = Bytes::from_slice(&env, b"gated");
+    let result = client.try_deposit(&token, &amount, &user, &salt, &0);
+    assert_contract_error(result, QuickexError::Unauthorized);
+
+    let commitment = client.create_amount_commitment(&user, &amount, &salt);
+    let result = client.try_deposit_with_commitment(&user, &token, &amount, &commitment, &0);
+    assert_contract_error(result, QuickexError::Unauthorized);
+
+    client.deposit(&token, &amount, &admin, &salt, &0);
+
+    client.set_operation_gated(&admin, &deposit_op, &false);
+    assert!(client.gated_operations().is_empty());
+    client.deposit(&token, &amount, &user, &salt, &0);
+}
+
+#[test]
+fn test_set_operation_gated_requires_admin() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    client.initialize(&admin);
+
+    let result = client.try_set_operation_gated(&user, &Symbol::new(&env, "deposit"), &true);
+    assert_contract_error(result, QuickexError::Unauthorized);
+}
+
+// ============================================================================
+// Oldest Active Escrow Tests
+// ============================================================================
+
+#[test]
+fn test_oldest_active_escrow_skips_terminal() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &(amount * 3));
+
+    assert_eq!(client.oldest_active_escrow(&owner, &owner), None);
+
+    let first_salt = Bytes::from_slice(&env, b"oldest_1");
+    let first = client.deposit(&token, &amount, &owner, &first_salt, &0);
+    env.ledger().with_mut(|li| li.timestamp += 100);
+    let second = client.deposit(
+        &token,
+        &amount,
+        &owner,
+        &Bytes::from_slice(&env, b"oldest_2"),
+        &0,
+    );
+    env.ledger().with_mut(|li| li.timestamp += 100);
+    client.deposit(
+        &token,
+        &amount,
+        &owner,
+        &Bytes::from_slice(&env, b"oldest_3"),
+        &0,
+    );
+
+    assert_eq!(
+        client.oldest_active_escrow(&owner, &owner),
+        Some(first.clone())
+    );
+
+    client.withdraw(&token, &amount, &first, &owner, &first_salt);
+    assert_eq!(client.oldest_active_escrow(&owner, &owner), Some(second));
+}
+
+#[test]
+fn test_oldest_active_escrow_respects_privacy() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+    let commitment = client.deposit(&token, &amount, &owner, &Bytes::from_slice(&env, b"o"), &0);
+
+    assert_eq!(
+        client.oldest_active_escrow(&owner, &stranger),
+        Some(commitment.clone())
+    );
+
+    client.set_privacy(&owner, &true);
+    assert_eq!(client.oldest_active_escrow(&owner, &stranger), None);
+    assert_eq!(
+        client.oldest_active_escrow(&owner, &owner),
+        Some(commitment)
+    );
+}
+
+// ============================================================================
+// Non-Existence Attestation Tests
+// ============================================================================
+
+#[test]
+fn test_attest_no_escrow_for_absent_commitment() {
+    let (env, client) = setup();
+    env.ledger().with_mut(|li| li.sequence_number = 42);
+    let commitment = BytesN::from_array(&env, &[5; 32]);
+
+    let attestation = client.attest_no_escrow(&commitment);
+    assert_eq!(attestation.commitment, commitment);
+    assert_eq!(attestation.ledger, 42);
+    assert!(!attestation.exists);
+}
+
+#[test]
+fn test_attest_no_escrow_for_existing_commitment() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+    let salt = Bytes::from_slice(&env, b"attest");
+    let commitment = client.deposit(&token, &amount, &owner, &salt, &0);
+
+    assert!(client.attest_no_escrow(&commitment).exists);
+
+    // Spent escrows still exist.
+    client.withdraw(&token, &amount, &commitment, &owner, &salt);
+    assert!(client.attest_no_escrow(&commitment).exists);
+}
+
+// ============================================================================
+// Scaled Amount Tests
+// ============================================================================
+
+#[test]
+fn test_escrow_amount_scaled_reports_token_decimals() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let amount: i128 = 12_345_678;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+    let commitment = client.deposit(
+        &token,
+        &amount,
+        &owner,
+        &Bytes::from_slice(&env, b"dec"),
+        &0,
+    );
+
+    let decimals = token::Client::new(&env, &token).decimals();
+    assert_eq!(
+        client.escrow_amount_scaled(&commitment, &stranger),
+        Some((amount, decimals))
+    );
+    assert_eq!(
+        client.escrow_amount_scaled(&BytesN::from_array(&env, &[6; 32]), &owner),
+        None
+    );
+
+    client.set_privacy(&owner, &true);
+    assert_eq!(client.escrow_amount_scaled(&commitment, &stranger), None);
+    assert_eq!(
+        client.escrow_amount_scaled(&commitment, &owner),
+        Some((amount, decimals))
+    );
+}
+
+// ============================================================================
+// Force Expire Tests
+// ============================================================================
+
+#[test]
+fn test_force_expire_unlocks_owner_refund_only() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    client.initialize(&admin);
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let token = create_test_token(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+    let commitment = client.deposit(&token, &amount, &owner, &Bytes::from_slice(&env, b"fx"), &0);
+
+    let result = client.try_refund(&commitment, &owner);
+    assert_contract_error(result, QuickexError::EscrowNotExpired);
+
+    client.force_expire(&admin, &commitment);
+
+    let result = client.try_refund(&commitment, &stranger);
+    assert_contract_error(result, QuickexError::InvalidOwner);
+
+    client.refund(&commitment, &owner);
+    assert_eq!(token::Client::new(&env, &token).balance(&owner), amount);
+
+    let result = client.try_force_expire(&admin, &commitment);
+    assert_contract_error(result, QuickexError::AlreadySpent);
+}
+
+#[test]
+fn test_force_expire_requires_admin() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+    client.initialize(&admin);
+    let token = create_test_token(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+    let commitment = client.deposit(&token, &amount, &owner, &Bytes::from_slice(&env, b"fx"), &0);
+
+    let result = client.try_force_expire(&owner, &commitment);
+    assert_contract_error(result, QuickexError::Unauthorized);
+}
+
+// ============================================================================
+// Purge Tests
+// ============================================================================
+
+#[test]
+fn test_purge_keeps_counters_consistent_across_lifecycle() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+    client.initialize(&admin);
+    let token = create_test_token(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &(amount * 2));
+
+    let salt = Bytes::from_slice(&env, b"purge_spent");
+    let spent = client.deposit(&token, &amount, &owner, &salt, &0);
+    let pending = client.deposit(
+        &token,
+        &amount,
+        &owner,
+        &Bytes::from_slice(&env, b"purge_p"),
+        &0,
+    );
+    assert_eq!(client.escrow_count_by_token(&token), 2);
+
+    client.withdraw(&token, &amount, &spent, &owner, &salt);
+    assert_eq!(client.escrow_count_by_token(&token), 1);
+    let counts_before = client.status_counts();
+
+    client.purge_escrow(&admin, &spent);
+    assert_eq!(client.escrow_count_by_token(&token), 1);
+    assert_eq!(client.status_counts(), counts_before);
+    assert!(!client.attest_no_escrow(&spent).exists);
+    assert!(client.get_escrow_by_id(&1, &owner).is_none());
+
+    let result = client.try_purge_escrow(&admin, &spent);
+    assert_contract_error(result, QuickexError::CommitmentNotFound);
+
+    let result = client.try_purge_escrow(&admin, &pending);
+    assert_contract_error(result, QuickexError::EscrowNotTerminal);
+
+    // A fresh deposit under the purged commitment starts a clean lifecycle.
+    client.deposit(&token, &amount, &owner, &salt, &0);
+    assert_eq!(client.escrow_count_by_token(&token), 2);
+    client.withdraw(&token, &amount, &spent, &owner, &salt);
+    assert_eq!(client.escrow_count_by_token(&token), 1);
+}
+
+#[test]
+fn test_purge_then_redeposit_retires_old_escrow_id() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let token = create_test_token(&env);
+    let from = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"purge_cycle");
+    let commitment = spent_commitment_escrow(&env, &client, &token, &from, &recipient, &salt);
+
+    client.purge_escrow(&admin, &commitment);
+    client.deposit_with_commitment(&from, &token, &500, &commitment, &100);
+    env.ledger().set_timestamp(env.ledger().timestamp() + 100);
+
+    assert!(client.get_escrow_by_id(&1, &from).is_none());
+    assert_eq!(client.get_escrow_by_id(&2, &from).unwrap().0, commitment);
+    assert_eq!(
+        client.escrows_created_between(&0, &u64::MAX, &1, &10),
+        soroban_sdk::vec![&env, commitment]
+    );
+    assert_eq!(client.unrefunded_expired_value(&token, &1, &10), 500);
+}
+
+#[test]
+fn test_purge_requires_admin() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+    client.initialize(&admin);
+    let token = create_test_token(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+    let salt = Bytes::from_slice(&env, b"purge_auth");
+    let commitment = client.deposit(&token, &amount, &owner, &salt, &0);
+    client.withdraw(&token, &amount, &commitment, &owner, &salt);
+
+    let result = client.try_purge_escrow(&owner, &commitment);
+    assert_contract_error(result, QuickexError::Unauthorized);
+}
+
+#[test]
+fn test_token_escrow_count_underflow_is_internal_error() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+
+    env.as_contract(&client.address, || {
+        assert_eq!(
+            crate::storage::decrement_token_escrow_count(&env, &token),
+            Err(QuickexError::InternalError)
+        );
+    });
+}
+
+// ============================================================================
+// Commitment Recovery Tests
+// ============================================================================
+
+#[test]
+fn test_recompute_commitment_matches_deposit_key() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+    let salt = Bytes::from_slice(&env, b"remembered_salt");
+    let commitment = client.deposit(&token, &amount, &owner, &salt, &0);
+
+    let recovered = client.recompute_commitment(&owner, &amount, &salt);
+    assert_eq!(recovered, commitment);
+    assert!(client.get_escrow_details(&recovered, &owner).is_some());
+}
+
+// ============================================================================
+// Signed Withdrawal Tests
+// ============================================================================
+
+#[test]
+fn test_withdraw_signed_rejects_replay() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &(amount * 2));
+    let first_salt = Bytes::from_slice(&env, b"signed_1");
+    let second_salt = Bytes::from_slice(&env, b"signed_2");
+    client.deposit(&token, &amount, &owner, &first_salt, &0);
+    client.deposit(&token, &amount, &owner, &second_salt, &0);
+
+    assert_eq!(client.withdraw_nonce(&owner), 0);
+    assert!(client.withdraw_signed(&token, &amount, &owner, &first_salt, &0));
+    assert_eq!(client.withdraw_nonce(&owner), 1);
+
+    // Replaying the exact signed call fails on the nonce.
+    let result = client.try_withdraw_signed(&token, &amount, &owner, &first_salt, &0);
+    assert_contract_error(result, QuickexError::StaleNonce);
+
+    // A signature over the old nonce cannot be reused for another escrow either.
+    let result = client.try_withdraw_signed(&token, &amount, &owner, &second_salt, &0);
+    assert_contract_error(result, QuickexError::StaleNonce);
+    let result = client.try_withdraw_signed(&token, &amount, &owner, &second_salt, &2);
+    assert_contract_error(result, QuickexError::StaleNonce);
+
+    assert!(client.withdraw_signed(&token, &amount, &owner, &second_salt, &1));
+    assert_eq!(client.withdraw_nonce(&owner), 2);
+    assert_eq!(token::Client::new(&env, &token).balance(&owner), amount * 2);
+}
+
+#[test]
+fn test_withdraw_signed_failure_keeps_nonce() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"missing");
+
+    let result = client.try_withdraw_signed(&token, &1000, &owner, &salt, &0);
+    assert_contract_error(result, QuickexError::CommitmentNotFound);
+    assert_eq!(client.withdraw_nonce(&owner), 0);
+}
+
+// ============================================================================
+// Bulk Viewer Tests
+// ============================================================================
+
+#[test]
+fn test_grant_and_revoke_viewers_in_bulk() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+    let commitment = client.deposit(
+        &token,
+        &amount,
+        &owner,
+        &Bytes::from_slice(&env, b"bulk"),
+        &0,
+    );
+    client.set_privacy(&owner, &true);
+
+    let a = Address::generate(&env);
+    let b = Address::generate(&env);
+    let c = Address::generate(&env);
+    client.grant_viewers(
+        &owner,
+        &soroban_sdk::vec![&env, a.clone(), b.clone(), c.clone(), a.clone()],
+    );
+    assert_eq!(
+        client.my_granted_viewers(&owner),
+        soroban_sdk::vec![&env, a.clone(), b.clone(), c.clone()]
+    );
+    for viewer in [&a, &b, &c] {
+        let view = client.get_escrow_details(&commitment, viewer).unwrap();
+        assert_eq!(view.amount, Some(amount));
+    }
+
+    client.revoke_viewers(&owner, &soroban_sdk::vec![&env, a.clone(), c.clone()]);
+    assert_eq!(
+        client.my_granted_viewers(&owner),
+        soroban_sdk::vec![&env, b.clone()]
+    );
+    assert_eq!(
+        client.get_escrow_details(&commitment, &a).unwrap().amount,
+        None
+    );
+    assert_eq!(
+        client.get_escrow_details(&commitment, &b).unwrap().amount,
+        Some(amount)
+    );
+}
+
+#[test]
+fn test_grant_viewers_enforces_cap() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+
+    let mut viewers = soroban_sdk::Vec::new(&env);
+    for _ in 0..50 {
+        viewers.push_back(Address::generate(&env));
+    }
+    client.grant_viewers(&owner, &viewers);
+    assert_eq!(client.my_granted_viewers(&owner).len(), 50);
+
+    let result = client.try_grant_viewer(&owner, &Address::generate(&env));
+    assert_contract_error(result, QuickexError::TooManyViewers);
+
+    // Re-granting an existing viewer is still a no-op.
+    client.grant_viewer(&owner, &viewers.get(0).unwrap());
+
+    viewers.push_back(Address::generate(&env));
+    let result = client.try_grant_viewers(&owner, &viewers);
+    assert_contract_error(result, QuickexError::BatchTooLarge);
+}
+
+// ============================================================================
+// Privacy State Tests
+// ============================================================================
+
+#[test]
+fn test_privacy_state_distinguishes_unset() {
+    let (env, client) = setup();
+    let account = Address::generate(&env);
+
+    assert_eq!(client.privacy_state(&account), PrivacyState::Unset);
+    assert!(!client.get_privacy(&account));
+
+    client.set_privacy(&account, &true);
+    assert_eq!(client.privacy_state(&account), PrivacyState::Enabled);
+
+    client.set_privacy(&account, &false);
+    assert_eq!(client.privacy_state(&account), PrivacyState::Disabled);
+    assert!(!client.get_privacy(&account));
+}
+
+// ============================================================================
+// Privacy Migration Tests
+// ============================================================================
+
+#[test]
+fn test_migrate_privacy_to_level() {
+    let (env, client) = setup();
+    let private = Address::generate(&env);
+    let public = Address::generate(&env);
+    client.set_privacy(&private, &true);
+
+    assert_eq!(client.migrate_privacy_to_level(&private), 2);
+    assert_eq!(client.privacy_status(&private), Some(2));
+    assert!(client.get_privacy(&private));
+    assert_eq!(client.privacy_history(&private), soroban_sdk::vec![&env, 2]);
+
+    assert_eq!(client.migrate_privacy_to_level(&public), 0);
+    assert_eq!(client.privacy_status(&public), Some(0));
+    assert!(!client.get_privacy(&public));
+}
+
+#[test]
+fn test_total_fees_accumulates_per_token() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_withdrawal_fees(&admin, &100, &None);
+
+    let token = create_test_token(&env);
+    let other_token = create_test_token(&env);
+    let user = Address::generate(&env);
+    assert_eq!(client.total_fees(&token), 0);
+
+    for (i, amount) in [10_000i128, 5_000, 2_050].iter().enumerate() {
+        let salt = Bytes::from_slice(&env, &[b'f', i as u8]);
+        let commitment = setup_fee_escrow(&env, &client, &token, &user, *amount, &salt);
+        client.withdraw(&token, amount, &commitment, &user, &salt);
+    }
+
+    // 100 + 50 + 20 (rounded down from 20.5)
+    assert_eq!(client.total_fees(&token), 170);
+    assert_eq!(token::Client::new(&env, &token).balance(&admin), 170);
+    assert_eq!(client.total_fees(&other_token), 0);
+}
+
+#[test]
+fn test_reconcile_all_reports_every_token() {
+    let (env, client) = setup();
+    let owner = Address::generate(&env);
+    let balanced = create_test_token(&env);
+    let surplus = create_test_token(&env);
+    let amount: i128 = 1000;
+    assert!(client.reconcile_all(&0).is_empty());
+
+    for (token, salt) in [(&balanced, b"rec_a"), (&surplus, b"rec_b")] {
+        token::StellarAssetClient::new(&env, token).mint(&owner, &amount);
+        client.deposit(token, &amount, &owner, &Bytes::from_slice(&env, salt), &0);
+    }
+    token::StellarAssetClient::new(&env, &surplus).mint(&client.address, &250);
+
+    // A second escrow in a known token does not register it twice.
+    token::StellarAssetClient::new(&env, &balanced).mint(&owner, &amount);
+    client.deposit(
+        &balanced,
+        &amount,
+        &owner,
+        &Bytes::from_slice(&env, b"rec_c"),
+        &0,
+    );
+
+    assert_eq!(
+        client.reconcile_all(&0),
+        soroban_sdk::vec![&env, (balanced, 0i128), (surplus.clone(), 250i128)]
+    );
+    assert_eq!(
+        client.reconcile_all(&1),
+        soroban_sdk::vec![&env, (surplus, 250i128)]
+    );
+    assert!(client.reconcile_all(&2).is_empty());
+}
+
+// ============================================================================
+// Expiry Extension Tests
+// ============================================================================
+
+#[test]
+fn test_extend_timeout_respects_max_extensions() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+    let salt = Bytes::from_slice(&env, b"capped");
+    let options = EscrowOptions {
+        max_extensions: Some(2),
+        ..Default::default()
+    };
+    let commitment = client.deposit_with_options(&token, &amount, &owner, &salt, &100, &options);
+
+    client.extend_timeout(&commitment, &owner, &50);
+    client.extend_timeout(&commitment, &owner, &50);
+    let result = client.try_extend_timeout(&commitment, &owner, &50);
+    assert_contract_error(result, QuickexError::ExtensionLimitReached);
+
+    let view = client.get_escrow_details(&commitment, &owner).unwrap();
+    assert_eq!(view.expires_at, Some(200));
+}
+
+#[test]
+fn test_extend_timeout_unlimited_by_default() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+    let salt = Bytes::from_slice(&env, b"uncapped");
+    let commitment = client.deposit(&token, &amount, &owner, &salt, &100);
+
+    for _ in 0..5 {
+        client.extend_timeout(&commitment, &owner, &10);
+    }
+    let view = client.get_escrow_details(&commitment, &owner).unwrap();
+    assert_eq!(view.expires_at, Some(150));
+
+    let result = client.try_extend_timeout(&commitment, &stranger, &10);
+    assert_contract_error(result, QuickexError::InvalidOwner);
+}
+
+#[test]
+fn test_extend_timeout_rejects_non_expiring_escrow() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"forever");
+    let commitment = setup_fee_escrow(&env, &client, &token, &owner, 1000, &salt);
+
+    let result = client.try_extend_timeout(&commitment, &owner, &10);
+    assert_contract_error(result, QuickexError::EscrowNotExpirable);
+}
+
+#[test]
+fn test_extend_timeout_rejects_lapsed_or_settled_escrow() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &(amount * 2));
+
+    let lapsed_salt = Bytes::from_slice(&env, b"lapsed");
+    let lapsed = client.deposit(&token, &amount, &owner, &lapsed_salt, &100);
+    let spent_salt = Bytes::from_slice(&env, b"spent");
+    let spent = client.deposit(&token, &amount, &owner, &spent_salt, &1_000);
+    client.withdraw(&token, &amount, &spent, &owner, &spent_salt);
+
+    env.ledger().set_timestamp(100);
+    let result = client.try_extend_timeout(&lapsed, &owner, &10);
+    assert_contract_error(result, QuickexError::EscrowExpired);
+    let result = client.try_extend_timeout(&spent, &owner, &10);
+    assert_contract_error(result, QuickexError::AlreadySpent);
+}
+
+// ============================================================================
+// Withdraw and swap
+// ============================================================================
+
+mod dex {
+    use soroban_sdk::{contract, contractimpl, symbol_short, token, Address, Env};
+
+    /// Mock DEX that swaps at a fixed rate out of its own `token_out` balance.
+    #[contract]
+    pub struct MockDex;
+
+    #[contractimpl]
+    impl MockDex {
+        /// Set the output rate in basis points of the input amount.
+        pub fn set_rate(env: Env, rate_bps: i128) {
+            env.storage()
+                .instance()
+                .set(&symbol_short!("rate"), &rate_bps);
+        }
+
+        pub fn swap(
+            env: Env,
+            from: Address,
+            token_in: Address,
+            token_out: Address,
+            amount_in: i128,
+            _min_out: i128,
+            to: Address,
+        ) -> i128 {
+            let this = env.current_contract_address();
+            let rate: i128 = env
+                .storage()
+                .instance()
+                .get(&symbol_short!("rate"))
+                .unwrap_or(10_000);
+            token::Client::new(&env, &token_in).transfer(&from, &this, &amount_in);
+            let out = amount_in * rate / 10_000;
+            token::Client::new(&env, &token_out).transfer(&this, &to, &out);
+            env.storage()
+                .instance()
+                .get(&symbol_short!("reported"))
+                .unwrap_or(out)
+        }
+
+        /// Report `out` from every later swap, whatever was actually delivered.
+        pub fn set_reported(env: Env, out: i128) {
+            env.storage()
+                .instance()
+                .set(&symbol_short!("reported"), &out);
+        }
+    }
+
+    /// Mock DEX that moves no tokens but claims a large output.
+    #[contract]
+    pub struct IdleDex;
+
+    #[contractimpl]
+    impl IdleDex {
+        pub fn swap(
+            _env: Env,
+            _from: Address,
+            _token_in: Address,
+            _token_out: Address,
+            _amount_in: i128,
+            _min_out: i128,
+            _to: Address,
+        ) -> i128 {
+            1_000_000
+        }
+    }
+}
+
+fn setup_dex(env: &Env, out_token: &Address, rate_bps: i128) -> Address {
+    let dex = env.register(dex::MockDex, ());
+    dex::MockDexClient::new(env, &dex).set_rate(&rate_bps);
+    token::StellarAssetClient::new(env, out_token).mint(&dex, &1_000_000);
+    dex
+}
+
+#[test]
+fn test_withdraw_and_swap_delivers_out_token() {
+    let (env, client) = setup();
+    let token_in = create_test_token(&env);
+    let token_out = create_test_token(&env);
+    let user = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"swap");
+    let commitment = setup_fee_escrow(&env, &client, &token_in, &user, 1_000, &salt);
+    let dex = setup_dex(&env, &token_out, 20_000);
+
+    let out = client.withdraw_and_swap(&1_000, &salt, &user, &dex, &1_900, &token_out);
+
+    assert_eq!(out, 2_000);
+    assert_eq!(token::Client::new(&env, &token_out).balance(&user), 2_000);
+    assert_eq!(token::Client::new(&env, &token_in).balance(&dex), 1_000);
+    assert_eq!(
+        token::Client::new(&env, &token_in).balance(&client.address),
+        0
+    );
+    assert_eq!(
+        client.get_states(&soroban_sdk::vec![&env, commitment]),
+        soroban_sdk::vec![&env, Some(EscrowStatus::Spent)]
+    );
+}
+
+#[test]
+fn test_withdraw_and_swap_swaps_net_of_fee() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_withdrawal_fees(&admin, &100, &None);
+    let token_in = create_test_token(&env);
+    let token_out = create_test_token(&env);
+    let user = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"swap-fee");
+    setup_fee_escrow(&env, &client, &token_in, &user, 1_000, &salt);
+    let dex = setup_dex(&env, &token_out, 10_000);
+
+    let out = client.withdraw_and_swap(&1_000, &salt, &user, &dex, &990, &token_out);
+
+    assert_eq!(out, 990);
+    assert_eq!(token::Client::new(&env, &token_in).balance(&admin), 10);
+    assert_eq!(token::Client::new(&env, &token_in).balance(&dex), 990);
+    assert_eq!(client.total_fees(&token_in), 10);
+}
+
+#[test]
+fn test_withdraw_and_swap_reverts_on_slippage() {
+    let (env, client) = setup();
+    let token_in = create_test_token(&env);
+    let token_out = create_test_token(&env);
+    let user = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"slip");
+    let commitment = setup_fee_escrow(&env, &client, &token_in, &user, 1_000, &salt);
+    let dex = setup_dex(&env, &token_out, 9_000);
+
+    let result = client.try_withdraw_and_swap(&1_000, &salt, &user, &dex, &950, &token_out);
+
+    assert_contract_error(result, QuickexError::SlippageExceeded);
+    assert_eq!(
+        client.get_states(&soroban_sdk::vec![&env, commitment]),
+        soroban_sdk::vec![&env, Some(EscrowStatus::Pending)]
+    );
+    assert_eq!(
+        token::Client::new(&env, &token_in).balance(&client.address),
+        1_000
+    );
+    assert_eq!(token::Client::new(&env, &token_out).balance(&user), 0);
+}
+
+#[test]
+fn test_withdraw_and_swap_ignores_overstated_dex_output() {
+    let (env, client) = setup();
+    let token_in = create_test_token(&env);
+    let token_out = create_test_token(&env);
+    let user = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"overstated");
+    let commitment = setup_fee_escrow(&env, &client, &token_in, &user, 1_000, &salt);
+    let dex = setup_dex(&env, &token_out, 5_000);
+    dex::MockDexClient::new(&env, &dex).set_reported(&2_000);
+
+    let result = client.try_withdraw_and_swap(&1_000, &salt, &user, &dex, &950, &token_out);
+
+    assert_contract_error(result, QuickexError::SlippageExceeded);
+    assert_eq!(
+        client.get_states(&soroban_sdk::vec![&env, commitment]),
+        soroban_sdk::vec![&env, Some(EscrowStatus::Pending)]
+    );
+}
+
+#[test]
+fn test_withdraw_and_swap_rejects_dex_that_moves_nothing() {
+    let (env, client) = setup();
+    let token_in = create_test_token(&env);
+    let token_out = create_test_token(&env);
+    let user = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"idle");
+    let commitment = setup_fee_escrow(&env, &client, &token_in, &user, 1_000, &salt);
+    let dex = env.register(dex::IdleDex, ());
+
+    let result = client.try_withdraw_and_swap(&1_000, &salt, &user, &dex, &950, &token_out);
+
+    assert_contract_error(result, QuickexError::SwapInputMismatch);
+    assert_eq!(
+        client.get_states(&soroban_sdk::vec![&env, commitment]),
+        soroban_sdk::vec![&env, Some(EscrowStatus::Pending)]
+    );
+    assert_eq!(
+        token::Client::new(&env, &token_in).balance(&client.address),
+        1_000
+    );
+}
+
+// ============================================================================
+// Salt tracking
+// ============================================================================
+
+#[test]
+fn test_salt_used_after_tracked_deposit() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let used = Bytes::from_slice(&env, b"used-salt");
+    let fresh = Bytes::from_slice(&env, b"fresh-salt");
+    client.set_salt_tracking(&owner, &true);
+
+    setup_fee_escrow(&env, &client, &token, &owner, 100, &used);
+
+    assert!(client.salt_used(&owner, &used));
+    assert!(!client.salt_used(&owner, &fresh));
+    assert!(!client.salt_used(&Address::generate(&env), &used));
+}
+
+#[test]
+fn test_salt_used_ignores_untracked_deposits() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"untracked");
+
+    setup_fee_escrow(&env, &client, &token, &owner, 100, &salt);
+    assert!(!client.salt_used(&owner, &salt));
+
+    client.set_salt_tracking(&owner, &true);
+    setup_fee_escrow(&env, &client, &token, &owner, 200, &salt);
+    client.set_salt_tracking(&owner, &false);
+    assert!(client.salt_used(&owner, &salt));
+}
+
+// ============================================================================
+// Stealth mode
+// ============================================================================
+
+#[test]
+fn test_stealth_escrow_hidden_from_strangers() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let viewer = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"stealth");
+    let commitment = setup_fee_escrow(&env, &client, &token, &owner, 500, &salt);
+
+    client.set_stealth(&owner, &true);
+    client.grant_viewer(&owner, &viewer);
+
+    assert!(client.is_stealth(&owner));
+    assert!(client.get_escrow_details(&commitment, &stranger).is_none());
+
+    let view = client.get_escrow_details(&commitment, &owner).unwrap();
+    assert_eq!(view.amount, Some(500));
+    assert_eq!(view.owner, Some(owner.clone()));
+    assert!(client.get_escrow_details(&commitment, &viewer).is_some());
+
+    client.set_stealth(&owner, &false);
+    assert!(client.get_escrow_details(&commitment, &stranger).is_some());
+}
+
+// ============================================================================
+// Bulk TTL extension
+// ============================================================================
+
+#[test]
+fn test_bump_all_ttls_keeps_owner_escrows_live() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let first = setup_fee_escrow(
+        &env,
+        &client,
+        &token,
+        &owner,
+        100,
+        &Bytes::from_slice(&env, b"a"),
+    );
+    let second = setup_fee_escrow(
+        &env,
+        &client,
+        &token,
+        &owner,
+        200,
+        &Bytes::from_slice(&env, b"b"),
+    );
+
+    env.ledger()
+        .with_mut(|l| l.sequence_number += ESCROW_TTL_LEDGERS - 10);
+    assert_eq!(client.escrow_ttl_remaining(&first), Some(10));
+
+    assert_eq!(client.bump_all_ttls(&owner, &(2 * ESCROW_TTL_LEDGERS)), 2);
+    env.ledger()
+        .with_mut(|l| l.sequence_number += ESCROW_TTL_LEDGERS);
+
+    for commitment in [first, second] {
+        assert_eq!(
+            client.escrow_ttl_remaining(&commitment),
+            Some(ESCROW_TTL_LEDGERS)
+        );
+        let actual = env.as_contract(&client.address, || {
+            env.storage()
+                .persistent()
+                .get_ttl(&DataKey::Escrow(commitment.clone().into()))
+        });
+        assert!(actual >= ESCROW_TTL_LEDGERS);
+    }
+}
+
+// ============================================================================
+// Upgrade record
+// ============================================================================
+
+/// Smallest WASM module the host accepts for upload: just the
+/// `contractenvmetav0` section declaring interface protocol 23.
+const MINIMAL_CONTRACT_WASM: &[u8] = &[
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // magic, version
+    0x00, 0x1e, 0x11, // custom section, 30 bytes, 17-byte name
+    b'c', b'o', b'n', b't', b'r', b'a', b'c', b't', b'e', b'n', b'v', b'm', b'e', b't', b'a', b'v',
+    b'0', //
+    0x00, 0x00, 0x00, 0x00, // ScEnvMetaKindInterfaceVersion
+    0x00, 0x00, 0x00, 0x17, // protocol 23
+    0x00, 0x00, 0x00, 0x00, // pre-release 0
+];
+
+#[test]
+fn test_last_upgrade_records_hash_and_timestamp() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    assert_eq!(client.last_upgrade(), None);
+
+    let wasm_hash = env
+        .deployer()
+        .upload_contract_wasm(Bytes::from_slice(&env, MINIMAL_CONTRACT_WASM));
+    env.ledger().with_mut(|l| l.timestamp = 1_700_000_000);
+    client.upgrade(&admin, &wasm_hash);
+
+    // The contract now runs the uploaded module, so read the record directly.
+    let info = env.as_contract(&client.address, || get_last_upgrade(&env));
+    assert_eq!(
+        info,
+        Some(UpgradeInfo {
+            wasm_hash,
+            timestamp: 1_700_000_000,
+            admin,
+        })
+    );
+}
+
+// ============================================================================
+// Deposit matching a published commitment
+// ============================================================================
+
+#[test]
+fn test_deposit_matching_commitment_accepts_match() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"published");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &1_000);
+    let expected = client.create_amount_commitment(&owner, &1_000, &salt);
+
+    let commitment =
+        client.deposit_matching_commitment(&token, &1_000, &owner, &salt, &expected, &0);
+
+    assert_eq!(commitment, expected);
+    assert_eq!(
+        token::Client::new(&env, &token).balance(&client.address),
+        1_000
+    );
+}
+
+#[test]
+fn test_deposit_matching_commitment_rejects_mismatch() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"published");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &1_000);
+    let expected = client.create_amount_commitment(&owner, &900, &salt);
+
+    let result =
+        client.try_deposit_matching_commitment(&token, &1_000, &owner, &salt, &expected, &0);
+
+    assert_contract_error(result, QuickexError::CommitmentMismatch);
+    assert_eq!(token::Client::new(&env, &token).balance(&owner), 1_000);
+    assert!(client.get_escrow_details(&expected, &owner).is_none());
+}
+
+// ============================================================================
+// Escrow privacy query
+// ============================================================================
+
+#[test]
+fn test_is_escrow_private_follows_owner_settings() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"private?");
+    let commitment = setup_fee_escrow(&env, &client, &token, &owner, 100, &salt);
+
+    assert!(!client.is_escrow_private(&commitment));
+
+    client.set_privacy(&owner, &true);
+    assert!(client.is_escrow_private(&commitment));
+
+    client.set_privacy(&owner, &false);
+    client.set_stealth(&owner, &true);
+    assert!(client.is_escrow_private(&commitment));
+
+    assert!(!client.is_escrow_private(&BytesN::from_array(&env, &[7; 32])));
+}
+
+// ============================================================================
+// Re-deposit onto terminal commitments
+// ============================================================================
+
+fn spent_commitment_escrow(
+    env: &Env,
+    client: &QuickexContractClient,
+    token: &Address,
+    from: &Address,
+    recipient: &Address,
+    salt: &Bytes,
+) -> BytesN<32> {
+    let commitment = client.create_amount_commitment(recipient, &500, salt);
+    token::StellarAssetClient::new(env, token).mint(from, &1_000);
+    client.deposit_with_commitment(from, token, &500, &commitment, &0);
+    client.withdraw(token, &500, &commitment, recipient, salt);
+    commitment
+}
+
+#[test]
+fn test_redeposit_on_spent_commitment_rejected_by_default() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let from = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"cycle");
+    let commitment = spent_commitment_escrow(&env, &client, &token, &from, &recipient, &salt);
+
+    assert!(!client.is_terminal_redeposit());
+    let result = client.try_deposit_with_commitment(&from, &token, &500, &commitment, &0);
+    assert_contract_error(result, QuickexError::CommitmentAlreadyExists);
+}
+
+#[test]
+fn test_redeposit_on_spent_commitment_allowed_by_flag() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let token = create_test_token(&env);
+    let from = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"cycle");
+    let commitment = spent_commitment_escrow(&env, &client, &token, &from, &recipient, &salt);
+
+    client.set_terminal_redeposit(&admin, &true);
+    client.deposit_with_commitment(&from, &token, &500, &commitment, &0);
+    assert_eq!(
+        client.get_states(&soroban_sdk::vec![&env, commitment.clone()]),
+        soroban_sdk::vec![&env, Some(EscrowStatus::Pending)]
+    );
+
+    // A pending escrow is never overwritten.
+    let result = client.try_deposit_with_commitment(&from, &token, &500, &commitment, &0);
+    assert_contract_error(result, QuickexError::CommitmentAlreadyExists);
+
+    assert!(client.withdraw(&token, &500, &commitment, &recipient, &salt));
+}
+
+#[test]
+fn test_redeposit_retires_old_escrow_id() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let token = create_test_token(&env);
+    let from = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"cycle");
+    let commitment = spent_commitment_escrow(&env, &client, &token, &from, &recipient, &salt);
+
+    client.set_terminal_redeposit(&admin, &true);
+    client.deposit_with_commitment(&from, &token, &500, &commitment, &100);
+    env.ledger().set_timestamp(env.ledger().timestamp() + 100);
+
+    assert!(client.get_escrow_by_id(&1, &from).is_none());
+    assert_eq!(client.get_escrow_by_id(&2, &from).unwrap().0, commitment);
+    assert_eq!(
+        client.escrows_created_between(&0, &u64::MAX, &1, &10),
+        soroban_sdk::vec![&env, commitment]
+    );
+    assert_eq!(client.unrefunded_expired_value(&token, &1, &10), 500);
+}
+
+// ============================================================================
+// Commitment openings
+// ============================================================================
+
+#[test]
+fn test_get_commitment_opening_valid() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"opening");
+    let commitment = setup_fee_escrow(&env, &client, &token, &owner, 750, &salt);
+
+    let opening = client
+        .get_commitment_opening(&commitment, &owner, &salt)
+        .unwrap();
+
+    assert_eq!(opening.owner, owner);
+    assert_eq!(opening.amount, 750);
+    assert_eq!(opening.salt, salt);
+    assert_eq!(opening.commitment, commitment);
+    assert!(client.verify_amount_commitment(&commitment, &owner, &opening.amount, &salt));
+}
+
+#[test]
+fn test_get_commitment_opening_wrong_salt_is_none() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"opening");
+    let commitment = setup_fee_escrow(&env, &client, &token, &owner, 750, &salt);
+
+    let wrong = Bytes::from_slice(&env, b"not-it");
+    assert_eq!(
+        client.get_commitment_opening(&commitment, &owner, &wrong),
+        None
+    );
+    let stranger = Address::generate(&env);
+    assert_eq!(
+        client.get_commitment_opening(&commitment, &stranger, &salt),
+        None
+    );
+}
+
+// ============================================================================
+// Global privacy kill-switch
+// ============================================================================
+
+#[test]
+fn test_disable_privacy_globally_reveals_and_restores() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"kill-switch");
+    let commitment = setup_fee_escrow(&env, &client, &token, &owner, 300, &salt);
+    client.set_privacy(&owner, &true);
+    assert_eq!(
+        client
+            .get_escrow_details(&commitment, &stranger)
+            .unwrap()
+            .amount,
+        None
+    );
+
+    client.disable_privacy_globally(&admin, &true);
+    assert!(client.is_privacy_disabled_globally());
+    assert!(!client.get_privacy(&owner));
+    let view = client.get_escrow_details(&commitment, &stranger).unwrap();
+    assert_eq!(view.amount, Some(300));
+    assert_eq!(view.owner, Some(owner.clone()));
+
+    // Stealth is overridden as well.
+    client.set_stealth(&owner, &true);
+    assert!(client.get_escrow_details(&commitment, &stranger).is_some());
+
+    client.disable_privacy_globally(&admin, &false);
+    assert!(client.get_privacy(&owner));
+    assert!(client.get_escrow_details(&commitment, &stranger).is_none());
+    client.set_stealth(&owner, &false);
+    assert_eq!(
+        client
+            .get_escrow_details(&commitment, &stranger)
+            .unwrap()
+            .amount,
+        None
+    );
+}
+
+#[test]
+fn test_disable_privacy_globally_admin_only() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let stranger = Address::generate(&env);
+
+    let result = client.try_disable_privacy_globally(&stranger, &true);
+    assert_contract_error(result, QuickexError::Unauthorized);
+    assert!(!client.is_privacy_disabled_globally());
+}
+
+// ============================================================================
+// Escrows by creation time
+// ============================================================================
+
+#[test]
+fn test_escrows_created_between_filters_on_created_at() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+
+    let mut commitments = soroban_sdk::Vec::new(&env);
+    for (i, ts) in [100u64, 200, 300, 400].iter().enumerate() {
+        env.ledger().with_mut(|l| l.timestamp = *ts);
+        let salt = Bytes::from_array(&env, &[i as u8]);
+        commitments.push_back(setup_fee_escrow(&env, &client, &token, &owner, 10, &salt));
+    }
+
+    assert_eq!(
+        client.escrows_created_between(&150, &300, &1, &10),
+        soroban_sdk::vec![
+            &env,
+            commitments.get_unchecked(1),
+            commitments.get_unchecked(2)
+        ]
+    );
+    assert_eq!(
+        client.escrows_created_between(&0, &u64::MAX, &1, &2),
+        commitments.slice(0..2)
+    );
+    assert_eq!(
+        client.escrows_created_between(&0, &u64::MAX, &3, &2),
+        commitments.slice(2..4)
+    );
+    assert!(client
+        .escrows_created_between(&500, &600, &1, &10)
+        .is_empty());
+    assert!(client
+        .escrows_created_between(&0, &u64::MAX, &5, &10)
+        .is_empty());
+}
+
+// ============================================================================
+// Cliff deposits
+// ============================================================================
+
+#[test]
+fn test_deposit_with_cliff_blocks_early_withdraw() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"cliff");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &1_000);
+    env.ledger().set_timestamp(1_000);
+
+    let commitment = client.deposit_with_cliff(&token, &1_000, &owner, &salt, &500, &100);
+
+    env.ledger().set_timestamp(1_099);
+    let result = client.try_withdraw(&token, &1_000, &commitment, &owner, &salt);
+    assert_contract_error(result, QuickexError::EscrowNotYetClaimable);
+    let result = client.try_refund(&commitment, &owner);
+    assert_contract_error(result, QuickexError::EscrowNotExpired);
+
+    env.ledger().set_timestamp(1_100);
+    assert!(client.withdraw(&token, &1_000, &commitment, &owner, &salt));
+    assert_eq!(token::Client::new(&env, &token).balance(&owner), 1_000);
+}
+
+#[test]
+fn test_deposit_with_cliff_must_end_before_timeout() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"cliff");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &1_000);
+
+    let result = client.try_deposit_with_cliff(&token, &1_000, &owner, &salt, &100, &100);
+    assert_contract_error(result, QuickexError::InvalidSchedule);
+}
+
+// ============================================================================
+// Unrefunded expired value
+// ============================================================================
+
+#[test]
+fn test_unrefunded_expired_value_sums_lapsed_escrows() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let other_token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &10_000);
+    token::StellarAssetClient::new(&env, &other_token).mint(&owner, &10_000);
+    env.ledger().set_timestamp(1_000);
+
+    let salt = |s: &[u8]| Bytes::from_slice(&env, s);
+    client.deposit(&token, &100, &owner, &salt(b"a"), &50);
+    client.deposit(&token, &200, &owner, &salt(b"b"), &50);
+    let refunded = client.deposit(&token, &400, &owner, &salt(b"c"), &50);
+    client.deposit(&token, &800, &owner, &salt(b"d"), &5_000);
+    client.deposit(&token, &1_600, &owner, &salt(b"e"), &0);
+    client.deposit(&other_token, &3_200, &owner, &salt(b"f"), &50);
+    assert_eq!(client.unrefunded_expired_value(&token, &1, &50), 0);
+
+    env.ledger().set_timestamp(1_100);
+    client.refund(&refunded, &owner);
+
+    assert_eq!(client.unrefunded_expired_value(&token, &1, &50), 300);
+    assert_eq!(
+        client.unrefunded_expired_value(&other_token, &1, &50),
+        3_200
+    );
+    assert_eq!(client.unrefunded_expired_value(&token, &2, &1), 200);
+}
+
+// ============================================================================
+// Storage invariants
+// ============================================================================
+
+#[test]
+fn test_verify_storage_invariants_healthy_contract() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"healthy");
+    setup_fee_escrow(&env, &client, &token, &owner, 100, &salt);
+    setup_fee_escrow(&env, &client, &token, &owner, 200, &salt);
+    client.create_escrow(&owner, &owner, &1);
+    client.withdraw(
+        &token,
+        &100,
+        &BytesN::from_array(&env, &[0; 32]),
+        &owner,
+        &salt,
+    );
+
+    let results = client.verify_storage_invariants();
+    assert_eq!(results.len(), 3);
+    for (name, ok) in results.iter() {
+        assert!(ok, "invariant {:?} failed", name);
+    }
+}
+
+#[test]
+fn test_verify_storage_invariants_detects_corrupt_counter() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    setup_fee_escrow(
+        &env,
+        &client,
+        &token,
+        &owner,
+        100,
+        &Bytes::from_slice(&env, b"x"),
+    );
+    env.as_contract(&client.address, || {
+        env.storage()
+            .persistent()
+            .set(&DataKey::EscrowCounter, &0u64);
+    });
+
+    let results = client.verify_storage_invariants();
+    assert_eq!(
+        results,
+        soroban_sdk::vec![
+            &env,
+            (Symbol::new(&env, "counter_covers_active"), false),
+            (Symbol::new(&env, "tvl_non_negative"), true),
+            (Symbol::new(&env, "status_counts_sum"), false),
+        ]
+    );
+}
+
+#[test]
+fn test_verify_storage_invariants_detects_overcounted_status() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    setup_fee_escrow(
+        &env,
+        &client,
+        &token,
+        &owner,
+        100,
+        &Bytes::from_slice(&env, b"x"),
+    );
+    env.as_contract(&client.address, || {
+        update_status_counts(&env, None, EscrowStatus::Pending);
+    });
+
+    let results = client.verify_storage_invariants();
+    assert_eq!(
+        results.get(2).unwrap(),
+        (Symbol::new(&env, "status_counts_sum"), false)
+    );
+}
+
+// ============================================================================
+// Pooled escrows
+// ============================================================================
+
+#[test]
+fn test_pool_contributors_withdraw_their_shares() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let creator = Address::generate(&env);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let asset = token::StellarAssetClient::new(&env, &token);
+    asset.mint(&alice, &1_000);
+    asset.mint(&bob, &1_000);
+    let pool_id = BytesN::from_array(&env, &[42; 32]);
+    env.ledger().set_timestamp(1_000);
+
+    client.open_pool(&pool_id, &creator, &token, &2_000);
+    assert_eq!(client.contribute(&pool_id, &alice, &token, &300), 300);
+    assert_eq!(client.contribute(&pool_id, &bob, &token, &600), 600);
+    assert_eq!(client.contribute(&pool_id, &alice, &token, &100), 400);
+    assert_eq!(client.get_pool(&pool_id).unwrap().total, 1_000);
+
+    let result = client.try_withdraw_share(&pool_id, &alice);
+    assert_contract_error(result, QuickexError::EscrowNotYetClaimable);
+
+    env.ledger().set_timestamp(2_000);
+    let result = client.try_contribute(&pool_id, &bob, &token, &10);
+    assert_contract_error(result, QuickexError::EscrowExpired);
+
+    assert_eq!(client.withdraw_share(&pool_id, &alice), 400);
+    assert_eq!(client.withdraw_share(&pool_id, &bob), 600);
+    let balances = token::Client::new(&env, &token);
+    assert_eq!(balances.balance(&alice), 1_000);
+    assert_eq!(balances.balance(&bob), 1_000);
+    assert_eq!(balances.balance(&client.address), 0);
+    assert_eq!(client.get_pool(&pool_id).unwrap().total, 0);
+
+    let result = client.try_withdraw_share(&pool_id, &alice);
+    assert_contract_error(result, QuickexError::NoPoolShare);
+    assert_eq!(client.pool_share(&pool_id, &alice), 0);
+}
+
+#[test]
+fn test_pool_rejects_wrong_token_and_duplicate_open() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let other = create_test_token(&env);
+    let creator = Address::generate(&env);
+    let pool_id = BytesN::from_array(&env, &[43; 32]);
+    env.ledger().set_timestamp(1_000);
+
+    client.open_pool(&pool_id, &creator, &token, &2_000);
+    let result = client.try_open_pool(&pool_id, &creator, &token, &3_000);
+    assert_contract_error(result, QuickexError::CommitmentAlreadyExists);
+
+    let result = client.try_contribute(&pool_id, &creator, &other, &10);
+    assert_contract_error(result, QuickexError::TokenMismatch);
+
+    let other_id = BytesN::from_array(&env, &[44; 32]);
+    let result = client.try_open_pool(&other_id, &creator, &token, &1_000);
+    assert_contract_error(result, QuickexError::InvalidSchedule);
+}
+
+// ============================================================================
+// Amount discrepancy
+// ============================================================================
+
+#[test]
+fn test_amount_discrepancy_reports_delta() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let counterparty = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"dispute");
+    let commitment = setup_fee_escrow(&env, &client, &token, &owner, 1_000, &salt);
+
+    assert_eq!(
+        client.amount_discrepancy(&commitment, &1_000, &counterparty),
+        Some(0)
+    );
+    assert_eq!(
+        client.amount_discrepancy(&commitment, &1_200, &counterparty),
+        Some(-200)
+    );
+    assert_eq!(
+        client.amount_discrepancy(&commitment, &900, &counterparty),
+        Some(100)
+    );
+    let missing = BytesN::from_array(&env, &[3; 32]);
+    assert_eq!(client.amount_discrepancy(&missing, &1_000, &owner), None);
+}
+
+#[test]
+fn test_amount_discrepancy_respects_privacy() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let viewer = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"dispute");
+    let commitment = setup_fee_escrow(&env, &client, &token, &owner, 1_000, &salt);
+    client.set_privacy(&owner, &true);
+    client.grant_viewer(&owner, &viewer);
+
+    assert_eq!(
+        client.amount_discrepancy(&commitment, &1_000, &stranger),
+        None
+    );
+    assert_eq!(
+        client.amount_discrepancy(&commitment, &1_000, &owner),
+        Some(0)
+    );
+    assert_eq!(
+        client.amount_discrepancy(&commitment, &400, &viewer),
+        Some(600)
+    );
+}
+
+#[test]
+fn test_amount_discrepancy_hidden_for_stealth_owner() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"stealthy");
+    let commitment = setup_fee_escrow(&env, &client, &token, &owner, 1_000, &salt);
+    client.set_stealth(&owner, &true);
+
+    assert_eq!(
+        client.amount_discrepancy(&commitment, &1_000, &stranger),
+        None
+    );
+    assert_eq!(
+        client.amount_discrepancy(&commitment, &1_000, &owner),
+        Some(0)
+    );
+}
+
+// ============================================================================
+// Partial withdrawals
+// ============================================================================
+
+#[test]
+fn test_withdraw_partial_until_spent() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let token_client = token::Client::new(&env, &token);
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"partial");
+    let commitment = setup_fee_escrow(&env, &client, &token, &owner, 1_000, &salt);
+
+    assert!(client.withdraw_partial(&1_000, &owner, &salt, &400));
+    assert_eq!(token_client.balance(&owner), 400);
+    assert_eq!(
+        client.get_commitment_state(&commitment),
+        Some(EscrowStatus::Pending)
+    );
+
+    assert!(client.withdraw_partial(&1_000, &owner, &salt, &600));
+    assert_eq!(token_client.balance(&owner), 1_000);
+    assert_eq!(token_client.balance(&client.address), 0);
+    assert_eq!(
+        client.get_commitment_state(&commitment),
+        Some(EscrowStatus::Spent)
+    );
+    assert!(client
+        .verify_storage_invariants()
+        .iter()
+        .all(|(_, holds)| holds));
+
+    let result = client.try_withdraw_partial(&1_000, &owner, &salt, &1);
+    assert_contract_error(result, QuickexError::AlreadySpent);
+}
+
+#[test]
+fn test_withdraw_partial_rejects_over_withdrawal() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"partial");
+    setup_fee_escrow(&env, &client, &token, &owner, 1_000, &salt);
+
+    client.withdraw_partial(&1_000, &owner, &salt, &700);
+    let result = client.try_withdraw_partial(&1_000, &owner, &salt, &301);
+    assert_contract_error(result, QuickexError::PartialWithdrawExceedsBalance);
+
+    let result = client.try_withdraw_partial(&1_000, &owner, &salt, &0);
+    assert_contract_error(result, QuickexError::InvalidAmount);
+}
+
+// ============================================================================
+// Chained escrows
+// ============================================================================
+
+#[test]
+fn test_chained_escrow_waits_for_prerequisite() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let first_salt = Bytes::from_slice(&env, b"stage-1");
+    let second_salt = Bytes::from_slice(&env, b"stage-2");
+    let first = setup_fee_escrow(&env, &client, &token, &owner, 500, &first_salt);
+
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &700);
+    let options = EscrowOptions {
+        next_of: Some(first.clone()),
+        ..Default::default()
+    };
+    let second = client.deposit_with_options(&token, &700, &owner, &second_salt, &0, &options);
+
+    let result = client.try_withdraw(&token, &700, &second, &owner, &second_salt);
+    assert_contract_error(result, QuickexError::PrerequisiteNotMet);
+
+    client.withdraw(&token, &500, &first, &owner, &first_salt);
+    assert!(client.withdraw(&token, &700, &second, &owner, &second_salt));
+    assert_eq!(token::Client::new(&env, &token).balance(&owner), 1_200);
+}
+
+#[test]
+fn test_chained_escrow_rejects_missing_prerequisite() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"stage-2");
+    let missing = BytesN::from_array(&env, &[9; 32]);
+
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &700);
+    let options = EscrowOptions {
+        next_of: Some(missing),
+        ..Default::default()
+    };
+    let chained = client.deposit_with_options(&token, &700, &owner, &salt, &0, &options);
+
+    let result = client.try_withdraw(&token, &700, &chained, &owner, &salt);
+    assert_contract_error(result, QuickexError::PrerequisiteNotMet);
+}
+
+// ============================================================================
+// Escrow owner lookup
+// ============================================================================
+
+#[test]
+fn test_escrow_owner_visible_for_public_escrow() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"contact");
+    let commitment = setup_fee_escrow(&env, &client, &token, &owner, 1_000, &salt);
+
+    assert_eq!(
+        client.escrow_owner(&commitment, &stranger),
+        Some(owner.clone())
+    );
+    let missing = BytesN::from_array(&env, &[5; 32]);
+    assert_eq!(client.escrow_owner(&missing, &owner), None);
+}
+
+#[test]
+fn test_escrow_owner_hidden_for_private_escrow() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let viewer = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"contact");
+    let commitment = setup_fee_escrow(&env, &client, &token, &owner, 1_000, &salt);
+    client.set_privacy(&owner, &true);
+    client.grant_viewer(&owner, &viewer);
+
+    assert_eq!(client.escrow_owner(&commitment, &stranger), None);
+    assert_eq!(
+        client.escrow_owner(&commitment, &viewer),
+        Some(owner.clone())
+    );
+    assert_eq!(
+        client.escrow_owner(&commitment, &owner),
+        Some(owner.clone())
+    );
+
+    client.set_privacy(&owner, &false);
+    client.set_stealth(&owner, &true);
+    assert_eq!(client.escrow_owner(&commitment, &stranger), None);
+}
+
+// ============================================================================
+// Configurable batch size
+// ============================================================================
+
+fn commitment_batch(env: &Env, len: u32) -> soroban_sdk::Vec<BytesN<32>> {
+    let mut commitments = soroban_sdk::Vec::new(env);
+    for i in 0..len {
+        commitments.push_back(BytesN::from_array(env, &[i as u8; 32]));
+    }
+    commitments
+}
+
+#[test]
+fn test_max_batch_size_defaults_to_fifty() {
+    let (env, client) = setup();
+    assert_eq!(client.max_batch_size(), 50);
+
+    let states = client.get_states(&commitment_batch(&env, 50));
+    assert_eq!(states.len(), 50);
+    let result = client.try_get_states(&commitment_batch(&env, 51));
+    assert_contract_error(result, QuickexError::BatchTooLarge);
+}
+
+#[test]
+fn test_set_max_batch_size_applies_to_batches() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_max_batch_size(&admin, &3);
+    assert_eq!(client.max_batch_size(), 3);
+
+    assert_eq!(client.get_states(&commitment_batch(&env, 3)).len(), 3);
+    let result = client.try_get_states(&commitment_batch(&env, 4));
+    assert_contract_error(result, QuickexError::BatchTooLarge);
+
+    let mut viewers = soroban_sdk::Vec::new(&env);
+    for _ in 0..4 {
+        viewers.push_back(Address::generate(&env));
+    }
+    let result = client.try_grant_viewers(&owner, &viewers);
+    assert_contract_error(result, QuickexError::BatchTooLarge);
+    viewers.pop_back();
+    client.grant_viewers(&owner, &viewers);
+}
+
+#[test]
+fn test_set_max_batch_size_validation() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    client.initialize(&admin);
+
+    let result = client.try_set_max_batch_size(&stranger, &10);
+    assert_contract_error(result, QuickexError::Unauthorized);
+    let result = client.try_set_max_batch_size(&admin, &0);
+    assert_contract_error(result, QuickexError::InvalidAmount);
+    assert_eq!(client.max_batch_size(), 50);
+}
+
+// ============================================================================
+// Escrows by owner
+// ============================================================================
+
+#[test]
+fn test_get_escrows_by_owner_pages_active_escrows() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let first_salt = Bytes::from_slice(&env, b"first");
+    let first = setup_fee_escrow(&env, &client, &token, &owner, 100, &first_salt);
+    let second = setup_fee_escrow(
+        &env,
+        &client,
+        &token,
+        &owner,
+        200,
+        &Bytes::from_slice(&env, b"second"),
+    );
+    let third = setup_fee_escrow(
+        &env,
+        &client,
+        &token,
+        &owner,
+        300,
+        &Bytes::from_slice(&env, b"third"),
+    );
+
+    let page = client.get_escrows_by_owner(&owner, &0, &2, &stranger);
+    assert_eq!(page, soroban_sdk::vec![&env, first.clone(), second.clone()]);
+    let page = client.get_escrows_by_owner(&owner, &2, &2, &stranger);
+    assert_eq!(page, soroban_sdk::vec![&env, third.clone()]);
+    assert!(client
+        .get_escrows_by_owner(&owner, &3, &2, &stranger)
+        .is_empty());
+
+    client.withdraw(&token, &100, &first, &owner, &first_salt);
+    let page = client.get_escrows_by_owner(&owner, &0, &10, &owner);
+    assert_eq!(page, soroban_sdk::vec![&env, second, third]);
+}
+
+#[test]
+fn test_get_escrows_by_owner_hidden_from_strangers_when_private() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let viewer = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"listed");
+    let commitment = setup_fee_escrow(&env, &client, &token, &owner, 100, &salt);
+    client.set_privacy(&owner, &true);
+    client.grant_viewer(&owner, &viewer);
+
+    assert!(client
+        .get_escrows_by_owner(&owner, &0, &10, &stranger)
+        .is_empty());
+    assert_eq!(
+        client.get_escrows_by_owner(&owner, &0, &10, &viewer),
+        soroban_sdk::vec![&env, commitment.clone()]
+    );
+    assert_eq!(
+        client.get_escrows_by_owner(&owner, &0, &10, &owner),
+        soroban_sdk::vec![&env, commitment]
+    );
+}
+
+// ============================================================================
+// Refund and reassign
+// ============================================================================
+
+#[test]
+fn test_refund_and_reassign_moves_funds_to_new_escrow() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let token_client = token::Client::new(&env, &token);
+    let owner = Address::generate(&env);
+    let old_recipient = Address::generate(&env);
+    let new_recipient = Address::generate(&env);
+    let amount: i128 = 1_000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+    let old_salt = Bytes::from_slice(&env, b"old");
+    let old = client.create_escrow_cpi(&owner, &token, &amount, &old_recipient, &old_salt, &100);
+
+    env.ledger().set_timestamp(100);
+    let new_salt = Bytes::from_slice(&env, b"new");
+    let new = client
+        .refund_and_reassign(&old, &owner, &new_recipient, &new_salt, &500)
+        .unwrap();
+
+    assert_eq!(
+        client.get_commitment_state(&old),
+        Some(EscrowStatus::Refunded)
+    );
+    assert_eq!(
+        client.get_commitment_state(&new),
+        Some(EscrowStatus::Pending)
+    );
+    assert_eq!(token_client.balance(&owner), 0);
+    assert_eq!(token_client.balance(&client.address), amount);
+    assert_eq!(
+        client.get_escrows_by_owner(&owner, &0, &10, &owner),
+        soroban_sdk::vec![&env, new.clone()]
+    );
+
+    client.withdraw(&token, &amount, &new, &new_recipient, &new_salt);
+    assert_eq!(token_client.balance(&new_recipient), amount);
+}
+
+#[test]
+fn test_refund_and_reassign_requires_expiry_and_owner() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let new_recipient = Address::generate(&env);
+    let amount: i128 = 1_000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+    let salt = Bytes::from_slice(&env, b"old");
+    let old = client.deposit(&token, &amount, &owner, &salt, &100);
+    let new_salt = Bytes::from_slice(&env, b"new");
+
+    let result = client.try_refund_and_reassign(&old, &owner, &new_recipient, &new_salt, &0);
+    assert_contract_error(result, QuickexError::EscrowNotExpired);
+
+    env.ledger().set_timestamp(100);
+    let result = client.try_refund_and_reassign(&old, &stranger, &new_recipient, &new_salt, &0);
+    assert_contract_error(result, QuickexError::InvalidOwner);
+    assert_eq!(
+        client.get_commitment_state(&old),
+        Some(EscrowStatus::Pending)
+    );
+}
+
+// ============================================================================
+// Privacy levels in escrow details
+// ============================================================================
+
+fn setup_level_escrow(
+    env: &Env,
+    client: &QuickexContractClient,
+    level: u32,
+) -> (Address, Address, BytesN<32>) {
+    let token = create_test_token(env);
+    let owner = Address::generate(env);
+    let salt = Bytes::from_slice(env, b"levels");
+    let commitment = setup_fee_escrow(env, client, &token, &owner, 1_000, &salt);
+    client.enable_privacy(&owner, &level);
+    (token, owner, commitment)
+}
+
+#[test]
+fn test_escrow_details_privacy_levels_for_stranger() {
+    let (env, client) = setup();
+    let stranger = Address::generate(&env);
+
+    let (token, owner, commitment) = setup_level_escrow(&env, &client, 0);
+    let view = client.get_escrow_details(&commitment, &stranger).unwrap();
+    assert_eq!(view.amount, Some(1_000));
+    assert_eq!(view.owner, Some(owner));
+    assert_eq!(view.token, Some(token));
+
+    let (token, _, commitment) = setup_level_escrow(&env, &client, 1);
+    let view = client.get_escrow_details(&commitment, &stranger).unwrap();
+    assert_eq!(view.amount, None);
+    assert!(view.owner.is_some());
+    assert_eq!(view.token, Some(token));
+
+    let (token, _, commitment) = setup_level_escrow(&env, &client, 2);
+    let view = client.get_escrow_details(&commitment, &stranger).unwrap();
+    assert_eq!(view.amount, None);
+    assert_eq!(view.owner, None);
+    assert_eq!(view.token, Some(token));
+    assert_eq!(view.expires_at, Some(0));
+
+    let (_, _, commitment) = setup_level_escrow(&env, &client, 3);
+    let view = client.get_escrow_details(&commitment, &stranger).unwrap();
+    assert_eq!(view.amount, None);
+    assert_eq!(view.owner, None);
+    assert_eq!(view.token, None);
+    assert_eq!(view.created_at, None);
+    assert_eq!(view.expires_at, None);
+    assert_eq!(view.status, EscrowStatus::Pending);
+}
+
+#[test]
+fn test_gated_reads_follow_privacy_levels_for_stranger() {
+    let (env, client) = setup();
+    let stranger = Address::generate(&env);
+    for level in 0..=3u32 {
+        let (_, owner, commitment) = setup_level_escrow(&env, &client, level);
+        let amount_visible = level < 1;
+        let owner_visible = level < 2;
+
+        assert_eq!(
+            client
+                .escrow_amount_scaled(&commitment, &stranger)
+                .is_some(),
+            amount_visible
+        );
+        assert_eq!(
+            client.amount_discrepancy(&commitment, &1_000, &stranger),
+            amount_visible.then_some(0)
+        );
+        assert_eq!(
+            client.max_withdrawable(&commitment, &stranger),
+            if amount_visible { 1_000 } else { 0 }
+        );
+        assert_eq!(
+            client.owner_stats(&owner, &stranger).is_some(),
+            amount_visible
+        );
+        assert_eq!(
+            client.escrow_owner(&commitment, &stranger),
+            owner_visible.then_some(owner.clone())
+        );
+        assert_eq!(
+            client.same_owner(&commitment, &commitment, &stranger),
+            owner_visible.then_some(true)
+        );
+        assert_eq!(
+            client
+                .get_escrows_by_owner(&owner, &0, &10, &stranger)
+                .len(),
+            owner_visible as u32
+        );
+        assert_eq!(
+            client.oldest_active_escrow(&owner, &stranger),
+            owner_visible.then_some(commitment.clone())
+        );
+
+        // The owner always sees everything.
+        assert!(client.escrow_amount_scaled(&commitment, &owner).is_some());
+        assert_eq!(
+            client.escrow_owner(&commitment, &owner),
+            Some(owner.clone())
+        );
+    }
+}
+
+#[test]
+fn test_escrow_details_privacy_levels_for_owner() {
+    let (env, client) = setup();
+    for level in 0..=3u32 {
+        let (token, owner, commitment) = setup_level_escrow(&env, &client, level);
+        let view = client.get_escrow_details(&commitment, &owner).unwrap();
+        assert_eq!(view.amount, Some(1_000));
+        assert_eq!(view.owner, Some(owner));
+        assert_eq!(view.token, Some(token));
+        assert_eq!(view.created_at, Some(0));
+    }
+}
+
+#[test]
+fn test_escrow_details_boolean_privacy_maps_to_level_two() {
+    let (env, client) = setup();
+    let stranger = Address::generate(&env);
+    let (token, owner, commitment) = setup_level_escrow(&env, &client, 0);
+    client.set_privacy(&owner, &true);
+
+    let view = client.get_escrow_details(&commitment, &stranger).unwrap();
+    assert_eq!(view.amount, None);
+    assert_eq!(view.owner, None);
+    assert_eq!(view.token, Some(token));
+
+    client.enable_privacy(&owner, &3);
+    let view = client.get_escrow_details(&commitment, &stranger).unwrap();
+    assert_eq!(view.token, None);
+    assert!(client.is_escrow_private(&commitment));
+}
+
+// ============================================================================
+// Granted viewer count
+// ============================================================================
+
+#[test]
+fn test_granted_count_tracks_grants_and_revocations() {
+    let (env, client) = setup();
+    let viewer = Address::generate(&env);
+    let first_owner = Address::generate(&env);
+    let second_owner = Address::generate(&env);
+    assert_eq!(client.granted_count(&viewer), 0);
+
+    client.grant_viewer(&first_owner, &viewer);
+    client.grant_viewer(&second_owner, &viewer);
+    client.grant_viewer(&second_owner, &viewer);
+    assert_eq!(client.granted_count(&viewer), 2);
+
+    client.revoke_viewer(&first_owner, &viewer);
+    assert_eq!(client.granted_count(&viewer), 1);
+}
+
+// ============================================================================
+// Marking escrows expired
+// ============================================================================
+
+#[test]
+fn test_mark_expired_then_refund() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1_000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+    let salt = Bytes::from_slice(&env, b"lapse");
+    let commitment = client.deposit(&token, &amount, &owner, &salt, &100);
+
+    let result = client.try_mark_expired(&commitment);
+    assert_contract_error(result, QuickexError::EscrowNotExpired);
+
+    env.ledger().set_timestamp(100);
+    client.mark_expired(&commitment);
+    assert_eq!(
+        client.get_commitment_state(&commitment),
+        Some(EscrowStatus::Expired)
+    );
+    assert_eq!(client.status_counts().expired, 1);
+    assert_eq!(client.status_counts().pending, 0);
+    client.mark_expired(&commitment);
+
+    let result = client.try_withdraw(&token, &amount, &commitment, &owner, &salt);
+    assert_contract_error(result, QuickexError::EscrowExpired);
+
+    client.refund(&commitment, &owner);
+    assert_eq!(
+        client.get_commitment_state(&commitment),
+        Some(EscrowStatus::Refunded)
+    );
+    assert_eq!(token::Client::new(&env, &token).balance(&owner), amount);
+    assert_eq!(client.status_counts().expired, 0);
+
+    let result = client.try_mark_expired(&commitment);
+    assert_contract_error(result, QuickexError::AlreadySpent);
+}
+
+// ============================================================================
+// Enumeration ordering
+// ============================================================================
+
+#[test]
+fn test_enumeration_keeps_deposit_order_across_operations() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let amount: i128 = 100;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &(amount * 5));
+
+    let mut commitments = soroban_sdk::Vec::new(&env);
+    for i in 0..5u8 {
+        let salt = Bytes::from_array(&env, &[i; 4]);
+        let timeout = if i == 3 { 10 } else { 0 };
+        commitments.push_back(client.deposit(&token, &amount, &owner, &salt, &timeout));
+    }
+
+    client.withdraw(
+        &token,
+        &amount,
+        &commitments.get(1).unwrap(),
+        &owner,
+        &Bytes::from_array(&env, &[1; 4]),
+    );
+    env.ledger().set_timestamp(10);
+    client.refund(&commitments.get(3).unwrap(), &owner);
+    let salt = Bytes::from_array(&env, &[5; 4]);
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+    let late = client.deposit(&token, &amount, &owner, &salt, &0);
+
+    assert_eq!(
+        client.get_escrows_by_owner(&owner, &0, &10, &owner),
+        soroban_sdk::vec![
+            &env,
+            commitments.get(0).unwrap(),
+            commitments.get(2).unwrap(),
+            commitments.get(4).unwrap(),
+            late.clone()
+        ]
+    );
+
+    let mut all = commitments.clone();
+    all.push_back(late);
+    assert_eq!(client.escrows_created_between(&0, &10, &1, &10), all);
+}
+
+// ============================================================================
+// Designated beneficiary
+// ============================================================================
+
+#[test]
+fn test_deposit_to_beneficiary_withdraws() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let token_client = token::Client::new(&env, &token);
+    let owner = Address::generate(&env);
+    let beneficiary = Address::generate(&env);
+    let amount: i128 = 1_000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+    let salt = Bytes::from_slice(&env, b"payment");
+
+    let commitment = client.deposit_to(&token, &amount, &owner, &beneficiary, &salt, &0);
+    let view = client.get_escrow_details(&commitment, &owner).unwrap();
+    assert_eq!(view.owner, Some(owner.clone()));
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_strict_owner_check(&admin, &true);
+    client.withdraw(&token, &amount, &commitment, &beneficiary, &salt);
+    assert_eq!(token_client.balance(&beneficiary), amount);
+    assert_eq!(token_client.balance(&owner), 0);
+}
+
+#[test]
+fn test_deposit_to_refunds_only_to_owner() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let token_client = token::Client::new(&env, &token);
+    let owner = Address::generate(&env);
+    let beneficiary = Address::generate(&env);
+    let amount: i128 = 1_000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+    let salt = Bytes::from_slice(&env, b"payment");
+    let commitment = client.deposit_to(&token, &amount, &owner, &beneficiary, &salt, &100);
+
+    env.ledger().set_timestamp(100);
+    let result = client.try_refund(&commitment, &beneficiary);
+    assert_contract_error(result, QuickexError::InvalidOwner);
+    client.refund(&commitment, &owner);
+    assert_eq!(token_client.balance(&owner), amount);
+    assert_eq!(token_client.balance(&beneficiary), 0);
+}
+
+// ============================================================================
+// Activity snapshots
+// ============================================================================
+
+#[test]
+fn test_snapshot_activity_respects_interval() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"snap");
+    let commitment = setup_fee_escrow(&env, &client, &token, &owner, 1_000, &salt);
+
+    env.ledger().set_timestamp(1_000);
+    let first = client.snapshot_activity();
+    assert_eq!(
+        first,
+        ActivitySnapshot {
+            timestamp: 1_000,
+            total_escrows: 1,
+            active_escrows: 1,
+            tvl_sum: 1_000,
+        }
+    );
+
+    env.ledger().set_timestamp(1_000 + 86_399);
+    let result = client.try_snapshot_activity();
+    assert_contract_error(result, QuickexError::SnapshotTooSoon);
+
+    client.withdraw(&token, &1_000, &commitment, &owner, &salt);
+    env.ledger().set_timestamp(1_000 + 86_400);
+    let second = client.snapshot_activity();
+    assert_eq!(second.active_escrows, 0);
+    assert_eq!(second.tvl_sum, 0);
+
+    assert_eq!(
+        client.get_snapshots(&10),
+        soroban_sdk::vec![&env, second.clone(), first]
+    );
+    assert_eq!(client.get_snapshots(&1), soroban_sdk::vec![&env, second]);
+}
+
+#[test]
+fn test_snapshot_buffer_is_bounded() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_snapshot_interval(&admin, &0);
+
+    for i in 0..35u64 {
+        env.ledger().set_timestamp(i);
+        client.snapshot_activity();
+    }
+    let snapshots = client.get_snapshots(&100);
+    assert_eq!(snapshots.len(), 30);
+    assert_eq!(snapshots.get(0).unwrap().timestamp, 34);
+    assert_eq!(snapshots.get(29).unwrap().timestamp, 5);
+}
+
+// ============================================================================
+// Admin sweep of abandoned escrows
+// ============================================================================
+
+#[test]
+fn test_sweep_expired_waits_for_grace_period() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    client.initialize(&admin);
+    let token = create_test_token(&env);
+    let token_client = token::Client::new(&env, &token);
+    let owner = Address::generate(&env);
+    let amount: i128 = 1_000;
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &amount);
+    let salt = Bytes::from_slice(&env, b"abandoned");
+    let commitment = client.deposit(&token, &amount, &owner, &salt, &100);
+
+    let grace = client.sweep_grace_period();
+    assert_eq!(grace, 90 * 24 * 60 * 60);
+    env.ledger().set_timestamp(100 + grace - 1);
+    let result = client.try_sweep_expired(&admin, &commitment, &treasury);
+    assert_contract_error(result, QuickexError::EscrowNotExpired);
+
+    let stranger = Address::generate(&env);
+    env.ledger().set_timestamp(100 + grace);
+    let result = client.try_sweep_expired(&stranger, &commitment, &stranger);
+    assert_contract_error(result, QuickexError::Unauthorized);
+
+    client.sweep_expired(&admin, &commitment, &treasury);
+    assert_eq!(token_client.balance(&treasury), amount);
+    assert_eq!(
+        client.get_commitment_state(&commitment),
+        Some(EscrowStatus::Refunded)
+    );
+    let result = client.try_refund(&commitment, &owner);
+    assert_contract_error(result, QuickexError::AlreadySpent);
+}
+
+#[test]
+fn test_sweep_expired_uses_configured_grace_period() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_sweep_grace_period(&admin, &50);
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"forever");
+    let open_ended = setup_fee_escrow(&env, &client, &token, &owner, 1_000, &salt);
+
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &500);
+    let salt = Bytes::from_slice(&env, b"abandoned");
+    let commitment = client.deposit(&token, &500, &owner, &salt, &100);
+
+    env.ledger().set_timestamp(150);
+    client.sweep_expired(&admin, &commitment, &treasury);
+    assert_eq!(token::Client::new(&env, &token).balance(&treasury), 500);
+
+    let result = client.try_sweep_expired(&admin, &open_ended, &treasury);
+    assert_contract_error(result, QuickexError::EscrowNotExpired);
+}
+
+// ============================================================================
+// Range-commitment escrows
+// ============================================================================
+
+#[test]
+fn test_withdraw_in_range_pays_stored_amount() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let token_client = token::Client::new(&env, &token);
+    let owner = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &1_234);
+    let salt = Bytes::from_slice(&env, b"bucket");
+
+    let commitment = client.deposit_in_range(&token, &1_234, &owner, &1_000, &2_000, &salt, &0);
+    assert!(client.verify_range_commitment(&commitment, &owner, &1_000, &2_000, &salt, &1_234));
+
+    assert!(client.withdraw_in_range(&1_000, &2_000, &salt, &owner));
+    assert_eq!(token_client.balance(&owner), 1_234);
+    assert_eq!(
+        client.get_commitment_state(&commitment),
+        Some(EscrowStatus::Spent)
+    );
+}
+
+#[test]
+fn test_withdraw_in_range_rejects_mismatched_bounds() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &1_234);
+    let salt = Bytes::from_slice(&env, b"bucket");
+    client.deposit_in_range(&token, &1_234, &owner, &1_000, &2_000, &salt, &0);
+
+    let result = client.try_withdraw_in_range(&1_000, &1_999, &salt, &owner);
+    assert_contract_error(result, QuickexError::CommitmentNotFound);
+    let result = client.try_withdraw_in_range(&1_000, &2_000, &salt, &Address::generate(&env));
+    assert_contract_error(result, QuickexError::CommitmentNotFound);
+
+    let result = client.try_deposit_in_range(&token, &999, &owner, &1_000, &2_000, &salt, &0);
+    assert_contract_error(result, QuickexError::InvalidAmount);
+}
+
+// ============================================================================
+// Batch deposits
+// ============================================================================
+
+#[test]
+fn test_deposit_batch_creates_escrows_in_order() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let token_client = token::Client::new(&env, &token);
+    let owner = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &600);
+    let first_salt = Bytes::from_slice(&env, b"one");
+    let second_salt = Bytes::from_slice(&env, b"two");
+    let entries = soroban_sdk::vec![
+        &env,
+        (100_i128, first_salt.clone(), 0_u64),
+        (500_i128, second_salt.clone(), 60_u64)
+    ];
+
+    let commitments = client.deposit_batch(&token, &owner, &entries);
+    assert_eq!(commitments.len(), 2);
+    assert_eq!(
+        commitments.get(0).unwrap(),
+        client.create_amount_commitment(&owner, &100, &first_salt)
+    );
+    assert_eq!(token_client.balance(&owner), 0);
+    assert_eq!(token_client.balance(&client.address), 600);
+    let view = client
+        .get_escrow_details(&commitments.get(1).unwrap(), &owner)
+        .unwrap();
+    assert_eq!(view.expires_at, Some(60));
+
+    client.withdraw(
+        &token,
+        &500,
+        &commitments.get(1).unwrap(),
+        &owner,
+        &second_salt,
+    );
+    assert_eq!(token_client.balance(&owner), 500);
+}
+
+#[test]
+fn test_deposit_batch_reverts_whole_batch() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let token_client = token::Client::new(&env, &token);
+    let owner = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &1_000);
+    let salt = Bytes::from_slice(&env, b"dup");
+
+    let duplicate = soroban_sdk::vec![
+        &env,
+        (100_i128, salt.clone(), 0_u64),
+        (100_i128, salt.clone(), 0_u64)
+    ];
+    let result = client.try_deposit_batch(&token, &owner, &duplicate);
+    assert_contract_error(result, QuickexError::CommitmentAlreadyExists);
+
+    let non_positive =
+        soroban_sdk::vec![&env, (100_i128, salt.clone(), 0_u64), (0_i128, salt, 0_u64)];
+    let result = client.try_deposit_batch(&token, &owner, &non_positive);
+    assert_contract_error(result, QuickexError::InvalidAmount);
+
+    assert_eq!(token_client.balance(&owner), 1_000);
+    assert_eq!(client.status_counts().pending, 0);
+
+    let mut oversized = soroban_sdk::Vec::new(&env);
+    for i in 0..51u32 {
+        oversized.push_back((1_i128, Bytes::from_array(&env, &i.to_be_bytes()), 0_u64));
+    }
+    let result = client.try_deposit_batch(&token, &owner, &oversized);
+    assert_contract_error(result, QuickexError::BatchTooLarge);
+}
+
+// ============================================================================
+// Owner statistics
+// ============================================================================
+
+#[test]
+fn test_owner_stats_reports_activity() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"first");
+    let first = setup_fee_escrow(&env, &client, &token, &owner, 100, &salt);
+    setup_fee_escrow(
+        &env,
+        &client,
+        &token,
+        &owner,
+        250,
+        &Bytes::from_slice(&env, b"second"),
+    );
+    client.withdraw(&token, &100, &first, &owner, &salt);
+
+    let expected = OwnerStats {
+        active_count: 1,
+        total_created: 2,
+        total_value_locked: 250,
+    };
+    assert_eq!(client.owner_stats(&owner, &owner), Some(expected.clone()));
+    assert_eq!(client.owner_stats(&owner, &stranger), Some(expected));
+}
+
+#[test]
+fn test_owner_stats_hidden_for_private_owner() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let viewer = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    setup_fee_escrow(
+        &env,
+        &client,
+        &token,
+        &owner,
+        100,
+        &Bytes::from_slice(&env, b"s"),
+    );
+    client.set_privacy(&owner, &true);
+    client.grant_viewer(&owner, &viewer);
+
+    assert_eq!(client.owner_stats(&owner, &stranger), None);
+    assert_eq!(
+        client
+            .owner_stats(&owner, &viewer)
+            .unwrap()
+            .total_value_locked,
+        100
+    );
+    assert_eq!(client.owner_stats(&owner, &owner).unwrap().active_count, 1);
+}
+
+// ============================================================================
+// Refund allow-list
+// ============================================================================
+
+#[test]
+fn test_refund_allow_list_rejects_unlisted_owner() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"listed");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &500);
+    let commitment = client.deposit(&token, &500, &owner, &salt, &100);
+    env.ledger().set_timestamp(env.ledger().timestamp() + 100);
+
+    client.set_refund_recipient_allowed(&admin, &Address::generate(&env), &true);
+    assert_contract_error(
+        client.try_refund(&commitment, &owner),
+        QuickexError::RecipientNotAllowed,
+    );
+    assert_eq!(
+        client.get_commitment_state(&commitment),
+        Some(EscrowStatus::Pending)
+    );
+}
+
+#[test]
+fn test_refund_allow_list_permits_listed_owner() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"listed");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &500);
+    let commitment = client.deposit(&token, &500, &owner, &salt, &100);
+    env.ledger().set_timestamp(env.ledger().timestamp() + 100);
+
+    client.set_refund_recipient_allowed(&admin, &owner, &true);
+    assert_eq!(
+        client.refund_allow_list(),
+        soroban_sdk::vec![&env, owner.clone()]
+    );
+    client.refund(&commitment, &owner);
+    assert_eq!(token::Client::new(&env, &token).balance(&owner), 500);
+
+    client.set_refund_recipient_allowed(&admin, &owner, &false);
+    assert!(client.refund_allow_list().is_empty());
+}
+
+// ============================================================================
+// Stuck escrows
+// ============================================================================
+
+#[test]
+fn test_is_stuck_reports_old_non_expiring_escrow() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let commitment = setup_fee_escrow(
+        &env,
+        &client,
+        &token,
+        &owner,
+        100,
+        &Bytes::from_slice(&env, b"lost"),
+    );
+    let start = env.ledger().timestamp();
+
+    env.ledger().set_timestamp(start + DEFAULT_STUCK_AGE - 1);
+    assert!(!client.is_stuck(&commitment));
+    env.ledger().set_timestamp(start + DEFAULT_STUCK_AGE);
+    assert!(client.is_stuck(&commitment));
+}
+
+#[test]
+fn test_is_stuck_ignores_expiring_escrow() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    client.set_stuck_age(&admin, &1_000);
+    assert_eq!(client.stuck_age(), 1_000);
+
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &100);
+    let salt = Bytes::from_slice(&env, b"expiring");
+    let commitment = client.deposit(&token, &100, &owner, &salt, &500);
+
+    env.ledger()
+        .set_timestamp(env.ledger().timestamp() + 10_000);
+    assert!(!client.is_stuck(&commitment));
+    assert!(!client.is_stuck(&BytesN::from_array(&env, &[9u8; 32])));
+}
+
+// ============================================================================
+// Token allow-list
+// ============================================================================
+
+#[test]
+fn test_token_allow_list_rejects_unlisted_token() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let listed = create_test_token(&env);
+    let unlisted = create_test_token(&env);
+    let owner = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &unlisted).mint(&owner, &100);
+    let salt = Bytes::from_slice(&env, b"token");
+
+    // An empty allow-list accepts any token.
+    assert!(client.is_token_allowed(&unlisted));
+
+    client.add_allowed_token(&admin, &listed);
+    assert!(!client.is_token_allowed(&unlisted));
+    assert_contract_error(
+        client.try_deposit(&unlisted, &100, &owner, &salt, &0),
+        QuickexError::TokenNotAllowed,
+    );
+    let commitment = BytesN::from_array(&env, &[3u8; 32]);
+    assert_contract_error(
+        client.try_deposit_with_commitment(&owner, &unlisted, &100, &commitment, &0),
+        QuickexError::TokenNotAllowed,
+    );
+
+    // Removing the only listed token makes the list permissive again.
+    client.remove_allowed_token(&admin, &listed);
+    client.deposit(&unlisted, &100, &owner, &salt, &0);
+}
+
+#[test]
+fn test_token_allow_list_accepts_listed_token() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let token = create_test_token(&env);
+    client.add_allowed_token(&admin, &token);
+    client.add_allowed_token(&admin, &token);
+
+    let owner = Address::generate(&env);
+    let commitment = setup_fee_escrow(
+        &env,
+        &client,
+        &token,
+        &owner,
+        100,
+        &Bytes::from_slice(&env, b"ok"),
+    );
+    assert_eq!(
+        client.get_commitment_state(&commitment),
+        Some(EscrowStatus::Pending)
+    );
+}
+
+#[test]
+fn test_rescue_stuck_requires_pause_and_age() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let recovery = Address::generate(&env);
+    let commitment = setup_fee_escrow(
+        &env,
+        &client,
+        &token,
+        &owner,
+        100,
+        &Bytes::from_slice(&env, b"lost"),
+    );
+    let start = env.ledger().timestamp();
+
+    env.ledger().set_timestamp(start + DEFAULT_STUCK_AGE);
+    assert_contract_error(
+        client.try_rescue_stuck(&admin, &commitment, &recovery),
+        QuickexError::ContractNotPaused,
+    );
+
+    client.set_paused(&admin, &true);
+    env.ledger().set_timestamp(start + DEFAULT_STUCK_AGE - 1);
+    assert_contract_error(
+        client.try_rescue_stuck(&admin, &commitment, &recovery),
+        QuickexError::EscrowNotStuck,
+    );
+
+    env.ledger().set_timestamp(start + DEFAULT_STUCK_AGE);
+    client.rescue_stuck(&admin, &commitment, &recovery);
+    assert_eq!(token::Client::new(&env, &token).balance(&recovery), 100);
+    assert_eq!(
+        client.get_commitment_state(&commitment),
+        Some(EscrowStatus::Refunded)
+    );
+    assert_contract_error(
+        client.try_rescue_stuck(&admin, &commitment, &recovery),
+        QuickexError::EscrowNotStuck,
+    );
+}
+
+#[test]
+fn test_rescue_stuck_rejects_expiring_escrow() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &100);
+    let commitment = client.deposit(&token, &100, &owner, &Bytes::from_slice(&env, b"exp"), &500);
+
+    client.set_paused(&admin, &true);
+    env.ledger()
+        .set_timestamp(env.ledger().timestamp() + DEFAULT_STUCK_AGE);
+    assert_contract_error(
+        client.try_rescue_stuck(&admin, &commitment, &Address::generate(&env)),
+        QuickexError::EscrowNotStuck,
+    );
+}
+
+// ============================================================================
+// Duplicate deposits
+// ============================================================================
+
+#[test]
+fn test_duplicate_deposit_rejected() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"twice");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &200);
+    let commitment = client.deposit(&token, &100, &owner, &salt, &0);
+
+    assert_contract_error(
+        client.try_deposit(&token, &100, &owner, &salt, &0),
+        QuickexError::CommitmentAlreadyExists,
+    );
+    assert_eq!(client.status_counts().pending, 1);
+    assert_eq!(client.escrow_count_by_token(&token), 1);
+    assert_eq!(token::Client::new(&env, &token).balance(&owner), 100);
+    assert!(client.withdraw(&token, &100, &commitment, &owner, &salt));
+}
+
+#[test]
+fn test_salt_hash_deposit_cannot_replace_plain_deposit() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"hashed");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &200);
+
+    let salt_hash: BytesN<32> = env.crypto().sha256(&salt).into();
+    let hash_bytes: Bytes = salt_hash.clone().into();
+    client.deposit(&token, &100, &owner, &hash_bytes, &0);
+    assert_contract_error(
+        client.try_deposit_with_salt_hash(&token, &100, &owner, &salt_hash, &0),
+        QuickexError::CommitmentAlreadyExists,
+    );
+}
+
+// ============================================================================
+// Stealth mode on narrow reads
+// ============================================================================
+
+#[test]
+fn test_narrow_reads_hide_stealth_owner_from_strangers() {
+    let (env, client) = setup();
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let viewer = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let first = setup_fee_escrow(
+        &env,
+        &client,
+        &token,
+        &owner,
+        100,
+        &Bytes::from_slice(&env, b"a"),
+    );
+    let second = setup_fee_escrow(
+        &env,
+        &client,
+        &token,
+        &owner,
+        200,
+        &Bytes::from_slice(&env, b"b"),
+    );
+    client.set_stealth(&owner, &true);
+    client.grant_viewer(&owner, &viewer);
+
+    assert_eq!(client.same_owner(&first, &second, &stranger), None);
+    assert_eq!(client.oldest_active_escrow(&owner, &stranger), None);
+    assert!(client.escrow_amount_scaled(&first, &stranger).is_none());
+
+    assert_eq!(client.same_owner(&first, &second, &viewer), Some(true));
+    assert_eq!(
+        client.oldest_active_escrow(&owner, &viewer),
+        Some(first.clone())
+    );
+    assert_eq!(client.escrow_amount_scaled(&first, &owner).unwrap().0, 100);
+}
+
+#[test]
+fn test_refund_with_fallback_pays_allow_listed_fallback() {
+    let (env, client) = setup();
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+    let token = create_test_token(&env);
+    let owner = Address::generate(&env);
+    let fallback = Address::generate(&env);
+    let salt = Bytes::from_slice(&env, b"listed");
+    token::StellarAssetClient::new(&env, &token).mint(&owner, &500);
+    let commitment = client.deposit(&token, &500, &owner, &salt, &100);
+    env.ledger().set_timestamp(env.ledger().timestamp() + 100);
+
+    client.set_refund_recipient_allowed(&admin, &fallback, &true);
+    client.refund_with_fallback(&commitment, &owner, &fallback);
+
+    let token_client = token::Client::new(&env, &token);
+    assert_eq!(token_client.balance(&fallback), 500);
+    assert_eq!(token_client.balance(&owner), 0);
+}
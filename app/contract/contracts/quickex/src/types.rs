@@ -2,7 +2,9 @@
 //!
 //! See [`crate::storage`] for the storage schema and key layout.
 
-use soroban_sdk::{contracttype, Address};
+use soroban_sdk::{contracttype, Address, Bytes, BytesN, Vec};
+
+use crate::privacy::{AMOUNT_HIDDEN_LEVEL, OWNER_HIDDEN_LEVEL};
 
 /// Escrow entry status.
 ///
@@ -11,10 +13,13 @@ use soroban_sdk::{contracttype, Address};
 /// ```text
 /// [*] --> Pending  : deposit()
 /// Pending --> Spent    : withdraw(proof)  [current_time < expires_at]
+/// Pending --> Expired  : mark_expired()   [current_time >= expires_at]
 /// Pending --> Refunded : refund(owner)    [current_time >= expires_at]
+/// Expired --> Refunded : refund(owner)
 /// ```
 ///
 /// - `Pending`:  Funds are escrowed, awaiting withdrawal or refund.
+/// - `Expired`:  Past expiry and marked as such; awaiting refund.
 /// - `Spent`:    Withdrawal completed successfully. Terminal state.
 /// - `Refunded`: Owner reclaimed funds after timeout. Terminal state.
 #[contracttype]
@@ -22,17 +27,32 @@ use soroban_sdk::{contracttype, Address};
 pub enum EscrowStatus {
     Pending,
     Spent,
-    /// Kept for backwards-compat with any existing on-chain data; semantically
-    /// equivalent to an escrow that has passed expiry but not yet been refunded.
+    /// Set by `mark_expired` once a pending escrow has passed expiry; it can only
+    /// be refunded or forwarded from here. An unmarked `Pending` escrow past
+    /// expiry behaves the same.
     Expired,
     Refunded,
 }
 
+/// Tri-state view of an account's boolean privacy flag.
+///
+/// Returned by [`QuickexContract::privacy_state`]. Unlike `get_privacy`, it tells an
+/// account that never set privacy apart from one that explicitly disabled it.
+#[contracttype]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PrivacyState {
+    /// Privacy has never been set for the account.
+    Unset,
+    Enabled,
+    Disabled,
+}
+
 /// Escrow entry structure.
 ///
-/// Stored under [`DataKey::Escrow`](crate::storage::DataKey::Escrow)(commitment) in persistent storage.
 /// Each entry corresponds to one deposit, keyed by the commitment hash
-/// `SHA256(owner || amount || salt)`.
+/// `SHA256(owner || amount || salt)`. Not stored as is: [`put_escrow`](crate::storage::put_escrow)
+/// splits it into a [`StoredEscrow`] and an [`EscrowExtra`], and
+/// [`get_escrow`](crate::storage::get_escrow) joins them back.
 #[contracttype]
 #[derive(Clone)]
 pub struct EscrowEntry {
@@ -49,37 +69,405 @@ pub struct EscrowEntry {
     /// Ledger timestamp after which withdrawal is blocked and refund is enabled.
     /// A value of `0` means the escrow never expires (no timeout).
     pub expires_at: u64,
+    /// Seconds added to `expires_at` on each withdrawal (`0` = no extension).
+    pub extend_on_withdraw: u64,
+    /// If set, the only address withdrawals may be sent to.
+    pub allowed_recipient: Option<Address>,
+    /// `SHA256(salt)` for escrows created by `deposit_with_salt_hash`; such escrows
+    /// can only be withdrawn by revealing the matching salt.
+    pub salt_hash: Option<BytesN<32>>,
+    /// Owner nonce bound into the commitment for escrows created by
+    /// `deposit_nonced`; such escrows must be withdrawn with `withdraw_nonced`.
+    pub nonce: Option<u64>,
+    /// Ledger sequence at which the escrow was created.
+    pub created_ledger: u32,
+    /// Ledgers that must close after `created_ledger` before withdrawal is allowed.
+    pub min_confirmations: u32,
+    /// If set, an expired escrow is forwarded here instead of being refundable
+    /// to the owner.
+    pub on_expiry_recipient: Option<Address>,
+    /// Unlock timestamps for installment escrows, ascending. `amount` is released
+    /// in equal parts via `withdraw_matured` as each time passes.
+    pub installments: Option<Vec<u64>>,
+    /// Number of installments already released.
+    pub installments_claimed: u32,
+    /// Maximum number of times the owner may call `extend_timeout`. `None` means
+    /// unlimited.
+    pub max_extensions: Option<u32>,
+    /// Number of times `extend_timeout` has been applied.
+    pub extensions_used: u32,
+    /// Ledger timestamp before which the escrow cannot be withdrawn (the cliff).
+    /// `0` means no cliff.
+    pub earliest_withdraw: u64,
+    /// Commitment of an escrow that must be spent before this one can be
+    /// withdrawn. `None` means the escrow is not chained.
+    pub next_of: Option<BytesN<32>>,
+    /// Address with withdraw rights, when distinct from the owner. The commitment
+    /// is bound to it, and only it may withdraw; refunds still go to `owner`.
+    /// `None` means the commitment is bound to whoever holds the salt.
+    pub beneficiary: Option<Address>,
+    /// Sequential ID assigned when the escrow was recorded. `0` for escrows
+    /// created before IDs were kept in the entry.
+    pub escrow_id: u64,
+}
+
+impl EscrowEntry {
+    /// Join the two stored halves of an escrow.
+    pub fn from_parts(stored: StoredEscrow, extra: EscrowExtra) -> Self {
+        EscrowEntry {
+            token: stored.token,
+            amount: stored.amount,
+            owner: stored.owner,
+            status: stored.status,
+            created_at: stored.created_at,
+            expires_at: stored.expires_at,
+            extend_on_withdraw: extra.extend_on_withdraw,
+            allowed_recipient: extra.allowed_recipient,
+            salt_hash: extra.salt_hash,
+            nonce: extra.nonce,
+            created_ledger: extra.created_ledger,
+            min_confirmations: extra.min_confirmations,
+            on_expiry_recipient: extra.on_expiry_recipient,
+            installments: extra.installments,
+            installments_claimed: extra.installments_claimed,
+            max_extensions: extra.max_extensions,
+            extensions_used: extra.extensions_used,
+            earliest_withdraw: extra.earliest_withdraw,
+            next_of: extra.next_of,
+            beneficiary: extra.beneficiary,
+            escrow_id: extra.escrow_id,
+        }
+    }
+
+    /// Split the entry into the halves stored under separate keys.
+    pub fn into_parts(self) -> (StoredEscrow, EscrowExtra) {
+        (
+            StoredEscrow {
+                token: self.token,
+                amount: self.amount,
+                owner: self.owner,
+                status: self.status,
+                created_at: self.created_at,
+                expires_at: self.expires_at,
+            },
+            EscrowExtra {
+                extend_on_withdraw: self.extend_on_withdraw,
+                allowed_recipient: self.allowed_recipient,
+                salt_hash: self.salt_hash,
+                nonce: self.nonce,
+                created_ledger: self.created_ledger,
+                min_confirmations: self.min_confirmations,
+                on_expiry_recipient: self.on_expiry_recipient,
+                installments: self.installments,
+                installments_claimed: self.installments_claimed,
+                max_extensions: self.max_extensions,
+                extensions_used: self.extensions_used,
+                earliest_withdraw: self.earliest_withdraw,
+                next_of: self.next_of,
+                beneficiary: self.beneficiary,
+                escrow_id: self.escrow_id,
+            },
+        )
+    }
+}
+
+/// Core escrow fields, stored under
+/// [`DataKey::Escrow`](crate::storage::DataKey::Escrow)(commitment).
+///
+/// This is the layout escrows were stored with by the first release. Soroban only
+/// decodes a stored struct whose fields match exactly, so adding a field here
+/// would make every existing escrow unreadable; new per-escrow data goes in
+/// [`EscrowExtra`] instead.
+#[contracttype]
+#[derive(Clone)]
+pub struct StoredEscrow {
+    pub token: Address,
+    pub amount: i128,
+    pub owner: Address,
+    pub status: EscrowStatus,
+    pub created_at: u64,
+    pub expires_at: u64,
+}
+
+/// Per-escrow options and bookkeeping added after the first release, stored under
+/// [`DataKey::EscrowExtra`](crate::storage::DataKey::EscrowExtra)(commitment).
+///
+/// Escrows written before this key existed have no entry and read as the default:
+/// no options set and no escrow ID. Field meanings are documented on
+/// [`EscrowEntry`].
+#[contracttype]
+#[derive(Clone, Default)]
+pub struct EscrowExtra {
+    pub extend_on_withdraw: u64,
+    pub allowed_recipient: Option<Address>,
+    pub salt_hash: Option<BytesN<32>>,
+    pub nonce: Option<u64>,
+    pub created_ledger: u32,
+    pub min_confirmations: u32,
+    pub on_expiry_recipient: Option<Address>,
+    pub installments: Option<Vec<u64>>,
+    pub installments_claimed: u32,
+    pub max_extensions: Option<u32>,
+    pub extensions_used: u32,
+    pub earliest_withdraw: u64,
+    pub next_of: Option<BytesN<32>>,
+    pub beneficiary: Option<Address>,
+    pub escrow_id: u64,
+}
+
+/// Contract-wide number of escrows in each stored status.
+///
+/// Returned by [`QuickexContract::status_counts`]. Updated on every transition, so
+/// a `Pending` escrow that has passed its expiry is still counted as `pending`
+/// until it is refunded.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct StatusCounts {
+    pub pending: u64,
+    pub spent: u64,
+    pub refunded: u64,
+    pub expired: u64,
+}
+
+impl StatusCounts {
+    fn slot(&mut self, status: EscrowStatus) -> &mut u64 {
+        match status {
+            EscrowStatus::Pending => &mut self.pending,
+            EscrowStatus::Spent => &mut self.spent,
+            EscrowStatus::Refunded => &mut self.refunded,
+            EscrowStatus::Expired => &mut self.expired,
+        }
+    }
+
+    /// Record one escrow moving from `from` (or newly created, if `None`) to `to`.
+    ///
+    /// Decrements saturate so escrows created before counting began do not
+    /// underflow their previous bucket.
+    pub fn transition(&mut self, from: Option<EscrowStatus>, to: EscrowStatus) {
+        if let Some(from) = from {
+            let slot = self.slot(from);
+            *slot = slot.saturating_sub(1);
+        }
+        let slot = self.slot(to);
+        *slot = slot.saturating_add(1);
+    }
+}
+
+/// Withdrawal fee configuration, set by the admin.
+///
+/// Rates are in basis points (1/100 of a percent) of the withdrawn amount.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FeeConfig {
+    /// Fee charged on withdrawals to accounts without privacy enabled.
+    pub fee_bps: u32,
+    /// Fee charged on withdrawals to accounts with privacy enabled. `None`
+    /// applies `fee_bps` (no discount).
+    pub fee_bps_private: Option<u32>,
+}
+
+/// Snapshot of an account's privacy settings and active escrows.
+///
+/// Returned by [`QuickexContract::export_account_state`] for off-chain backup or
+/// migration to another deployment.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AccountExport {
+    /// The exported account.
+    pub owner: Address,
+    /// Boolean privacy flag, as returned by `get_privacy`.
+    pub privacy_enabled: bool,
+    /// Numeric privacy level, as returned by `privacy_status`.
+    pub privacy_level: Option<u32>,
+    /// Privacy level history, as returned by `privacy_history`.
+    pub privacy_history: Vec<u32>,
+    /// Viewers the account has granted access to, in grant order.
+    pub viewers: Vec<Address>,
+    /// Commitments of the account's non-terminal escrows, in creation order.
+    pub active_escrows: Vec<BytesN<32>>,
+}
+
+/// Record of whether an escrow existed for a commitment at a given ledger.
+///
+/// Returned by [`QuickexContract::attest_no_escrow`], which also publishes it as a
+/// `NonExistenceChecked` event so the check is recorded on-chain.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NonExistenceAttestation {
+    /// The commitment that was checked.
+    pub commitment: BytesN<32>,
+    /// Ledger sequence at which the check was made.
+    pub ledger: u32,
+    /// Whether an escrow (in any status) existed for the commitment.
+    pub exists: bool,
+}
+
+/// Verified preimage of an escrow commitment.
+///
+/// Returned by [`QuickexContract::get_commitment_opening`] once
+/// `SHA256(owner || amount || salt)` has been checked against a stored escrow, for
+/// export to an external verifier.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommitmentOpening {
+    /// Address bound into the commitment.
+    pub owner: Address,
+    /// Escrowed amount bound into the commitment.
+    pub amount: i128,
+    /// Salt bound into the commitment.
+    pub salt: Bytes,
+    /// The commitment itself.
+    pub commitment: BytesN<32>,
+}
+
+/// Pooled escrow funded by any number of contributors.
+///
+/// Created by [`QuickexContract::open_pool`]. Contributors add funds until
+/// `release_at`, then each withdraws their own share with
+/// [`QuickexContract::withdraw_share`].
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Pool {
+    /// Token the pool holds.
+    pub token: Address,
+    /// Account that opened the pool.
+    pub creator: Address,
+    /// Ledger timestamp from which shares can be withdrawn and contributions stop.
+    pub release_at: u64,
+    /// Sum of all outstanding shares.
+    pub total: i128,
+}
+
+/// Record of the most recent contract upgrade.
+///
+/// Written by [`QuickexContract::upgrade`] and returned by
+/// [`QuickexContract::last_upgrade`], so clients can confirm which WASM is live
+/// without relying on the upgrade event.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UpgradeInfo {
+    /// Hash of the WASM the contract was upgraded to.
+    pub wasm_hash: BytesN<32>,
+    /// Ledger timestamp of the upgrade.
+    pub timestamp: u64,
+    /// Admin that performed the upgrade.
+    pub admin: Address,
+}
+
+/// Escrow statistics for a single owner.
+///
+/// Returned by [`QuickexContract::owner_stats`].
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OwnerStats {
+    /// Number of the owner's `Pending` and `Expired` escrows.
+    pub active_count: u32,
+    /// Number of escrows ever created for the owner.
+    pub total_created: u64,
+    /// Sum of the amounts held in the owner's active escrows, in each token's base
+    /// units.
+    pub total_value_locked: i128,
+}
+
+/// Point-in-time summary of escrow activity.
+///
+/// Recorded by [`QuickexContract::snapshot_activity`] and returned by
+/// [`QuickexContract::get_snapshots`].
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ActivitySnapshot {
+    /// Ledger timestamp the snapshot was taken at.
+    pub timestamp: u64,
+    /// Number of escrows ever created (the escrow counter).
+    pub total_escrows: u64,
+    /// Number of `Pending` and `Expired` escrows.
+    pub active_escrows: u64,
+    /// Sum of the TVL of every known token, in each token's base units.
+    pub tvl_sum: i128,
+}
+
+/// Optional per-escrow settings supplied at deposit time.
+///
+/// Passed to [`QuickexContract::deposit_with_options`]. The [`Default`] value
+/// reproduces the behaviour of a plain `deposit`.
+#[contracttype]
+#[derive(Clone, Default)]
+pub struct EscrowOptions {
+    /// Seconds added to `expires_at` each time the escrow is withdrawn from, so an
+    /// actively-used escrow does not lapse. `0` disables the extension, and escrows
+    /// without an expiry are never extended.
+    pub extend_on_withdraw: u64,
+    /// Restrict withdrawals to a single recipient address. `None` allows any
+    /// recipient that can prove the commitment.
+    pub allowed_recipient: Option<Address>,
+    /// Number of ledgers that must close after the deposit before it can be
+    /// withdrawn. `0` allows withdrawal in the deposit ledger.
+    pub min_confirmations: u32,
+    /// Forward the funds to this address once the escrow expires, instead of
+    /// allowing the owner to refund. `None` keeps normal refund behaviour.
+    pub on_expiry_recipient: Option<Address>,
+    /// Release the escrow in equal installments, one per unlock timestamp
+    /// (ascending, at most 50). Such escrows are withdrawn with `withdraw_matured`
+    /// to `allowed_recipient`, or to the owner if none is set.
+    pub installments: Option<Vec<u64>>,
+    /// Cap on how many times the owner may push the expiry out with
+    /// `extend_timeout`, giving the counterparty a firm deadline. `None` allows
+    /// unlimited extensions.
+    pub max_extensions: Option<u32>,
+    /// Seconds after the deposit before the escrow can be withdrawn at all. Must be
+    /// shorter than the timeout when one is set. `0` disables the cliff.
+    pub cliff_secs: u64,
+    /// Chain this escrow after another: it cannot be withdrawn until the escrow
+    /// with this commitment has been spent. `None` leaves it unchained.
+    pub next_of: Option<BytesN<32>>,
 }
 
 /// Privacy-aware view of an escrow entry.
 ///
 /// Returned by [`QuickexContract::get_escrow_details`] instead of the raw
-/// [`EscrowEntry`]. Sensitive fields (`amount`, `owner`) are set to `None`
-/// when the escrow owner has privacy enabled and the caller is not the owner.
+/// [`EscrowEntry`]. Fields are set to `None` according to the privacy level
+/// that applies to the caller: the owner's numeric privacy level, or level 2 if
+/// only the boolean privacy flag is on (the higher of the two wins). The owner
+/// and viewers the owner has granted access to always see level 0.
 ///
 /// ## Field visibility
 ///
-/// | Field        | Privacy off | Privacy on + caller is owner | Privacy on + caller is stranger |
-/// |--------------|-------------|------------------------------|---------------------------------|
-/// | `token`      | ✓           | ✓                            | ✓                               |
-/// | `status`     | ✓           | ✓                            | ✓                               |
-/// | `created_at` | ✓           | ✓                            | ✓                               |
-/// | `expires_at` | ✓           | ✓                            | ✓                               |
-/// | `amount`     | ✓           | ✓                            | `None`                          |
-/// | `owner`      | ✓           | ✓                            | `None`                          |
+/// | Field        | Level 0 | Level 1 | Level 2 | Level 3 and above |
+/// |--------------|---------|---------|---------|-------------------|
+/// | `status`     | ✓       | ✓       | ✓       | ✓                 |
+/// | `token`      | ✓       | ✓       | ✓       | `None`            |
+/// | `created_at` | ✓       | ✓       | ✓       | `None`            |
+/// | `expires_at` | ✓       | ✓       | ✓       | `None`            |
+/// | `owner`      | ✓       | ✓       | `None`  | `None`            |
+/// | `amount`     | ✓       | `None`  | `None`  | `None`            |
 #[contracttype]
 #[derive(Clone)]
 pub struct PrivacyAwareEscrowView {
-    /// Token contract address (always visible).
-    pub token: Address,
-    /// Escrowed amount. `None` when privacy is enabled and caller is not the owner.
+    /// Token contract address. `None` at privacy level 3.
+    pub token: Option<Address>,
+    /// Escrowed amount. `None` at privacy level 1 and above.
     pub amount: Option<i128>,
-    /// Owner address. `None` when privacy is enabled and caller is not the owner.
+    /// Owner address. `None` at privacy level 2 and above.
     pub owner: Option<Address>,
     /// Current lifecycle status (always visible).
     pub status: EscrowStatus,
-    /// Creation timestamp (always visible).
-    pub created_at: u64,
-    /// Expiry timestamp; `0` means no expiry (always visible).
-    pub expires_at: u64,
+    /// Creation timestamp. `None` at privacy level 3.
+    pub created_at: Option<u64>,
+    /// Expiry timestamp; `0` means no expiry. `None` at privacy level 3.
+    pub expires_at: Option<u64>,
+}
+
+impl PrivacyAwareEscrowView {
+    /// Build a view of `entry` with the fields hidden at privacy `level`.
+    pub fn from_entry(entry: EscrowEntry, level: u32) -> Self {
+        let metadata_visible = level < 3;
+        Self {
+            token: metadata_visible.then_some(entry.token),
+            amount: (level < AMOUNT_HIDDEN_LEVEL).then_some(entry.amount),
+            owner: (level < OWNER_HIDDEN_LEVEL).then_some(entry.owner),
+            status: entry.status,
+            created_at: metadata_visible.then_some(entry.created_at),
+            expires_at: metadata_visible.then_some(entry.expires_at),
+        }
+    }
 }